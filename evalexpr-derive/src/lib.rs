@@ -0,0 +1,133 @@
+//! Derive macros for [evalexpr](https://docs.rs/evalexpr).
+//!
+//! `#[derive(IntoConfiguration)]` implements `evalexpr::IntoConfiguration` for structs with named fields.
+//! Each field becomes a variable with the name of the field, converted to a `Value` with `Into<Value>`.
+//! Fields marked with `#[evalexpr(skip)]` are left out.
+//!
+//! ```rust
+//! extern crate evalexpr;
+//! #[macro_use]
+//! extern crate evalexpr_derive;
+//!
+//! use evalexpr::{eval_with_configuration, IntoConfiguration, Value};
+//!
+//! #[derive(IntoConfiguration)]
+//! struct Point {
+//!     x: i64,
+//!     y: i64,
+//!     #[evalexpr(skip)]
+//!     _label: (),
+//! }
+//!
+//! fn main() {
+//!     let configuration = Point { x: 3, y: 4, _label: () }.into_configuration();
+//!     assert_eq!(eval_with_configuration("x * y", &configuration), Ok(Value::Int(12)));
+//! }
+//! ```
+
+extern crate proc_macro;
+extern crate proc_macro2;
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::{
+    parse_macro_input, parse_quote, spanned::Spanned, Data, DeriveInput, Error, Field, Fields,
+};
+
+#[proc_macro_derive(IntoConfiguration, attributes(evalexpr))]
+pub fn derive_into_configuration(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    into_configuration_impl(&input)
+        .unwrap_or_else(compile_error)
+        .into()
+}
+
+/// Converts the error to `compile_error!` invocations.
+/// Unlike `Error::into_compile_error`, these do not refer to `::core`, which is not in scope in crates of the 2015 edition.
+fn compile_error(error: Error) -> TokenStream2 {
+    error
+        .into_iter()
+        .map(|error| {
+            let message = error.to_string();
+            quote_spanned!(error.span()=> compile_error!(#message);)
+        })
+        .collect()
+}
+
+fn into_configuration_impl(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new_spanned(
+                    &input.ident,
+                    "IntoConfiguration can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "IntoConfiguration can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut insertions = Vec::new();
+    let mut generics = input.generics.clone();
+    // Fields of concrete types are checked in the generated function, which points to the field with the unsupported type
+    let is_generic = input.generics.type_params().next().is_some();
+    for field in fields {
+        if is_skipped(field)? {
+            continue;
+        }
+        let ident = field.ident.as_ref().expect("named fields have identifiers");
+        let ty = &field.ty;
+        let name = ident.to_string();
+        let name = name.trim_start_matches("r#");
+        // Errors about fields that cannot be converted to values point to the type of the field
+        insertions.push(quote_spanned! {ty.span()=>
+            configuration.insert_variable(#name.into(), self.#ident);
+        });
+        if is_generic {
+            generics
+                .make_where_clause()
+                .predicates
+                .push(parse_quote!(#ty: Into<::evalexpr::Value>));
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::evalexpr::IntoConfiguration for #ident #ty_generics #where_clause {
+            fn into_configuration(self) -> ::evalexpr::HashMapConfiguration {
+                let mut configuration = ::evalexpr::HashMapConfiguration::new();
+                #(#insertions)*
+                configuration
+            }
+        }
+    })
+}
+
+/// Returns true if the field is marked with `#[evalexpr(skip)]`.
+fn is_skipped(field: &Field) -> Result<bool, Error> {
+    let mut skipped = false;
+    for attribute in &field.attrs {
+        if !attribute.path().is_ident("evalexpr") {
+            continue;
+        }
+        attribute.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skipped = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported evalexpr attribute, expected `skip`"))
+            }
+        })?;
+    }
+    Ok(skipped)
+}
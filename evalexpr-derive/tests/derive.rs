@@ -0,0 +1,62 @@
+extern crate evalexpr;
+#[macro_use]
+extern crate evalexpr_derive;
+extern crate trybuild;
+
+use evalexpr::{eval_with_configuration, Configuration, IntoConfiguration, Value};
+
+#[derive(IntoConfiguration)]
+struct Order {
+    quantity: i64,
+    price: f64,
+    express: bool,
+    customer: String,
+    #[evalexpr(skip)]
+    _internal_id: u128,
+}
+
+#[derive(IntoConfiguration)]
+struct Wrapper<T> {
+    value: T,
+}
+
+#[test]
+fn test_into_configuration() {
+    let configuration = Order {
+        quantity: 3,
+        price: 2.5,
+        express: true,
+        customer: "Alice".to_string(),
+        _internal_id: 42,
+    }
+    .into_configuration();
+
+    assert_eq!(
+        eval_with_configuration("quantity * price", &configuration),
+        Ok(Value::Float(7.5))
+    );
+    assert_eq!(
+        eval_with_configuration("express && customer == 'Alice'", &configuration),
+        Ok(Value::Boolean(true))
+    );
+    assert_eq!(
+        configuration.variable_names(),
+        vec!["customer", "express", "price", "quantity"]
+    );
+}
+
+#[test]
+fn test_generic_into_configuration() {
+    let configuration = Wrapper { value: 4 }.into_configuration();
+    assert_eq!(
+        eval_with_configuration("value + 1", &configuration),
+        Ok(Value::Int(5))
+    );
+}
+
+#[test]
+fn test_ui() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/pass_*.rs");
+    cases.compile_fail("tests/ui/fail_*.rs");
+}
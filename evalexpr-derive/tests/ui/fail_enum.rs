@@ -0,0 +1,9 @@
+#[macro_use]
+extern crate evalexpr_derive;
+
+#[derive(IntoConfiguration)]
+enum Shape {
+    Circle { radius: f64 },
+}
+
+fn main() {}
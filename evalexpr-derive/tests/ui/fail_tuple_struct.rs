@@ -0,0 +1,7 @@
+#[macro_use]
+extern crate evalexpr_derive;
+
+#[derive(IntoConfiguration)]
+struct Point(i64, i64);
+
+fn main() {}
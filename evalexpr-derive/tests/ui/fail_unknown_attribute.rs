@@ -0,0 +1,10 @@
+#[macro_use]
+extern crate evalexpr_derive;
+
+#[derive(IntoConfiguration)]
+struct Point {
+    #[evalexpr(rename = "y")]
+    x: i64,
+}
+
+fn main() {}
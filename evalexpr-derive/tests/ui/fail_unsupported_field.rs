@@ -0,0 +1,15 @@
+extern crate evalexpr;
+#[macro_use]
+extern crate evalexpr_derive;
+
+struct Color;
+
+#[derive(IntoConfiguration)]
+struct Pixel {
+    x: i64,
+    color: Color,
+}
+
+fn main() {
+    let _ = Pixel { x: 0, color: Color };
+}
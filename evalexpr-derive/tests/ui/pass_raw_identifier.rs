@@ -0,0 +1,18 @@
+extern crate evalexpr;
+#[macro_use]
+extern crate evalexpr_derive;
+
+use evalexpr::{eval_with_configuration, IntoConfiguration, Value};
+
+#[derive(IntoConfiguration)]
+struct Keywords {
+    r#type: i64,
+}
+
+fn main() {
+    let configuration = Keywords { r#type: 1 }.into_configuration();
+    assert_eq!(
+        eval_with_configuration("type", &configuration),
+        Ok(Value::Int(1))
+    );
+}
@@ -1,11 +1,235 @@
 use crate::value::Value;
-use function::Function;
-use std::collections::HashMap;
+use error::Error;
+#[cfg(feature = "async")]
+use function::AsyncFunction;
+use function::{Function, FunctionWithContext};
+#[cfg(feature = "lru")]
+use lru::LruCache;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(not(feature = "std"))]
+use std::collections::BTreeMap as Map;
+#[cfg(feature = "std")]
+use std::collections::HashMap as Map;
+use std::{
+    any::Any,
+    boxed::Box,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+#[cfg(feature = "lru")]
+use std::{cell::RefCell, num::NonZeroUsize};
 
 pub trait Configuration {
     fn get_value(&self, identifier: &str) -> Option<&Value>;
 
+    /// Returns the value of the given variable, or `Value::Null` if it does not exist.
+    fn get_value_or_null(&self, identifier: &str) -> Value {
+        self.get_value(identifier).cloned().unwrap_or(Value::Null)
+    }
+
+    /// Returns the value of the given variable, or the given default if it does not exist.
+    fn get_value_or_default(&self, identifier: &str, default: Value) -> Value {
+        self.get_value(identifier).cloned().unwrap_or(default)
+    }
+
     fn get_function(&self, identifier: &str) -> Option<&Function>;
+
+    /// Returns the identifiers of all variables defined by this configuration.
+    /// Configurations that cannot enumerate their variables return an empty vector.
+    fn variable_names(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns the identifiers of all functions defined by this configuration.
+    /// Configurations that cannot enumerate their functions return an empty vector.
+    fn function_names(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Returns the maximum nesting depth of expressions evaluated with this configuration, or `None` if the depth is unlimited.
+    /// Evaluating a deeper expression fails with `Error::MaxDepthExceeded` instead of overflowing the stack.
+    fn max_depth(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the maximum amount of nodes of expressions evaluated with this configuration, or `None` if the amount is unlimited.
+    /// Evaluating a larger expression fails with `Error::MaxNodeCountExceeded` before any node is evaluated.
+    fn max_node_count(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the maximum length in bytes of strings created by expressions evaluated with this configuration, or `None` if the length is unlimited.
+    /// Creating a longer string fails with `Error::StringTooLong`.
+    /// Repeated strings are limited to 2^30 bytes even if the length is unlimited.
+    fn max_string_length(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns the maximum length of arrays created by expressions evaluated with this configuration, or `None` if the length is unlimited.
+    /// Creating a longer array fails with `Error::ArrayTooLong`.
+    /// Ranges are limited to 2^24 elements even if the length is unlimited.
+    fn max_array_length(&self) -> Option<usize> {
+        None
+    }
+
+    /// Called when an evaluated expression reads the given variable, with the value that was found for it.
+    /// Does nothing by default.
+    fn on_variable_access(&self, _identifier: &str, _value: Option<&Value>) {}
+
+    /// Called when an evaluated expression calls the given function with the given arguments, before the function is looked up.
+    /// This includes builtin functions and functions called by higher-order functions such as `map`.
+    /// Does nothing by default.
+    fn on_function_call(&self, _identifier: &str, _arguments: &[Value]) {}
+
+    /// Returns a configuration that looks up variables and functions in `self` first, and in `fallback` if they are not found.
+    fn chain<B: Configuration>(self, fallback: B) -> ChainedConfiguration<Self, B>
+    where
+        Self: Sized,
+    {
+        ChainedConfiguration::new(self, fallback)
+    }
+}
+
+/// A configuration whose variables can be assigned by the evaluated expression, such as in `a = 3; a + 2`.
+pub trait MutableConfiguration: Configuration {
+    /// Sets the value of the given variable, creating it if it does not exist.
+    fn set_variable(&mut self, identifier: &str, value: Value);
+
+    /// Called before the evaluation of the body of an `if`-`else` expression, such that variables assigned in the body can be discarded afterwards.
+    /// Does nothing by default, such that assigned variables remain visible.
+    fn push_scope(&mut self) {}
+
+    /// Called after the evaluation of the body of an `if`-`else` expression, also if it failed, to discard the scope created by `push_scope`.
+    /// Does nothing by default.
+    fn pop_scope(&mut self) {}
+}
+
+/// Converts a value into a configuration whose variables are its fields.
+/// This can be derived for structs with named fields with `#[derive(IntoConfiguration)]` from the `evalexpr-derive` crate.
+pub trait IntoConfiguration {
+    fn into_configuration(self) -> HashMapConfiguration;
+}
+
+/// A configuration that additionally provides functions whose results are computed asynchronously.
+/// Async functions can only be called from expressions evaluated with `eval_async`.
+#[cfg(feature = "async")]
+pub trait AsyncConfiguration: Configuration {
+    fn get_async_function(&self, identifier: &str) -> Option<&AsyncFunction>;
+}
+
+impl<C: Configuration + ?Sized> Configuration for &C {
+    fn get_value(&self, identifier: &str) -> Option<&Value> {
+        (**self).get_value(identifier)
+    }
+
+    fn get_function(&self, identifier: &str) -> Option<&Function> {
+        (**self).get_function(identifier)
+    }
+
+    fn variable_names(&self) -> Vec<String> {
+        (**self).variable_names()
+    }
+
+    fn function_names(&self) -> Vec<String> {
+        (**self).function_names()
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        (**self).max_depth()
+    }
+
+    fn max_node_count(&self) -> Option<usize> {
+        (**self).max_node_count()
+    }
+
+    fn max_string_length(&self) -> Option<usize> {
+        (**self).max_string_length()
+    }
+
+    fn max_array_length(&self) -> Option<usize> {
+        (**self).max_array_length()
+    }
+
+    fn on_variable_access(&self, identifier: &str, value: Option<&Value>) {
+        (**self).on_variable_access(identifier, value)
+    }
+
+    fn on_function_call(&self, identifier: &str, arguments: &[Value]) {
+        (**self).on_function_call(identifier, arguments)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<C: AsyncConfiguration + ?Sized> AsyncConfiguration for &C {
+    fn get_async_function(&self, identifier: &str) -> Option<&AsyncFunction> {
+        (**self).get_async_function(identifier)
+    }
+}
+
+/// Allows storing configurations as `Box<dyn Configuration>` and still using them wherever a configuration is expected, such as in `chain`.
+impl<C: Configuration + ?Sized> Configuration for Box<C> {
+    fn get_value(&self, identifier: &str) -> Option<&Value> {
+        (**self).get_value(identifier)
+    }
+
+    fn get_function(&self, identifier: &str) -> Option<&Function> {
+        (**self).get_function(identifier)
+    }
+
+    fn variable_names(&self) -> Vec<String> {
+        (**self).variable_names()
+    }
+
+    fn function_names(&self) -> Vec<String> {
+        (**self).function_names()
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        (**self).max_depth()
+    }
+
+    fn max_node_count(&self) -> Option<usize> {
+        (**self).max_node_count()
+    }
+
+    fn max_string_length(&self) -> Option<usize> {
+        (**self).max_string_length()
+    }
+
+    fn max_array_length(&self) -> Option<usize> {
+        (**self).max_array_length()
+    }
+
+    fn on_variable_access(&self, identifier: &str, value: Option<&Value>) {
+        (**self).on_variable_access(identifier, value)
+    }
+
+    fn on_function_call(&self, identifier: &str, arguments: &[Value]) {
+        (**self).on_function_call(identifier, arguments)
+    }
+}
+
+impl<C: MutableConfiguration + ?Sized> MutableConfiguration for Box<C> {
+    fn set_variable(&mut self, identifier: &str, value: Value) {
+        (**self).set_variable(identifier, value)
+    }
+
+    fn push_scope(&mut self) {
+        (**self).push_scope()
+    }
+
+    fn pop_scope(&mut self) {
+        (**self).pop_scope()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<C: AsyncConfiguration + ?Sized> AsyncConfiguration for Box<C> {
+    fn get_async_function(&self, identifier: &str) -> Option<&AsyncFunction> {
+        (**self).get_async_function(identifier)
+    }
 }
 
 pub struct EmptyConfiguration;
@@ -18,28 +242,128 @@ impl Configuration for EmptyConfiguration {
     fn get_function(&self, _identifier: &str) -> Option<&Function> {
         None
     }
+
+    /// Limits the depth to a value that is safe for the default stack size.
+    fn max_depth(&self) -> Option<usize> {
+        Some(512)
+    }
 }
 
+/// A configuration that stores its variables and functions in hash maps.
+/// If the `std` feature is disabled, the variables and functions are stored in B-tree maps instead.
+#[derive(Clone, Default)]
 pub struct HashMapConfiguration {
-    variables: HashMap<String, Value>,
-    functions: HashMap<String, Function>,
+    variables: Map<String, Value>,
+    functions: Map<String, Function>,
+    functions_with_context: Map<String, FunctionWithContext>,
+    #[cfg(feature = "async")]
+    async_functions: Map<String, AsyncFunction>,
+    max_depth: Option<usize>,
+    max_node_count: Option<usize>,
+    max_string_length: Option<usize>,
+    max_array_length: Option<usize>,
 }
 
 impl HashMapConfiguration {
     pub fn new() -> Self {
-        Self {
-            variables: Default::default(),
-            functions: Default::default(),
-        }
+        Self::default()
+    }
+
+    /// Returns a builder that creates a configuration by chaining calls, such as `HashMapConfiguration::builder().var("a", 1).build()`.
+    pub fn builder() -> ConfigurationBuilder {
+        ConfigurationBuilder::default()
     }
 
-    pub fn insert_variable(&mut self, identifier: String, value: Value) {
-        self.variables.insert(identifier, value);
+    pub fn insert_variable<V: Into<Value>>(&mut self, identifier: String, value: V) {
+        self.variables.insert(identifier, value.into());
     }
 
+    /// Inserts the given function.
+    /// If the function has no name, it is named after the given identifier.
     pub fn insert_function(&mut self, identifier: String, function: Function) {
+        let function = match function.name() {
+            Some(_) => function,
+            None => Function::with_name(&identifier, function),
+        };
         self.functions.insert(identifier, function);
     }
+
+    /// Inserts the given function with context.
+    /// Functions with context can only be called when evaluating with a context, such as with `eval_with_configuration_and_context`.
+    pub fn insert_function_with_context(
+        &mut self,
+        identifier: String,
+        function: FunctionWithContext,
+    ) {
+        self.functions_with_context.insert(identifier, function);
+    }
+
+    #[cfg(feature = "async")]
+    pub fn insert_async_function(&mut self, identifier: String, function: AsyncFunction) {
+        self.async_functions.insert(identifier, function);
+    }
+
+    /// Limits the nesting depth of expressions evaluated with this configuration.
+    pub fn set_max_depth(&mut self, depth: usize) {
+        self.max_depth = Some(depth);
+    }
+
+    /// Limits the amount of nodes of expressions evaluated with this configuration.
+    pub fn set_max_node_count(&mut self, node_count: usize) {
+        self.max_node_count = Some(node_count);
+    }
+
+    /// Limits the length in bytes of strings created by expressions evaluated with this configuration.
+    pub fn set_max_string_length(&mut self, length: usize) {
+        self.max_string_length = Some(length);
+    }
+
+    /// Limits the length of arrays created by expressions evaluated with this configuration.
+    pub fn set_max_array_length(&mut self, length: usize) {
+        self.max_array_length = Some(length);
+    }
+
+    /// Inserts all variables and functions of `other` into this configuration.
+    /// On conflicts, the entries of `other` replace the existing ones.
+    pub fn extend(&mut self, other: &HashMapConfiguration) {
+        self.variables.extend(
+            other
+                .variables
+                .iter()
+                .map(|(identifier, value)| (identifier.clone(), value.clone())),
+        );
+        self.functions.extend(
+            other
+                .functions
+                .iter()
+                .map(|(identifier, function)| (identifier.clone(), function.clone())),
+        );
+        self.functions_with_context.extend(
+            other
+                .functions_with_context
+                .iter()
+                .map(|(identifier, function)| (identifier.clone(), function.clone())),
+        );
+        #[cfg(feature = "async")]
+        self.async_functions.extend(
+            other
+                .async_functions
+                .iter()
+                .map(|(identifier, function)| (identifier.clone(), function.clone())),
+        );
+    }
+
+    /// Returns a configuration with all variables and functions of this configuration and `other`.
+    /// On conflicts, the entries of `other` are used.
+    pub fn merge(mut self, other: HashMapConfiguration) -> HashMapConfiguration {
+        self.variables.extend(other.variables);
+        self.functions.extend(other.functions);
+        self.functions_with_context
+            .extend(other.functions_with_context);
+        #[cfg(feature = "async")]
+        self.async_functions.extend(other.async_functions);
+        self
+    }
 }
 
 impl Configuration for HashMapConfiguration {
@@ -50,4 +374,553 @@ impl Configuration for HashMapConfiguration {
     fn get_function(&self, identifier: &str) -> Option<&Function> {
         self.functions.get(identifier)
     }
+
+    /// Returns the identifiers of all variables, sorted alphabetically.
+    fn variable_names(&self) -> Vec<String> {
+        sorted_keys(&self.variables)
+    }
+
+    /// Returns the identifiers of all functions, sorted alphabetically.
+    fn function_names(&self) -> Vec<String> {
+        sorted_keys(&self.functions)
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    fn max_node_count(&self) -> Option<usize> {
+        self.max_node_count
+    }
+
+    fn max_string_length(&self) -> Option<usize> {
+        self.max_string_length
+    }
+
+    fn max_array_length(&self) -> Option<usize> {
+        self.max_array_length
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncConfiguration for HashMapConfiguration {
+    fn get_async_function(&self, identifier: &str) -> Option<&AsyncFunction> {
+        self.async_functions.get(identifier)
+    }
+}
+
+fn sorted_keys<V>(map: &Map<String, V>) -> Vec<String> {
+    let mut keys: Vec<String> = map.keys().cloned().collect();
+    keys.sort();
+    keys
+}
+
+impl MutableConfiguration for HashMapConfiguration {
+    fn set_variable(&mut self, identifier: &str, value: Value) {
+        self.variables.insert(identifier.to_string(), value);
+    }
+}
+
+/// Builds a `HashMapConfiguration` by chaining calls.
+#[derive(Clone, Default)]
+pub struct ConfigurationBuilder {
+    configuration: HashMapConfiguration,
+}
+
+impl ConfigurationBuilder {
+    /// Adds the given variable.
+    pub fn var<V: Into<Value>>(mut self, identifier: &str, value: V) -> Self {
+        self.configuration
+            .insert_variable(identifier.to_string(), value);
+        self
+    }
+
+    /// Adds a function that accepts `argument_amount` arguments.
+    pub fn func<F>(mut self, identifier: &str, argument_amount: usize, function: F) -> Self
+    where
+        F: Fn(&[Value]) -> Result<Value, Error> + Send + Sync + 'static,
+    {
+        self.configuration.insert_function(
+            identifier.to_string(),
+            Function::new(argument_amount, Box::new(function)),
+        );
+        self
+    }
+
+    pub fn build(self) -> HashMapConfiguration {
+        self.configuration
+    }
+}
+
+/// A configuration that stores its variables in a stack of scopes, such that variables assigned in the body of an `if`-`else` expression do not leak out of it.
+/// Variables are looked up from the innermost scope to the outermost one, and assigned in the innermost scope, where they shadow variables of outer scopes.
+/// It has no functions, but can be chained with a configuration that has, as in `ScopedConfiguration::new().chain(functions)`.
+///
+/// The outermost scope always exists and holds the variables that remain after the evaluation.
+#[derive(Clone, Debug)]
+pub struct ScopedConfiguration {
+    scopes: Vec<Map<String, Value>>,
+}
+
+impl ScopedConfiguration {
+    /// Creates a configuration with an empty outermost scope.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts the variable into the innermost scope.
+    pub fn insert_variable<V: Into<Value>>(&mut self, identifier: String, value: V) {
+        self.scopes
+            .last_mut()
+            .expect("the outermost scope is never removed")
+            .insert(identifier, value.into());
+    }
+
+    /// Returns the amount of scopes, which is one if only the outermost scope exists.
+    pub fn scope_count(&self) -> usize {
+        self.scopes.len()
+    }
+}
+
+impl Default for ScopedConfiguration {
+    fn default() -> Self {
+        Self {
+            scopes: vec![Map::new()],
+        }
+    }
+}
+
+impl Configuration for ScopedConfiguration {
+    fn get_value(&self, identifier: &str) -> Option<&Value> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(identifier))
+    }
+
+    fn get_function(&self, _identifier: &str) -> Option<&Function> {
+        None
+    }
+
+    /// Returns the identifiers of the variables of all scopes, sorted alphabetically.
+    fn variable_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .scopes
+            .iter()
+            .flat_map(|scope| scope.keys().cloned())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+impl MutableConfiguration for ScopedConfiguration {
+    fn set_variable(&mut self, identifier: &str, value: Value) {
+        self.insert_variable(identifier.to_string(), value);
+    }
+
+    fn push_scope(&mut self) {
+        self.scopes.push(Map::new());
+    }
+
+    /// Removes the innermost scope with its variables, unless it is the outermost scope.
+    fn pop_scope(&mut self) {
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+}
+
+/// A configuration made of two layers.
+/// Variables and functions are looked up in the front layer first, and in the fallback layer if they are not found.
+#[derive(Clone)]
+pub struct ChainedConfiguration<A, B> {
+    front: A,
+    fallback: B,
+}
+
+impl<A: Configuration, B: Configuration> ChainedConfiguration<A, B> {
+    pub fn new(front: A, fallback: B) -> Self {
+        Self { front, fallback }
+    }
+}
+
+impl<A: Configuration, B: Configuration> Configuration for ChainedConfiguration<A, B> {
+    fn get_value(&self, identifier: &str) -> Option<&Value> {
+        self.front
+            .get_value(identifier)
+            .or_else(|| self.fallback.get_value(identifier))
+    }
+
+    fn get_function(&self, identifier: &str) -> Option<&Function> {
+        self.front
+            .get_function(identifier)
+            .or_else(|| self.fallback.get_function(identifier))
+    }
+
+    /// Returns the identifiers of the variables of both layers, sorted alphabetically and without duplicates.
+    fn variable_names(&self) -> Vec<String> {
+        merge_names(self.front.variable_names(), self.fallback.variable_names())
+    }
+
+    /// Returns the identifiers of the functions of both layers, sorted alphabetically and without duplicates.
+    fn function_names(&self) -> Vec<String> {
+        merge_names(self.front.function_names(), self.fallback.function_names())
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        self.front.max_depth().or_else(|| self.fallback.max_depth())
+    }
+
+    fn max_node_count(&self) -> Option<usize> {
+        self.front
+            .max_node_count()
+            .or_else(|| self.fallback.max_node_count())
+    }
+
+    fn max_string_length(&self) -> Option<usize> {
+        self.front
+            .max_string_length()
+            .or_else(|| self.fallback.max_string_length())
+    }
+
+    fn max_array_length(&self) -> Option<usize> {
+        self.front
+            .max_array_length()
+            .or_else(|| self.fallback.max_array_length())
+    }
+
+    /// Notifies both layers.
+    fn on_variable_access(&self, identifier: &str, value: Option<&Value>) {
+        self.front.on_variable_access(identifier, value);
+        self.fallback.on_variable_access(identifier, value);
+    }
+
+    /// Notifies both layers.
+    fn on_function_call(&self, identifier: &str, arguments: &[Value]) {
+        self.front.on_function_call(identifier, arguments);
+        self.fallback.on_function_call(identifier, arguments);
+    }
+}
+
+#[cfg(feature = "async")]
+impl<A: AsyncConfiguration, B: AsyncConfiguration> AsyncConfiguration
+    for ChainedConfiguration<A, B>
+{
+    fn get_async_function(&self, identifier: &str) -> Option<&AsyncFunction> {
+        self.front
+            .get_async_function(identifier)
+            .or_else(|| self.fallback.get_async_function(identifier))
+    }
+}
+
+fn merge_names(mut front: Vec<String>, fallback: Vec<String>) -> Vec<String> {
+    front.extend(fallback);
+    front.sort();
+    front.dedup();
+    front
+}
+
+/// Assigned variables are written to the front layer, where they shadow the variables of the fallback layer.
+impl<A: MutableConfiguration, B: Configuration> MutableConfiguration
+    for ChainedConfiguration<A, B>
+{
+    fn set_variable(&mut self, identifier: &str, value: Value) {
+        self.front.set_variable(identifier, value);
+    }
+
+    fn push_scope(&mut self) {
+        self.front.push_scope()
+    }
+
+    fn pop_scope(&mut self) {
+        self.front.pop_scope()
+    }
+}
+
+/// A hash map configuration together with a context that is passed to its functions with context.
+/// Functions without context take precedence over functions with context with the same identifier.
+pub struct ConfigurationWithContext<'a> {
+    configuration: &'a HashMapConfiguration,
+    functions: Map<String, Function>,
+}
+
+impl<'a> ConfigurationWithContext<'a> {
+    /// Binds the given context to the functions with context of the configuration.
+    pub fn new(
+        configuration: &'a HashMapConfiguration,
+        context: Arc<dyn Any + Send + Sync>,
+    ) -> Self {
+        let functions = configuration
+            .functions_with_context
+            .iter()
+            .map(|(identifier, function)| {
+                (
+                    identifier.clone(),
+                    function.bind(identifier, context.clone()),
+                )
+            })
+            .collect();
+        Self {
+            configuration,
+            functions,
+        }
+    }
+}
+
+impl<'a> Configuration for ConfigurationWithContext<'a> {
+    fn get_value(&self, identifier: &str) -> Option<&Value> {
+        self.configuration.get_value(identifier)
+    }
+
+    fn get_function(&self, identifier: &str) -> Option<&Function> {
+        self.configuration
+            .get_function(identifier)
+            .or_else(|| self.functions.get(identifier))
+    }
+
+    fn variable_names(&self) -> Vec<String> {
+        self.configuration.variable_names()
+    }
+
+    /// Returns the identifiers of the functions with and without context, sorted alphabetically and without duplicates.
+    fn function_names(&self) -> Vec<String> {
+        merge_names(
+            self.configuration.function_names(),
+            sorted_keys(&self.functions),
+        )
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        self.configuration.max_depth()
+    }
+
+    fn max_node_count(&self) -> Option<usize> {
+        self.configuration.max_node_count()
+    }
+
+    fn max_string_length(&self) -> Option<usize> {
+        self.configuration.max_string_length()
+    }
+
+    fn max_array_length(&self) -> Option<usize> {
+        self.configuration.max_array_length()
+    }
+}
+
+/// A callback that is notified of a variable access, with the identifier of the variable and the value that was found for it.
+pub type VariableAccessCallback = Arc<dyn Fn(&str, Option<&Value>) + Send + Sync>;
+
+/// A callback that is notified of a function call, with the identifier of the function and the arguments.
+pub type FunctionCallCallback = Arc<dyn Fn(&str, &[Value]) + Send + Sync>;
+
+/// A configuration that notifies callbacks of all variable accesses and function calls of evaluated expressions, for example to audit them.
+/// Everything else is delegated to the inner configuration, which is notified as well.
+///
+/// The callbacks cannot deny an access.
+/// To restrict which variables and functions can be used, implement `Configuration` instead.
+#[derive(Clone)]
+pub struct AuditingConfiguration<C> {
+    inner: C,
+    on_variable_access: VariableAccessCallback,
+    on_function_call: FunctionCallCallback,
+}
+
+impl<C: Configuration> AuditingConfiguration<C> {
+    pub fn new(
+        inner: C,
+        on_variable_access: VariableAccessCallback,
+        on_function_call: FunctionCallCallback,
+    ) -> Self {
+        Self {
+            inner,
+            on_variable_access,
+            on_function_call,
+        }
+    }
+
+    /// Returns the wrapped configuration.
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: Configuration> Configuration for AuditingConfiguration<C> {
+    fn get_value(&self, identifier: &str) -> Option<&Value> {
+        self.inner.get_value(identifier)
+    }
+
+    fn get_function(&self, identifier: &str) -> Option<&Function> {
+        self.inner.get_function(identifier)
+    }
+
+    fn variable_names(&self) -> Vec<String> {
+        self.inner.variable_names()
+    }
+
+    fn function_names(&self) -> Vec<String> {
+        self.inner.function_names()
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        self.inner.max_depth()
+    }
+
+    fn max_node_count(&self) -> Option<usize> {
+        self.inner.max_node_count()
+    }
+
+    fn max_string_length(&self) -> Option<usize> {
+        self.inner.max_string_length()
+    }
+
+    fn max_array_length(&self) -> Option<usize> {
+        self.inner.max_array_length()
+    }
+
+    fn on_variable_access(&self, identifier: &str, value: Option<&Value>) {
+        self.inner.on_variable_access(identifier, value);
+        (self.on_variable_access)(identifier, value);
+    }
+
+    fn on_function_call(&self, identifier: &str, arguments: &[Value]) {
+        self.inner.on_function_call(identifier, arguments);
+        (self.on_function_call)(identifier, arguments);
+    }
+}
+
+impl<C: MutableConfiguration> MutableConfiguration for AuditingConfiguration<C> {
+    fn set_variable(&mut self, identifier: &str, value: Value) {
+        self.inner.set_variable(identifier, value)
+    }
+
+    fn push_scope(&mut self) {
+        self.inner.push_scope()
+    }
+
+    fn pop_scope(&mut self) {
+        self.inner.pop_scope()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<C: AsyncConfiguration> AsyncConfiguration for AuditingConfiguration<C> {
+    fn get_async_function(&self, identifier: &str) -> Option<&AsyncFunction> {
+        self.inner.get_async_function(identifier)
+    }
+}
+
+/// A configuration that caches the results of variable lookups in the configuration it borrows, for configurations where looking up a variable is expensive.
+/// The cache holds the results of the most recently used variables up to a fixed capacity, including variables that were not found.
+///
+/// Since configurations return references to their values, the inner configuration is borrowed for as long as the cache exists.
+/// If the inner configuration changes its variables through interior mutability, the affected entries must be invalidated.
+#[cfg(feature = "lru")]
+pub struct LruCachedConfiguration<'a, C: ?Sized> {
+    inner: &'a C,
+    cache: RefCell<LruCache<String, Option<&'a Value>>>,
+}
+
+#[cfg(feature = "lru")]
+impl<'a, C: Configuration + ?Sized> LruCachedConfiguration<'a, C> {
+    /// Creates an empty cache for the variables of the given configuration that holds up to `capacity` variables.
+    pub fn new(inner: &'a C, capacity: NonZeroUsize) -> Self {
+        Self {
+            inner,
+            cache: RefCell::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Removes the cached result of the given variable, such that it is looked up again on its next access.
+    pub fn invalidate(&self, identifier: &str) {
+        self.cache.borrow_mut().pop(identifier);
+    }
+
+    /// Removes all cached results.
+    pub fn invalidate_all(&self) {
+        self.cache.borrow_mut().clear();
+    }
+
+    /// Returns the wrapped configuration.
+    pub fn inner(&self) -> &'a C {
+        self.inner
+    }
+}
+
+#[cfg(feature = "lru")]
+impl<'a, C: Configuration + ?Sized> Configuration for LruCachedConfiguration<'a, C> {
+    fn get_value(&self, identifier: &str) -> Option<&Value> {
+        if let Some(value) = self.cache.borrow_mut().get(identifier) {
+            return *value;
+        }
+        let inner: &'a C = self.inner;
+        let value = inner.get_value(identifier);
+        self.cache.borrow_mut().put(identifier.to_string(), value);
+        value
+    }
+
+    fn get_function(&self, identifier: &str) -> Option<&Function> {
+        self.inner.get_function(identifier)
+    }
+
+    fn variable_names(&self) -> Vec<String> {
+        self.inner.variable_names()
+    }
+
+    fn function_names(&self) -> Vec<String> {
+        self.inner.function_names()
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        self.inner.max_depth()
+    }
+
+    fn max_node_count(&self) -> Option<usize> {
+        self.inner.max_node_count()
+    }
+
+    fn max_string_length(&self) -> Option<usize> {
+        self.inner.max_string_length()
+    }
+
+    fn max_array_length(&self) -> Option<usize> {
+        self.inner.max_array_length()
+    }
+
+    fn on_variable_access(&self, identifier: &str, value: Option<&Value>) {
+        self.inner.on_variable_access(identifier, value)
+    }
+
+    fn on_function_call(&self, identifier: &str, arguments: &[Value]) {
+        self.inner.on_function_call(identifier, arguments)
+    }
+}
+
+#[cfg(all(feature = "lru", feature = "async"))]
+impl<'a, C: AsyncConfiguration + ?Sized> AsyncConfiguration for LruCachedConfiguration<'a, C> {
+    fn get_async_function(&self, identifier: &str) -> Option<&AsyncFunction> {
+        self.inner.get_async_function(identifier)
+    }
+}
+
+/// Serializes the variables of the configuration as a map from identifiers to values.
+/// Functions cannot be serialized and are skipped.
+#[cfg(feature = "serde")]
+impl Serialize for HashMapConfiguration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.variables.serialize(serializer)
+    }
+}
+
+/// Deserializes a configuration from a map from identifiers to values.
+/// The resulting configuration contains no functions.
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for HashMapConfiguration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self {
+            variables: Map::deserialize(deserializer)?,
+            ..Self::default()
+        })
+    }
 }
@@ -0,0 +1,206 @@
+use std::fmt::{Display, Formatter, Result};
+
+use error::Error;
+
+/// Writes a message for users, followed by the position in the source string if it is known.
+/// Multiple errors are written one per line.
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", ErrorMessage(self))?;
+        if let Some(span) = self.span() {
+            write!(f, " at {}..{}", span.start, span.end)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes the message of an error without its position in the source string.
+pub(crate) struct ErrorMessage<'a>(pub &'a Error);
+
+impl<'a> Display for ErrorMessage<'a> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self.0 {
+            Error::WrongArgumentAmount { expected, actual } => {
+                write!(f, "Expected {} arguments, but got {}", expected, actual)?
+            }
+            Error::ExpectedNumber { actual } => write!(f, "Expected a number, but got {}", actual)?,
+            Error::ExpectedInt { actual } => write!(f, "Expected an integer, but got {}", actual)?,
+            Error::ExpectedPositiveInt { actual } => {
+                write!(f, "Expected a non-negative integer, but got {}", actual)?
+            }
+            Error::ExpectedFloat { actual } => write!(f, "Expected a float, but got {}", actual)?,
+            Error::ExpectedString { actual } => write!(f, "Expected a string, but got {}", actual)?,
+            Error::ExpectedArray { actual } => write!(f, "Expected an array, but got {}", actual)?,
+            Error::ExpectedObject { actual } => {
+                write!(f, "Expected an object, but got {}", actual)?
+            }
+            Error::ExpectedArrayOrString { actual } => {
+                write!(f, "Expected an array or a string, but got {}", actual)?
+            }
+            Error::ExpectedBoolean { actual } => {
+                write!(f, "Expected a boolean, but got {}", actual)?
+            }
+            Error::TypeMismatch {
+                expected,
+                actual,
+                argument_index,
+            } => write!(
+                f,
+                "Expected the argument at index {} to be of type {}, but got {}",
+                argument_index, expected, actual
+            )?,
+            Error::ReturnTypeMismatch { expected, actual } => write!(
+                f,
+                "Expected the function to return a value of type {}, but got {}",
+                expected, actual
+            )?,
+            Error::IntegerOverflow { operator, lhs, rhs } => write!(
+                f,
+                "Integer overflow when computing {} {} {}",
+                lhs, operator, rhs
+            )?,
+            Error::InvalidArgumentAmountRange { min, max } => write!(
+                f,
+                "The maximum amount of arguments {} is lower than the minimum amount {}",
+                max, min
+            )?,
+            Error::InvalidClampRange { min, max } => write!(
+                f,
+                "Invalid range for clamp with minimum {} and maximum {}",
+                min, max
+            )?,
+            Error::DomainError { function, argument } => write!(
+                f,
+                "The function {} is not defined for {}",
+                function, argument
+            )?,
+            Error::DivisionByZero => write!(f, "Division by zero")?,
+            Error::NaN => write!(f, "The result is not a number")?,
+            Error::Infinity => write!(f, "The result is infinite")?,
+            Error::FunctionError { name, error } => {
+                write!(f, "Error in function {}: {}", name, error)?
+            }
+            Error::ConvergenceFailure { iterations } => write!(
+                f,
+                "No fixed point was reached within {} iterations",
+                iterations
+            )?,
+            Error::CircularDependency => {
+                write!(f, "The values cycle without reaching a fixed point")?
+            }
+            Error::EmptyArray => write!(f, "Expected a non-empty array")?,
+            Error::IndexOutOfBounds { index, length } => {
+                write!(f, "Index {} is out of bounds for length {}", index, length)?
+            }
+            Error::UnexpectedComma => write!(f, "Unexpected comma outside of a function call")?,
+            Error::UnexpectedSpread => write!(f, "Unexpected spread outside of a function call")?,
+            Error::UnexpectedNamedArgument { name } => write!(
+                f,
+                "Unexpected named argument {} outside of a function call",
+                name
+            )?,
+            Error::UnknownArgumentName { function, name } => write!(
+                f,
+                "The function {} has no parameter named {}",
+                function, name
+            )?,
+            Error::DuplicateArgumentName { function, name } => write!(
+                f,
+                "The parameter {} of the function {} is given more than once",
+                name, function
+            )?,
+            Error::EmptyExpression => write!(f, "The expression is empty")?,
+            Error::StringTooLong { limit, actual } => write!(
+                f,
+                "A string of length {} exceeds the maximum length of {}",
+                actual, limit
+            )?,
+            Error::ArrayTooLong { limit, actual } => write!(
+                f,
+                "An array of length {} exceeds the maximum length of {}",
+                actual, limit
+            )?,
+            Error::MaxDepthExceeded { max_depth } => write!(
+                f,
+                "The expression is nested deeper than the maximum depth of {}",
+                max_depth
+            )?,
+            Error::MaxNodeCountExceeded { max_node_count } => write!(
+                f,
+                "The expression has more than the maximum of {} nodes",
+                max_node_count
+            )?,
+            Error::ImmutableConfiguration => write!(
+                f,
+                "Variables can only be assigned in mutable configurations"
+            )?,
+            Error::InvalidAssignmentTarget => write!(f, "Only variables can be assigned")?,
+            Error::EvaluatedRootNode => write!(f, "Tried to evaluate the root node")?,
+            Error::AppendedToLeafNode { .. } => write!(f, "Expected an operator between values")?,
+            Error::PrecedenceViolation { .. } => write!(f, "Operator precedence violated")?,
+            Error::VariableIdentifierNotFound { identifier, .. } => {
+                write!(f, "Variable not found: {}", identifier)?
+            }
+            Error::FunctionIdentifierNotFound { identifier, .. } => {
+                write!(f, "Function not found: {}", identifier)?
+            }
+            Error::TypeError => write!(f, "A value has the wrong type")?,
+            Error::UnmatchedLBrace { .. } => write!(f, "Unmatched opening brace")?,
+            Error::UnmatchedRBrace { .. } => write!(f, "Unmatched closing brace")?,
+            Error::UnmatchedQuestionMark { .. } => write!(f, "Question mark without colon")?,
+            Error::UnmatchedColon { .. } => write!(f, "Colon without question mark")?,
+            Error::UnmatchedLBracket { .. } => write!(f, "Unmatched opening square bracket")?,
+            Error::UnmatchedRBracket { .. } => write!(f, "Unmatched closing square bracket")?,
+            Error::UnmatchedLCurlyBrace { .. } => write!(f, "Unmatched opening curly brace")?,
+            Error::UnmatchedRCurlyBrace { .. } => write!(f, "Unmatched closing curly brace")?,
+            Error::UnexpectedLCurlyBrace { .. } => {
+                write!(f, "Curly braces are only allowed after if and else")?
+            }
+            Error::UnmatchedIf { .. } => write!(f, "If without block")?,
+            Error::UnmatchedElse { .. } => write!(f, "Else without if or block")?,
+            Error::UnmatchedLet { .. } => write!(f, "Let without identifier, value or in")?,
+            Error::UnmatchedQuote { .. } => write!(f, "Unmatched quote")?,
+            Error::UnknownEscapeSequence { character, .. } => {
+                write!(f, "Unknown escape sequence \\{}", character)?
+            }
+            Error::InvalidUnicodeEscape { .. } => write!(f, "Invalid unicode escape sequence")?,
+            Error::UnmatchedComment { .. } => write!(f, "Unmatched block comment")?,
+            Error::UnmatchedPartialToken { first, second, .. } => match second {
+                Some(second) => write!(f, "Unexpected tokens {:?} {:?}", first, second)?,
+                None => write!(f, "Unexpected token {:?}", first)?,
+            },
+            Error::ParseIntError { error, .. } => write!(f, "Invalid integer literal: {}", error)?,
+            Error::ParseError(message) => write!(f, "{}", message)?,
+            Error::SyntaxError(error) => write!(f, "{}", error)?,
+            Error::FormatError(message) => write!(f, "Format error: {}", message)?,
+            Error::RegexError(message) => write!(f, "Invalid regular expression: {}", message)?,
+            Error::JsonError(message) => write!(f, "Invalid JSON: {}", message)?,
+            Error::DecodeError(message) => write!(f, "Decode error: {}", message)?,
+            Error::UnknownHashAlgorithm(algorithm) => {
+                write!(f, "Unknown hash algorithm {:?}", algorithm)?
+            }
+            Error::DateError(message) => write!(f, "Date error: {}", message)?,
+            Error::MultipleErrors(errors) => {
+                for (index, error) in errors.iter().enumerate() {
+                    if index > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", error)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Allows errors to be propagated with `?` into `Box<dyn std::error::Error + Send + Sync>` and error types of other crates.
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::FunctionError { error, .. } => Some(error.as_ref()),
+            Error::ParseIntError { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -1,5 +1,14 @@
-use crate::value::Value;
-use token::PartialToken;
+use crate::value::{FloatType, IntType, Value, ValueType};
+use std::{boxed::Box, num::ParseIntError, string::String, vec::Vec};
+use token::{PartialToken, Span};
+
+mod display;
+mod syntax;
+
+pub use self::syntax::SyntaxError;
+
+/// The result of evaluating an expression, which is a `Value` unless another type is given.
+pub type EvalResult<T = Value> = Result<T, Error>;
 
 #[derive(Debug, PartialEq)]
 pub enum Error {
@@ -10,43 +19,314 @@ pub enum Error {
     ExpectedNumber {
         actual: Value,
     },
+    ExpectedInt {
+        actual: Value,
+    },
+    ExpectedPositiveInt {
+        actual: Value,
+    },
+    ExpectedFloat {
+        actual: Value,
+    },
+    ExpectedString {
+        actual: Value,
+    },
+    ExpectedArray {
+        actual: Value,
+    },
+    ExpectedObject {
+        actual: Value,
+    },
+    ExpectedArrayOrString {
+        actual: Value,
+    },
     ExpectedBoolean {
         actual: Value,
     },
 
+    /// An argument of a `TypedFunction` is not of its declared type.
+    /// The index of the first argument is zero.
+    TypeMismatch {
+        expected: ValueType,
+        actual: Value,
+        argument_index: usize,
+    },
+
+    /// The body of a `TypedFunction` returned a value that is not of its declared return type.
+    ReturnTypeMismatch {
+        expected: ValueType,
+        actual: Value,
+    },
+
+    /// An integer operation overflowed.
+    IntegerOverflow {
+        operator: &'static str,
+        lhs: IntType,
+        rhs: IntType,
+    },
+
+    /// An integer was divided by zero, or the remainder of a division by zero was computed.
+    DivisionByZero,
+
+    /// The result of a float operation is not a number, such as `0.0 * x` for infinite `x`.
+    NaN,
+
+    /// The result of a float operation is infinite, for example because it is too large.
+    Infinity,
+
+    /// A function was called with an argument it is not defined for, such as `sqrt(-1)`.
+    DomainError {
+        function: &'static str,
+        argument: Value,
+    },
+
+    /// `Function::variadic` was called with a maximum amount of arguments that is lower than the minimum.
+    InvalidArgumentAmountRange {
+        min: usize,
+        max: usize,
+    },
+
+    /// `clamp` was called with a minimum that is greater than the maximum, or that is not comparable to it, such as `NaN`.
+    InvalidClampRange {
+        min: FloatType,
+        max: FloatType,
+    },
+
+    /// A named function returned an error.
+    FunctionError {
+        name: String,
+        error: Box<Error>,
+    },
+
+    /// `converge` did not reach a fixed point within the maximum amount of iterations.
+    ConvergenceFailure {
+        iterations: usize,
+    },
+
+    /// `converge` found a value that it had already seen before, so the values cycle without reaching a fixed point.
+    CircularDependency,
+
+    /// A function that requires a non-empty array was called with an empty array.
+    EmptyArray,
+
+    /// An array or string was indexed with an index that is out of its bounds.
+    IndexOutOfBounds {
+        index: IntType,
+        length: usize,
+    },
+
+    /// Comma-separated values were found where they are not allowed.
+    /// Comma-separated values outside of function calls evaluate to tuples.
+    UnexpectedComma,
+
+    /// The spread operator `...` was used outside of the arguments of a function call.
+    UnexpectedSpread,
+
+    /// A named argument such as `color: "red"` was used outside of the arguments of a function call.
+    UnexpectedNamedArgument {
+        name: String,
+    },
+
+    /// A function was called with a named argument that is not one of its parameter names.
+    /// Only functions created with `Function::new_named` have parameter names.
+    UnknownArgumentName {
+        function: String,
+        name: String,
+    },
+
+    /// A function was called with several arguments for the same parameter, either by name or by position.
+    DuplicateArgumentName {
+        function: String,
+        name: String,
+    },
+
     /// The given expression is empty
     EmptyExpression,
 
+    /// An expression could not be parsed, as returned by `parse_expression`.
+    SyntaxError(SyntaxError),
+
+    /// A string was created that is longer than the maximum length in bytes allowed by the configuration.
+    StringTooLong {
+        limit: usize,
+        actual: usize,
+    },
+
+    /// An array was created that is longer than the maximum length allowed by the configuration.
+    ArrayTooLong {
+        limit: usize,
+        actual: usize,
+    },
+
+    /// The expression is nested deeper than the maximum depth allowed by the configuration.
+    MaxDepthExceeded {
+        max_depth: usize,
+    },
+
+    /// The expression has more nodes than allowed by the configuration.
+    MaxNodeCountExceeded {
+        max_node_count: usize,
+    },
+
+    /// Tried to assign a variable while evaluating with a configuration that is not mutable.
+    ImmutableConfiguration,
+
+    /// The left side of an assignment is not a variable identifier.
+    InvalidAssignmentTarget,
+
     /// Tried to evaluate the root node.
     /// The root node should only be used as dummy node.
     EvaluatedRootNode,
 
     /// Tried to append a child to a leaf node.
     /// Leaf nodes cannot have children.
-    AppendedToLeafNode,
+    AppendedToLeafNode {
+        span: Option<Span>,
+    },
 
     /// Tried to append a child to a node such that the precedence of the child is not higher.
-    PrecedenceViolation,
+    PrecedenceViolation {
+        span: Option<Span>,
+    },
 
     /// A `VariableIdentifier` operation did not find its value in the configuration.
-    VariableIdentifierNotFound(String),
+    VariableIdentifierNotFound {
+        identifier: String,
+        span: Option<Span>,
+    },
 
     /// A `FunctionIdentifier` operation did not find its value in the configuration.
-    FunctionIdentifierNotFound(String),
+    FunctionIdentifierNotFound {
+        identifier: String,
+        span: Option<Span>,
+    },
 
     /// A value has the wrong type.
     TypeError,
 
     /// An opening brace without a matching closing brace was found.
-    UnmatchedLBrace,
+    UnmatchedLBrace {
+        span: Option<Span>,
+    },
 
     /// A closing brace without a matching opening brace was found.
-    UnmatchedRBrace,
+    UnmatchedRBrace {
+        span: Option<Span>,
+    },
+
+    /// A question mark without a matching colon was found.
+    UnmatchedQuestionMark {
+        span: Option<Span>,
+    },
+
+    /// An opening square bracket without a matching closing square bracket was found.
+    UnmatchedLBracket {
+        span: Option<Span>,
+    },
+
+    /// A closing square bracket without a matching opening square bracket was found.
+    UnmatchedRBracket {
+        span: Option<Span>,
+    },
+
+    /// A colon without a matching question mark was found.
+    UnmatchedColon {
+        span: Option<Span>,
+    },
+
+    /// An opening curly brace without a matching closing curly brace was found.
+    UnmatchedLCurlyBrace {
+        span: Option<Span>,
+    },
+
+    /// A closing curly brace without a matching opening curly brace was found.
+    UnmatchedRCurlyBrace {
+        span: Option<Span>,
+    },
+
+    /// An opening curly brace was found that does not start the block of an `if` or `else`.
+    UnexpectedLCurlyBrace {
+        span: Option<Span>,
+    },
+
+    /// An `if` without a block was found.
+    UnmatchedIf {
+        span: Option<Span>,
+    },
+
+    /// A `let` was found that is not followed by an identifier, `=`, a value and `in`.
+    UnmatchedLet {
+        span: Option<Span>,
+    },
+
+    /// An `else` was found that does not follow the block of an `if`, or that is not followed by a block or another `if`.
+    UnmatchedElse {
+        span: Option<Span>,
+    },
+
+    /// A string literal without a closing quote was found.
+    UnmatchedQuote {
+        span: Option<Span>,
+    },
+
+    /// A string literal contains a backslash followed by a character that does not form an escape sequence.
+    UnknownEscapeSequence {
+        character: char,
+        span: Option<Span>,
+    },
+
+    /// A string literal contains a `\u` escape sequence that is not followed by the hexadecimal code of a unicode scalar value.
+    InvalidUnicodeEscape {
+        span: Option<Span>,
+    },
+
+    /// A block comment without a matching end was found.
+    UnmatchedComment {
+        span: Option<Span>,
+    },
 
     UnmatchedPartialToken {
         first: PartialToken,
         second: Option<PartialToken>,
+        span: Option<Span>,
     },
+
+    /// An integer literal could not be parsed, for example because it is too large.
+    ParseIntError {
+        error: ParseIntError,
+        span: Option<Span>,
+    },
+
+    /// A string could not be converted to another type.
+    /// Contains the message of the underlying parse error.
+    ParseError(String),
+
+    /// The template of `sprintf` is invalid or does not match its arguments.
+    /// Contains a description of the problem.
+    FormatError(String),
+
+    /// A pattern of `regex_match` or `regex_replace` is not a valid regular expression.
+    /// Contains the message of the regex compiler.
+    RegexError(String),
+
+    /// A string passed to `parse_json` is not valid JSON.
+    /// Contains the message of the JSON parser.
+    JsonError(String),
+
+    /// A string passed to `base64_decode` or `base64_url_decode` is not valid base64, or does not decode to UTF-8.
+    /// Contains a description of the problem.
+    DecodeError(String),
+
+    /// The algorithm passed to `hash` is not supported.
+    /// Contains the name of the algorithm.
+    UnknownHashAlgorithm(String),
+
+    /// A date is invalid or out of the supported range, or a timezone is unknown.
+    /// Contains a description of the problem.
+    DateError(String),
+
+    /// Several errors occurred, for example when validating an expression.
+    MultipleErrors(Vec<Error>),
 }
 
 impl Error {
@@ -58,12 +338,177 @@ impl Error {
         Error::ExpectedNumber { actual }
     }
 
+    pub fn expected_int(actual: Value) -> Self {
+        Error::ExpectedInt { actual }
+    }
+
+    pub fn expected_positive_int(actual: Value) -> Self {
+        Error::ExpectedPositiveInt { actual }
+    }
+
+    pub fn expected_float(actual: Value) -> Self {
+        Error::ExpectedFloat { actual }
+    }
+
+    pub fn expected_string(actual: Value) -> Self {
+        Error::ExpectedString { actual }
+    }
+
+    pub fn expected_array(actual: Value) -> Self {
+        Error::ExpectedArray { actual }
+    }
+
+    pub fn expected_object(actual: Value) -> Self {
+        Error::ExpectedObject { actual }
+    }
+
+    pub fn expected_array_or_string(actual: Value) -> Self {
+        Error::ExpectedArrayOrString { actual }
+    }
+
     pub fn expected_boolean(actual: Value) -> Self {
         Error::ExpectedBoolean { actual }
     }
 
-    pub fn unmatched_partial_token(first: PartialToken, second: Option<PartialToken>) -> Self {
-        Error::UnmatchedPartialToken { first, second }
+    pub fn type_mismatch(expected: ValueType, actual: Value, argument_index: usize) -> Self {
+        Error::TypeMismatch {
+            expected,
+            actual,
+            argument_index,
+        }
+    }
+
+    pub fn integer_overflow(operator: &'static str, lhs: IntType, rhs: IntType) -> Self {
+        Error::IntegerOverflow { operator, lhs, rhs }
+    }
+
+    pub fn domain_error(function: &'static str, argument: Value) -> Self {
+        Error::DomainError { function, argument }
+    }
+
+    pub fn unexpected_named_argument(name: String) -> Self {
+        Error::UnexpectedNamedArgument { name }
+    }
+
+    pub fn unknown_argument_name(function: String, name: String) -> Self {
+        Error::UnknownArgumentName { function, name }
+    }
+
+    pub fn duplicate_argument_name(function: String, name: String) -> Self {
+        Error::DuplicateArgumentName { function, name }
+    }
+
+    pub fn function_error(name: String, error: Error) -> Self {
+        Error::FunctionError {
+            name,
+            error: Box::new(error),
+        }
+    }
+
+    pub fn convergence_failure(iterations: usize) -> Self {
+        Error::ConvergenceFailure { iterations }
+    }
+
+    pub fn index_out_of_bounds(index: IntType, length: usize) -> Self {
+        Error::IndexOutOfBounds { index, length }
+    }
+
+    pub fn max_depth_exceeded(max_depth: usize) -> Self {
+        Error::MaxDepthExceeded { max_depth }
+    }
+
+    pub fn max_node_count_exceeded(max_node_count: usize) -> Self {
+        Error::MaxNodeCountExceeded { max_node_count }
+    }
+
+    pub fn unmatched_partial_token(
+        first: PartialToken,
+        second: Option<PartialToken>,
+        span: Span,
+    ) -> Self {
+        Error::UnmatchedPartialToken {
+            first,
+            second,
+            span: Some(span),
+        }
+    }
+
+    pub fn parse_int_error(error: ParseIntError, span: Span) -> Self {
+        Error::ParseIntError {
+            error,
+            span: Some(span),
+        }
+    }
+
+    pub fn variable_identifier_not_found(identifier: String, span: Option<Span>) -> Self {
+        Error::VariableIdentifierNotFound { identifier, span }
+    }
+
+    pub fn function_identifier_not_found(identifier: String, span: Option<Span>) -> Self {
+        Error::FunctionIdentifierNotFound { identifier, span }
+    }
+
+    /// Returns the position in the source string that caused this error, if known.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Error::AppendedToLeafNode { span }
+            | Error::PrecedenceViolation { span }
+            | Error::VariableIdentifierNotFound { span, .. }
+            | Error::FunctionIdentifierNotFound { span, .. }
+            | Error::UnmatchedLBrace { span }
+            | Error::UnmatchedRBrace { span }
+            | Error::UnmatchedQuestionMark { span }
+            | Error::UnmatchedColon { span }
+            | Error::UnmatchedLBracket { span }
+            | Error::UnmatchedRBracket { span }
+            | Error::UnmatchedLCurlyBrace { span }
+            | Error::UnmatchedRCurlyBrace { span }
+            | Error::UnexpectedLCurlyBrace { span }
+            | Error::UnmatchedIf { span }
+            | Error::UnmatchedLet { span }
+            | Error::UnmatchedElse { span }
+            | Error::UnmatchedComment { span }
+            | Error::UnmatchedQuote { span }
+            | Error::UnknownEscapeSequence { span, .. }
+            | Error::InvalidUnicodeEscape { span }
+            | Error::UnmatchedPartialToken { span, .. }
+            | Error::ParseIntError { span, .. } => span.clone(),
+            _ => None,
+        }
+    }
+
+    /// Sets the span of this error to the given span, if the error supports spans and has none yet.
+    pub(crate) fn with_span(mut self, new_span: &Span) -> Self {
+        match &mut self {
+            Error::AppendedToLeafNode { span }
+            | Error::PrecedenceViolation { span }
+            | Error::VariableIdentifierNotFound { span, .. }
+            | Error::FunctionIdentifierNotFound { span, .. }
+            | Error::UnmatchedLBrace { span }
+            | Error::UnmatchedRBrace { span }
+            | Error::UnmatchedQuestionMark { span }
+            | Error::UnmatchedColon { span }
+            | Error::UnmatchedLBracket { span }
+            | Error::UnmatchedRBracket { span }
+            | Error::UnmatchedLCurlyBrace { span }
+            | Error::UnmatchedRCurlyBrace { span }
+            | Error::UnexpectedLCurlyBrace { span }
+            | Error::UnmatchedIf { span }
+            | Error::UnmatchedLet { span }
+            | Error::UnmatchedElse { span }
+            | Error::UnmatchedComment { span }
+            | Error::UnmatchedQuote { span }
+            | Error::UnknownEscapeSequence { span, .. }
+            | Error::InvalidUnicodeEscape { span }
+            | Error::UnmatchedPartialToken { span, .. }
+            | Error::ParseIntError { span, .. }
+                if span.is_none() =>
+            {
+                *span = Some(new_span.clone());
+            }
+            _ => {}
+        }
+        self
     }
 }
 
@@ -75,6 +520,22 @@ pub fn expect_argument_amount(actual: usize, expected: usize) -> Result<(), Erro
     }
 }
 
+/// Returns `Error::StringTooLong` if the given length of a string exceeds the given limit.
+pub fn expect_string_length(actual: usize, limit: Option<usize>) -> Result<(), Error> {
+    match limit {
+        Some(limit) if actual > limit => Err(Error::StringTooLong { limit, actual }),
+        _ => Ok(()),
+    }
+}
+
+/// Returns `Error::ArrayTooLong` if the given length of an array exceeds the given limit.
+pub fn expect_array_length(actual: usize, limit: Option<usize>) -> Result<(), Error> {
+    match limit {
+        Some(limit) if actual > limit => Err(Error::ArrayTooLong { limit, actual }),
+        _ => Ok(()),
+    }
+}
+
 pub fn expect_number(actual: &Value) -> Result<(), Error> {
     match actual {
         Value::Float(_) | Value::Int(_) => Ok(()),
@@ -82,6 +543,39 @@ pub fn expect_number(actual: &Value) -> Result<(), Error> {
     }
 }
 
+/// Returns `Error::NaN` or `Error::Infinity` if the given result of a float operation is not finite.
+/// With the `permissive_floats` feature, all results are accepted.
+pub fn expect_finite(result: FloatType) -> Result<FloatType, Error> {
+    if cfg!(feature = "permissive_floats") || result.is_finite() {
+        Ok(result)
+    } else if result.is_nan() {
+        Err(Error::NaN)
+    } else {
+        Err(Error::Infinity)
+    }
+}
+
+pub fn expect_int(actual: &Value) -> Result<IntType, Error> {
+    match actual {
+        Value::Int(int) => Ok(*int),
+        _ => Err(Error::expected_int(actual.clone())),
+    }
+}
+
+pub fn expect_string(actual: &Value) -> Result<&str, Error> {
+    match actual {
+        Value::String(string) => Ok(string),
+        _ => Err(Error::expected_string(actual.clone())),
+    }
+}
+
+pub fn expect_array(actual: &Value) -> Result<&[Value], Error> {
+    match actual {
+        Value::Array(array) => Ok(array),
+        _ => Err(Error::expected_array(actual.clone())),
+    }
+}
+
 pub fn expect_boolean(actual: &Value) -> Result<bool, Error> {
     match actual {
         Value::Boolean(boolean) => Ok(*boolean),
@@ -0,0 +1,78 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    string::{String, ToString},
+};
+
+use error::{display::ErrorMessage, Error};
+
+/// The amount of characters before and after the position of a syntax error that are included in its fragment.
+const FRAGMENT_RADIUS: usize = 10;
+
+/// An error that occurred while parsing an expression, together with its position in the expression.
+/// This is returned by `parse_expression`, for example to underline the position of the error in an editor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// The message of the error, without its position.
+    pub message: String,
+    /// The byte offset of the error in the expression.
+    /// Errors without a known position, such as missing operands, are positioned at the end of the expression.
+    pub position: usize,
+    /// The line of the error, starting at 1.
+    pub line: usize,
+    /// The column of the error in characters, starting at 1.
+    pub column: usize,
+    /// The part of the expression around the error, with up to 10 characters on either side of the position.
+    pub fragment: String,
+}
+
+impl SyntaxError {
+    /// Creates a syntax error for the given error that occurred while parsing the given expression.
+    pub fn new(expression: &str, error: &Error) -> Self {
+        let mut position = error
+            .span()
+            .map_or(expression.len(), |span| span.start)
+            .min(expression.len());
+        while !expression.is_char_boundary(position) {
+            position -= 1;
+        }
+
+        let before = &expression[..position];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        let fragment_start = before
+            .char_indices()
+            .rev()
+            .nth(FRAGMENT_RADIUS - 1)
+            .map_or(0, |(index, _)| index);
+        let fragment_end = expression[position..]
+            .char_indices()
+            .nth(FRAGMENT_RADIUS)
+            .map_or(expression.len(), |(index, _)| position + index);
+
+        SyntaxError {
+            message: ErrorMessage(error).to_string(),
+            position,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            fragment: expression[fragment_start..fragment_end].to_string(),
+        }
+    }
+}
+
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.message, self.line, self.column
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SyntaxError {}
+
+impl From<SyntaxError> for Error {
+    fn from(error: SyntaxError) -> Self {
+        Error::SyntaxError(error)
+    }
+}
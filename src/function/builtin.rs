@@ -0,0 +1,1100 @@
+#[cfg(feature = "encoding")]
+use base64::{
+    engine::{
+        general_purpose::{STANDARD, URL_SAFE},
+        GeneralPurpose,
+    },
+    Engine,
+};
+use configuration::Configuration;
+use error::{
+    expect_argument_amount, expect_array, expect_boolean, expect_finite, expect_int, expect_number,
+    expect_string, Error,
+};
+#[cfg(feature = "chrono")]
+use function::date;
+#[cfg(feature = "regex")]
+use function::regex;
+use function::{default_function, Function};
+use math;
+#[cfg(feature = "crypto")]
+use sha1::Sha1;
+#[cfg(feature = "crypto")]
+use sha2::{Digest, Sha256};
+#[cfg(feature = "std")]
+use std::cell::Cell;
+use std::{
+    boxed::Box,
+    cmp::Ordering,
+    convert::TryFrom,
+    num::{ParseFloatError, ParseIntError},
+    slice,
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(feature = "uuid")]
+use uuid::Uuid;
+use value::{FloatType, IntType, Value};
+#[cfg(feature = "crypto")]
+use xxhash_rust::xxh64::xxh64;
+
+/// True if the builtin function with the given identifier exists and always returns the same result for the same arguments.
+pub fn is_pure_builtin_function(identifier: &str) -> bool {
+    !matches!(identifier, "now" | "uuid" | "env" | "env_or")
+        && builtin_function(identifier).is_some()
+}
+
+/// Returns the builtin function with the given identifier, if it exists.
+/// Builtin functions are available in all expressions, unless the configuration defines a function with the same identifier.
+pub fn builtin_function(identifier: &str) -> Option<Function> {
+    match identifier {
+        "abs" => Some(Function::new(
+            1,
+            Box::new(|arguments| match &arguments[0] {
+                Value::Int(int) => int
+                    .checked_abs()
+                    .map(Value::Int)
+                    .ok_or_else(|| Error::integer_overflow("abs", *int, 0)),
+                Value::Float(float) => Ok(Value::Float(math::abs(*float))),
+                value => Err(Error::expected_number(value.clone())),
+            }),
+        )),
+        "floor" => Some(float_function(math::floor)),
+        "ceil" => Some(float_function(math::ceil)),
+        "round" => Some(float_function(math::round)),
+        "sqrt" => Some(partial_float_function("sqrt", math::sqrt)),
+        "ln" => Some(partial_float_function("ln", math::ln)),
+        "log2" => Some(partial_float_function("log2", math::log2)),
+        "log10" => Some(partial_float_function("log10", math::log10)),
+        "sin" => Some(partial_float_function("sin", math::sin)),
+        "cos" => Some(partial_float_function("cos", math::cos)),
+        "tan" => Some(partial_float_function("tan", math::tan)),
+        "asin" => Some(partial_float_function("asin", math::asin)),
+        "acos" => Some(partial_float_function("acos", math::acos)),
+        "atan" => Some(partial_float_function("atan", math::atan)),
+        "atan2" => Some(Function::new(
+            2,
+            Box::new(|arguments| {
+                expect_number(&arguments[0])?;
+                expect_number(&arguments[1])?;
+                Ok(Value::Float(math::atan2(
+                    arguments[0].as_float().unwrap(),
+                    arguments[1].as_float().unwrap(),
+                )))
+            }),
+        )),
+        "pow" => Some(Function::new(
+            2,
+            Box::new(|arguments| match (&arguments[0], &arguments[1]) {
+                (Value::Int(base), Value::Int(exponent)) => {
+                    if *exponent < 0 {
+                        return Err(Error::expected_positive_int(Value::Int(*exponent)));
+                    }
+                    u32::try_from(*exponent)
+                        .ok()
+                        .and_then(|exponent| base.checked_pow(exponent))
+                        .map(Value::Int)
+                        .ok_or_else(|| Error::integer_overflow("pow", *base, *exponent))
+                }
+                (base, exponent) => {
+                    expect_number(base)?;
+                    expect_number(exponent)?;
+                    Ok(Value::Float(expect_finite(math::powf(
+                        base.as_float().unwrap(),
+                        exponent.as_float().unwrap(),
+                    ))?))
+                }
+            }),
+        )),
+        "clamp" => Some(Function::new(
+            3,
+            Box::new(|arguments| {
+                for argument in arguments {
+                    expect_number(argument)?;
+                }
+                let min = arguments[1].as_float().unwrap();
+                let max = arguments[2].as_float().unwrap();
+                // Integers are compared as integers, because distinct large integers may be the same float
+                let ordering = match (&arguments[1], &arguments[2]) {
+                    (Value::Int(min), Value::Int(max)) => Some(min.cmp(max)),
+                    _ => min.partial_cmp(&max),
+                };
+                // Floats may not be comparable, and `clamp` panics for those
+                if !matches!(ordering, Some(Ordering::Less | Ordering::Equal)) {
+                    return Err(Error::InvalidClampRange { min, max });
+                }
+
+                match (&arguments[0], &arguments[1], &arguments[2]) {
+                    (Value::Int(value), Value::Int(min), Value::Int(max)) => {
+                        Ok(Value::Int(*value.clamp(min, max)))
+                    }
+                    (value, _, _) => Ok(Value::Float(value.as_float().unwrap().clamp(min, max))),
+                }
+            }),
+        )),
+        "gcd" => Some(Function::new(
+            2,
+            Box::new(|arguments| {
+                let a = expect_int(&arguments[0])?;
+                let b = expect_int(&arguments[1])?;
+                // The only result that does not fit is 2^63, as in `gcd(i64::MIN, 0)`
+                IntType::try_from(gcd(a.unsigned_abs(), b.unsigned_abs()))
+                    .map(Value::Int)
+                    .map_err(|_| Error::integer_overflow("gcd", a, b))
+            }),
+        )),
+        "lcm" => Some(Function::new(
+            2,
+            Box::new(|arguments| {
+                let a = expect_int(&arguments[0])?;
+                let b = expect_int(&arguments[1])?;
+                if a == 0 || b == 0 {
+                    return Ok(Value::Int(0));
+                }
+                // Dividing first avoids overflowing for results that fit
+                let (a_abs, b_abs) = (a.unsigned_abs(), b.unsigned_abs());
+                (a_abs / gcd(a_abs, b_abs))
+                    .checked_mul(b_abs)
+                    .and_then(|lcm| IntType::try_from(lcm).ok())
+                    .map(Value::Int)
+                    .ok_or_else(|| Error::integer_overflow("lcm", a, b))
+            }),
+        )),
+        "number_format" => Some(Function::new(
+            2,
+            Box::new(|arguments| {
+                expect_number(&arguments[0])?;
+                let decimal_places = expect_int(&arguments[1])?;
+                number_format(&arguments[0], decimal_places).map(Value::String)
+            }),
+        )),
+        "sum" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                fold_numbers(&arguments[0], "+", IntType::checked_add, |a, b| a + b)
+            }),
+        )),
+        "product" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                fold_numbers(&arguments[0], "*", IntType::checked_mul, |a, b| a * b)
+            }),
+        )),
+        "avg" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                let numbers = expect_numbers(&arguments[0])?;
+                Ok(Value::Float(expect_finite(mean(&numbers))?))
+            }),
+        )),
+        "stdev" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                let numbers = expect_numbers(&arguments[0])?;
+                let mean = mean(&numbers);
+                let variance = numbers
+                    .iter()
+                    .map(|number| (number - mean) * (number - mean))
+                    .sum::<FloatType>()
+                    / numbers.len() as FloatType;
+                Ok(Value::Float(expect_finite(math::sqrt(variance))?))
+            }),
+        )),
+        "len" => Some(Function::new(
+            1,
+            Box::new(|arguments| match &arguments[0] {
+                Value::String(string) => Ok(Value::Int(string.chars().count() as IntType)),
+                Value::Array(array) | Value::Tuple(array) => Ok(Value::Int(array.len() as IntType)),
+                Value::Object(object) => Ok(Value::Int(object.len() as IntType)),
+                value => Err(Error::expected_array_or_string(value.clone())),
+            }),
+        )),
+        "char_at" => Some(Function::new(
+            2,
+            Box::new(|arguments| {
+                let string = expect_string(&arguments[0])?;
+                let index = expect_int(&arguments[1])?;
+                let length = string.chars().count();
+                // Negative indices count from the end
+                let position = if index < 0 {
+                    usize::try_from(index.unsigned_abs())
+                        .ok()
+                        .and_then(|offset| length.checked_sub(offset))
+                } else {
+                    usize::try_from(index).ok()
+                };
+                position
+                    .and_then(|position| string.chars().nth(position))
+                    .map(|character| Value::String(character.to_string()))
+                    .ok_or_else(|| Error::index_out_of_bounds(index, length))
+            }),
+        )),
+        "char_code" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                let mut characters = expect_string(&arguments[0])?.chars();
+                match (characters.next(), characters.next()) {
+                    (Some(character), None) => Ok(Value::Int(IntType::from(u32::from(character)))),
+                    _ => Err(Error::domain_error("char_code", arguments[0].clone())),
+                }
+            }),
+        )),
+        "from_char_code" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                let code = expect_int(&arguments[0])?;
+                u32::try_from(code)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .map(|character| Value::String(character.to_string()))
+                    .ok_or_else(|| Error::domain_error("from_char_code", Value::Int(code)))
+            }),
+        )),
+        "trim" => Some(string_function(|string| string.trim().to_string())),
+        "to_upper" => Some(string_function(str::to_uppercase)),
+        "to_lower" => Some(string_function(str::to_lowercase)),
+        "starts_with" => Some(string_predicate(|string, prefix| {
+            string.starts_with(prefix)
+        })),
+        "ends_with" => Some(string_predicate(|string, suffix| string.ends_with(suffix))),
+        "contains" => Some(string_predicate(|string, substring| {
+            string.contains(substring)
+        })),
+        "replace" => Some(Function::new(
+            3,
+            Box::new(|arguments| {
+                let string = expect_string(&arguments[0])?;
+                let from = expect_string(&arguments[1])?;
+                let to = expect_string(&arguments[2])?;
+                Ok(Value::String(string.replace(from, to)))
+            }),
+        )),
+        "split" => Some(Function::new(
+            2,
+            Box::new(|arguments| {
+                let string = expect_string(&arguments[0])?;
+                let delimiter = expect_string(&arguments[1])?;
+                Ok(Value::Array(
+                    string
+                        .split(delimiter)
+                        .map(|part| Value::String(part.to_string()))
+                        .collect(),
+                ))
+            }),
+        )),
+        "sprintf" => Some(Function::new_variadic(
+            1,
+            Box::new(|arguments| {
+                let template = expect_string(&arguments[0])?;
+                sprintf(template, &arguments[1..]).map(Value::String)
+            }),
+        )),
+        #[cfg(feature = "regex")]
+        "regex_match" => Some(Function::new(
+            2,
+            Box::new(|arguments| {
+                let string = expect_string(&arguments[0])?;
+                let regex = regex::compile(expect_string(&arguments[1])?)?;
+                Ok(Value::Boolean(regex.is_match(string)))
+            }),
+        )),
+        #[cfg(feature = "regex")]
+        "regex_replace" => Some(Function::new(
+            3,
+            Box::new(|arguments| {
+                let string = expect_string(&arguments[0])?;
+                let regex = regex::compile(expect_string(&arguments[1])?)?;
+                let replacement = expect_string(&arguments[2])?;
+                Ok(Value::String(
+                    regex.replace_all(string, replacement).into_owned(),
+                ))
+            }),
+        )),
+        #[cfg(feature = "json")]
+        "parse_json" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                serde_json::from_str(expect_string(&arguments[0])?)
+                    .map(json_to_value)
+                    .map_err(|error| Error::JsonError(error.to_string()))
+            }),
+        )),
+        #[cfg(feature = "encoding")]
+        "base64_encode" => Some(base64_encode_function(&STANDARD)),
+        #[cfg(feature = "encoding")]
+        "base64_decode" => Some(base64_decode_function(&STANDARD)),
+        #[cfg(feature = "encoding")]
+        "base64_url_encode" => Some(base64_encode_function(&URL_SAFE)),
+        #[cfg(feature = "encoding")]
+        "base64_url_decode" => Some(base64_decode_function(&URL_SAFE)),
+        #[cfg(feature = "crypto")]
+        "hash" => Some(Function::new(
+            2,
+            Box::new(|arguments| {
+                let bytes = expect_string(&arguments[0])?.as_bytes();
+                let digest = match expect_string(&arguments[1])? {
+                    "sha256" => Sha256::digest(bytes).to_vec(),
+                    "sha1" => Sha1::digest(bytes).to_vec(),
+                    "md5" => md5::compute(bytes).0.to_vec(),
+                    "fnv" => fnv1a(bytes).to_be_bytes().to_vec(),
+                    "xxhash" => xxh64(bytes, 0).to_be_bytes().to_vec(),
+                    algorithm => return Err(Error::UnknownHashAlgorithm(algorithm.to_string())),
+                };
+                Ok(Value::String(
+                    digest.iter().map(|byte| format!("{:02x}", byte)).collect(),
+                ))
+            }),
+        )),
+        #[cfg(feature = "env_access")]
+        "env" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                Ok(env_variable(expect_string(&arguments[0])?).unwrap_or(Value::Null))
+            }),
+        )),
+        #[cfg(feature = "env_access")]
+        "env_or" => Some(Function::new(
+            2,
+            Box::new(|arguments| {
+                Ok(env_variable(expect_string(&arguments[0])?)
+                    .unwrap_or_else(|| arguments[1].clone()))
+            }),
+        )),
+        #[cfg(feature = "uuid")]
+        "uuid" => Some(Function::new(
+            0,
+            Box::new(|_| Ok(Value::String(Uuid::new_v4().to_string()))),
+        )),
+        #[cfg(feature = "chrono")]
+        "now" => Some(Function::new(0, Box::new(|_| Ok(Value::Int(date::now()))))),
+        #[cfg(feature = "chrono")]
+        // Only the timezone is optional
+        "timestamp" => Function::variadic(
+            6,
+            Some(7),
+            Box::new(|arguments| date::timestamp(arguments).map(Value::Int)),
+        )
+        .ok(),
+        #[cfg(feature = "chrono")]
+        "date_add" => Some(Function::new(
+            2,
+            Box::new(|arguments| {
+                let timestamp = expect_int(&arguments[0])?;
+                let milliseconds = expect_int(&arguments[1])?;
+                let result = timestamp
+                    .checked_add(milliseconds)
+                    .ok_or_else(|| Error::integer_overflow("date_add", timestamp, milliseconds))?;
+                date::expect_timestamp(result).map(Value::Int)
+            }),
+        )),
+        #[cfg(feature = "chrono")]
+        "date_diff" => Some(Function::new(
+            2,
+            Box::new(|arguments| {
+                let a = expect_int(&arguments[0])?;
+                let b = expect_int(&arguments[1])?;
+                a.checked_sub(b)
+                    .map(Value::Int)
+                    .ok_or_else(|| Error::integer_overflow("date_diff", a, b))
+            }),
+        )),
+        "type_of" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                Ok(Value::String(
+                    match &arguments[0] {
+                        Value::String(_) => "string",
+                        Value::Float(_) => "float",
+                        Value::Int(_) => "int",
+                        Value::Boolean(_) => "boolean",
+                        Value::Array(_) => "array",
+                        Value::Tuple(_) => "tuple",
+                        Value::Object(_) => "object",
+                        Value::Null => "null",
+                    }
+                    .to_string(),
+                ))
+            }),
+        )),
+        "to_int" => Some(Function::new(
+            1,
+            Box::new(|arguments| match &arguments[0] {
+                Value::Int(int) => Ok(Value::Int(*int)),
+                Value::Float(float) => Ok(Value::Int(*float as IntType)),
+                Value::Boolean(boolean) => Ok(Value::Int(*boolean as IntType)),
+                Value::String(string) => string
+                    .parse()
+                    .map(Value::Int)
+                    .map_err(|error: ParseIntError| Error::ParseError(error.to_string())),
+                _ => Err(Error::TypeError),
+            }),
+        )),
+        "to_float" => Some(Function::new(
+            1,
+            Box::new(|arguments| match &arguments[0] {
+                Value::Int(int) => Ok(Value::Float(*int as FloatType)),
+                Value::Float(float) => Ok(Value::Float(*float)),
+                Value::String(string) => string
+                    .parse()
+                    .map(Value::Float)
+                    .map_err(|error: ParseFloatError| Error::ParseError(error.to_string())),
+                _ => Err(Error::TypeError),
+            }),
+        )),
+        "to_string" => Some(Function::new(
+            1,
+            Box::new(|arguments| match &arguments[0] {
+                Value::String(string) => Ok(Value::String(string.clone())),
+                value => Ok(Value::String(value.to_string())),
+            }),
+        )),
+        "to_bool" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                Ok(Value::Boolean(match &arguments[0] {
+                    Value::String(string) => !string.is_empty(),
+                    Value::Float(float) => *float != 0.0,
+                    Value::Int(int) => *int != 0,
+                    Value::Boolean(boolean) => *boolean,
+                    Value::Array(array) | Value::Tuple(array) => !array.is_empty(),
+                    Value::Object(object) => !object.is_empty(),
+                    Value::Null => false,
+                }))
+            }),
+        )),
+        "sort" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                let mut array = expect_array(&arguments[0])?.to_vec();
+                match array.first() {
+                    Some(Value::String(_)) => {
+                        for element in &array {
+                            expect_string(element)?;
+                        }
+                        array.sort_by(|a, b| a.as_str().cmp(&b.as_str()));
+                    }
+                    _ => {
+                        for element in &array {
+                            expect_number(element)?;
+                        }
+                        // Integers are only widened to floats if the array contains floats, to keep their precision
+                        if array.iter().all(Value::is_int) {
+                            array.sort_by(Value::total_cmp);
+                        } else {
+                            array.sort_by(|a, b| {
+                                a.as_float()
+                                    .partial_cmp(&b.as_float())
+                                    .unwrap_or(Ordering::Equal)
+                            });
+                        }
+                    }
+                }
+                Ok(Value::Array(array))
+            }),
+        )),
+        "reverse" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                Ok(Value::Array(
+                    expect_array(&arguments[0])?.iter().rev().cloned().collect(),
+                ))
+            }),
+        )),
+        "unique" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                let mut result: Vec<Value> = Vec::new();
+                for element in expect_array(&arguments[0])? {
+                    if !result.iter().any(|existing| existing.deep_equal(element)) {
+                        result.push(element.clone());
+                    }
+                }
+                Ok(Value::Array(result))
+            }),
+        )),
+        "flatten" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                let mut result = Vec::new();
+                for element in expect_array(&arguments[0])? {
+                    match element {
+                        Value::Array(inner) => result.extend(inner.iter().cloned()),
+                        element => result.push(element.clone()),
+                    }
+                }
+                Ok(Value::Array(result))
+            }),
+        )),
+        "slice" => Some(Function::new(
+            3,
+            Box::new(|arguments| {
+                let array = expect_array(&arguments[0])?;
+                let start = clamp_index(expect_int(&arguments[1])?, array.len());
+                let end = clamp_index(expect_int(&arguments[2])?, array.len());
+                Ok(Value::Array(if start < end {
+                    array[start..end].to_vec()
+                } else {
+                    Vec::new()
+                }))
+            }),
+        )),
+        "first" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                expect_array(&arguments[0])?
+                    .first()
+                    .cloned()
+                    .ok_or(Error::EmptyArray)
+            }),
+        )),
+        "last" => Some(Function::new(
+            1,
+            Box::new(|arguments| {
+                expect_array(&arguments[0])?
+                    .last()
+                    .cloned()
+                    .ok_or(Error::EmptyArray)
+            }),
+        )),
+        _ => None,
+    }
+}
+
+/// The maximum amount of iterations of `converge` if no maximum is given.
+const DEFAULT_MAX_CONVERGE_ITERATIONS: usize = 1000;
+
+/// The amount of previous values `converge` compares each new value with to detect cycles.
+const CONVERGE_CYCLE_WINDOW: usize = 16;
+
+/// The maximum nesting of `recurse` if the configuration does not limit the depth.
+#[cfg(feature = "std")]
+const DEFAULT_MAX_RECURSION_DEPTH: usize = 64;
+
+#[cfg(feature = "std")]
+thread_local! {
+    /// The amount of calls of `recurse` that are currently evaluated on this thread.
+    static RECURSION_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts a call of `recurse` until it is dropped, so the depth is restored even if the call panics.
+#[cfg(feature = "std")]
+struct RecursionGuard;
+
+#[cfg(feature = "std")]
+impl RecursionGuard {
+    fn enter(max_depth: usize) -> Result<Self, Error> {
+        let depth = RECURSION_DEPTH.with(|depth| depth.get()) + 1;
+        if depth > max_depth {
+            return Err(Error::max_depth_exceeded(max_depth));
+        }
+        RECURSION_DEPTH.with(|current| current.set(depth));
+        Ok(RecursionGuard)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Drop for RecursionGuard {
+    fn drop(&mut self) {
+        RECURSION_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    }
+}
+
+/// A builtin function that has access to the configuration it is evaluated with.
+pub struct HigherOrderFunction {
+    argument_amount: usize,
+    /// The amount of arguments that may follow the required arguments, which is `usize::MAX` if any amount is accepted.
+    optional_argument_amount: usize,
+    function: fn(&[Value], &dyn Configuration) -> Result<Value, Error>,
+}
+
+impl HigherOrderFunction {
+    /// Returns `Error::WrongArgumentAmount` if this function does not accept the given amount of arguments.
+    pub fn expect_argument_amount(&self, actual: usize) -> Result<(), Error> {
+        if actual > self.argument_amount
+            && actual
+                <= self
+                    .argument_amount
+                    .saturating_add(self.optional_argument_amount)
+        {
+            Ok(())
+        } else {
+            expect_argument_amount(actual, self.argument_amount)
+        }
+    }
+
+    pub fn call(
+        &self,
+        arguments: &[Value],
+        configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        self.expect_argument_amount(arguments.len())?;
+        (self.function)(arguments, configuration)
+    }
+}
+
+/// Returns the builtin higher-order function with the given identifier, if it exists.
+/// Higher-order functions take the names of other functions as arguments, and call them with the configuration they are evaluated with.
+pub fn higher_order_function(identifier: &str) -> Option<HigherOrderFunction> {
+    match identifier {
+        "map" => Some(HigherOrderFunction {
+            argument_amount: 2,
+            optional_argument_amount: 0,
+            function: |arguments, configuration| {
+                let function = expect_string(&arguments[1])?;
+                let mut result = Vec::new();
+                for element in expect_array(&arguments[0])? {
+                    result.push(call_function(
+                        function,
+                        slice::from_ref(element),
+                        configuration,
+                    )?);
+                }
+                Ok(Value::Array(result))
+            },
+        }),
+        "filter" => Some(HigherOrderFunction {
+            argument_amount: 2,
+            optional_argument_amount: 0,
+            function: |arguments, configuration| {
+                let function = expect_string(&arguments[1])?;
+                let mut result = Vec::new();
+                for element in expect_array(&arguments[0])? {
+                    let is_kept = call_function(function, slice::from_ref(element), configuration)?;
+                    if expect_boolean(&is_kept)? {
+                        result.push(element.clone());
+                    }
+                }
+                Ok(Value::Array(result))
+            },
+        }),
+        "reduce" => Some(HigherOrderFunction {
+            argument_amount: 3,
+            optional_argument_amount: 0,
+            function: |arguments, configuration| {
+                let function = expect_string(&arguments[1])?;
+                let mut accumulator = arguments[2].clone();
+                for element in expect_array(&arguments[0])? {
+                    accumulator =
+                        call_function(function, &[accumulator, element.clone()], configuration)?;
+                }
+                Ok(accumulator)
+            },
+        }),
+        "converge" => Some(HigherOrderFunction {
+            argument_amount: 2,
+            optional_argument_amount: 1,
+            function: |arguments, configuration| {
+                let function = expect_string(&arguments[1])?;
+                let max_iterations = match arguments.get(2) {
+                    Some(argument) => match expect_int(argument)? {
+                        int if int > 0 => int as usize,
+                        _ => return Err(Error::expected_positive_int(argument.clone())),
+                    },
+                    None => DEFAULT_MAX_CONVERGE_ITERATIONS,
+                };
+
+                let mut previous: Vec<Value> = Vec::new();
+                let mut value = arguments[0].clone();
+                for _ in 0..max_iterations {
+                    let next = call_function(function, slice::from_ref(&value), configuration)?;
+                    if next == value {
+                        return Ok(next);
+                    }
+                    if previous.contains(&next) {
+                        return Err(Error::CircularDependency);
+                    }
+                    if previous.len() == CONVERGE_CYCLE_WINDOW {
+                        previous.remove(0);
+                    }
+                    previous.push(value);
+                    value = next;
+                }
+                Err(Error::convergence_failure(max_iterations))
+            },
+        }),
+        // Functions of the configuration that call `recurse` in expressions they evaluate with the same configuration,
+        // recurse through this function, so its nesting is limited like the depth of expressions.
+        #[cfg(feature = "std")]
+        "recurse" => Some(HigherOrderFunction {
+            argument_amount: 1,
+            optional_argument_amount: usize::MAX,
+            function: |arguments, configuration| {
+                let function = expect_string(&arguments[0])?;
+                let max_depth = configuration
+                    .max_depth()
+                    .unwrap_or(DEFAULT_MAX_RECURSION_DEPTH);
+                let _guard = RecursionGuard::enter(max_depth)?;
+                call_function(function, &arguments[1..], configuration)
+            },
+        }),
+        _ => None,
+    }
+}
+
+/// Calls the function with the given identifier from the configuration, or the global or builtin function if the configuration does not contain it.
+fn call_function(
+    identifier: &str,
+    arguments: &[Value],
+    configuration: &dyn Configuration,
+) -> Result<Value, Error> {
+    configuration.on_function_call(identifier, arguments);
+    if let Some(function) = configuration.get_function(identifier) {
+        function.call(arguments)
+    } else if let Some(function) = default_function(identifier) {
+        function.call(arguments)
+    } else if let Some(function) = higher_order_function(identifier) {
+        function.call(arguments, configuration)
+    } else {
+        Err(Error::function_identifier_not_found(
+            identifier.to_string(),
+            None,
+        ))
+    }
+}
+
+/// Converts an index into a position in a sequence of the given length.
+/// Negative indices count from the end, and indices outside of the sequence are clamped to its bounds.
+fn clamp_index(index: IntType, len: usize) -> usize {
+    if index < 0 {
+        let from_end = index.unsigned_abs();
+        (len as u64).saturating_sub(from_end) as usize
+    } else {
+        (index as u64).min(len as u64) as usize
+    }
+}
+
+/// Creates a function that maps its single string argument to a new string.
+fn string_function(function: fn(&str) -> String) -> Function {
+    Function::new(
+        1,
+        Box::new(move |arguments| Ok(Value::String(function(expect_string(&arguments[0])?)))),
+    )
+}
+
+/// Creates a function that checks a relation between two string arguments.
+fn string_predicate(predicate: fn(&str, &str) -> bool) -> Function {
+    Function::new(
+        2,
+        Box::new(move |arguments| {
+            let string = expect_string(&arguments[0])?;
+            let other = expect_string(&arguments[1])?;
+            Ok(Value::Boolean(predicate(string, other)))
+        }),
+    )
+}
+
+/// Returns the elements of the given non-empty array of numbers.
+fn expect_non_empty_array(value: &Value) -> Result<&[Value], Error> {
+    match value {
+        Value::Array(array) if array.is_empty() => Err(Error::EmptyArray),
+        Value::Array(array) => {
+            for element in array {
+                expect_number(element)?;
+            }
+            Ok(array)
+        }
+        value => Err(Error::expected_array(value.clone())),
+    }
+}
+
+/// Returns the elements of the given non-empty array of numbers as floats.
+fn expect_numbers(value: &Value) -> Result<Vec<FloatType>, Error> {
+    Ok(expect_non_empty_array(value)?
+        .iter()
+        .map(|element| element.as_float().unwrap())
+        .collect())
+}
+
+fn mean(numbers: &[FloatType]) -> FloatType {
+    numbers.iter().sum::<FloatType>() / numbers.len() as FloatType
+}
+
+/// Combines the elements of the given non-empty array of numbers.
+/// The result is an integer if all elements are integers, and a float otherwise.
+fn fold_numbers(
+    value: &Value,
+    operator: &'static str,
+    int_operation: fn(IntType, IntType) -> Option<IntType>,
+    float_operation: fn(FloatType, FloatType) -> FloatType,
+) -> Result<Value, Error> {
+    let array = expect_non_empty_array(value)?;
+    if array.iter().all(Value::is_int) {
+        let mut result = array[0].as_int().unwrap();
+        for element in &array[1..] {
+            let element = element.as_int().unwrap();
+            result = int_operation(result, element)
+                .ok_or_else(|| Error::integer_overflow(operator, result, element))?;
+        }
+        Ok(Value::Int(result))
+    } else {
+        let mut result = array[0].as_float().unwrap();
+        for element in &array[1..] {
+            result = expect_finite(float_operation(result, element.as_float().unwrap()))?;
+        }
+        Ok(Value::Float(result))
+    }
+}
+
+/// The maximum amount of decimal places of formatted numbers, which is the largest precision supported by `format!`.
+const MAX_PRECISION: usize = u16::MAX as usize;
+
+/// Replaces the placeholders of the template with the given arguments in order.
+/// Supports `%s` for any value, `%d` for integers, `%f` for numbers with six decimals, `%.Nf` for numbers with `N` decimals and `%%` for a percent sign.
+fn sprintf(template: &str, arguments: &[Value]) -> Result<String, Error> {
+    let mut result = String::new();
+    let mut arguments = arguments.iter();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+
+        let mut precision = None;
+        if chars.peek() == Some(&'.') {
+            chars.next();
+            let mut digits = String::new();
+            while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                digits.push(digit);
+            }
+            if digits.is_empty() {
+                return Err(Error::FormatError(format!(
+                    "Expected a precision after '%.' in {:?}",
+                    template
+                )));
+            }
+            precision = Some(
+                digits
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|precision| *precision <= MAX_PRECISION)
+                    .ok_or_else(|| {
+                        Error::FormatError(format!(
+                            "The precision in {:?} exceeds the maximum of {}",
+                            template, MAX_PRECISION
+                        ))
+                    })?,
+            );
+        }
+
+        let placeholder = match (chars.next(), precision) {
+            (Some('%'), None) => {
+                result.push('%');
+                continue;
+            }
+            (Some(placeholder @ ('s' | 'd')), None) | (Some(placeholder @ 'f'), _) => placeholder,
+            _ => {
+                return Err(Error::FormatError(format!(
+                    "Invalid placeholder in {:?}",
+                    template
+                )))
+            }
+        };
+        let argument = arguments
+            .next()
+            .ok_or_else(|| Error::FormatError(format!("Too few arguments for {:?}", template)))?;
+        match placeholder {
+            's' => match argument {
+                Value::String(string) => result.push_str(string),
+                value => result.push_str(&value.to_string()),
+            },
+            'd' => match argument {
+                Value::Int(int) => result.push_str(&int.to_string()),
+                value => {
+                    return Err(Error::FormatError(format!(
+                        "Expected an integer for %d, but got {}",
+                        value
+                    )))
+                }
+            },
+            _ => match argument.as_float() {
+                Some(float) => result.push_str(&format!("{:.*}", precision.unwrap_or(6), float)),
+                None => {
+                    return Err(Error::FormatError(format!(
+                        "Expected a number for %f, but got {}",
+                        argument
+                    )))
+                }
+            },
+        }
+    }
+
+    if arguments.next().is_some() {
+        return Err(Error::FormatError(format!(
+            "Too many arguments for {:?}",
+            template
+        )));
+    }
+    Ok(result)
+}
+
+/// Converts a parsed JSON value into a value, where numbers are integers if they fit into `IntType`.
+#[cfg(feature = "json")]
+fn json_to_value(json: serde_json::Value) -> Value {
+    match json {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(boolean) => Value::Boolean(boolean),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(int) => Value::Int(int),
+            // Numbers that are not valid floats are rejected by the parser
+            None => Value::Float(number.as_f64().unwrap_or(FloatType::NAN)),
+        },
+        serde_json::Value::String(string) => Value::String(string),
+        serde_json::Value::Array(array) => {
+            Value::Array(array.into_iter().map(json_to_value).collect())
+        }
+        serde_json::Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| (key, json_to_value(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// Creates a function that encodes the UTF-8 bytes of its single string argument with the given base64 engine.
+#[cfg(feature = "encoding")]
+fn base64_encode_function(engine: &'static GeneralPurpose) -> Function {
+    Function::new(
+        1,
+        Box::new(move |arguments| Ok(Value::String(engine.encode(expect_string(&arguments[0])?)))),
+    )
+}
+
+/// Creates a function that decodes its single string argument with the given base64 engine into a UTF-8 string.
+#[cfg(feature = "encoding")]
+fn base64_decode_function(engine: &'static GeneralPurpose) -> Function {
+    Function::new(
+        1,
+        Box::new(move |arguments| {
+            let bytes = engine
+                .decode(expect_string(&arguments[0])?)
+                .map_err(|error| Error::DecodeError(error.to_string()))?;
+            String::from_utf8(bytes)
+                .map(Value::String)
+                .map_err(|error| Error::DecodeError(error.to_string()))
+        }),
+    )
+}
+
+/// Returns the 64-bit FNV-1a hash of the bytes.
+#[cfg(feature = "crypto")]
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(PRIME)
+    })
+}
+
+/// Returns the value of the environment variable with the given name, if it is set and valid unicode.
+#[cfg(feature = "env_access")]
+fn env_variable(name: &str) -> Option<Value> {
+    std::env::var(name).ok().map(Value::String)
+}
+
+/// Creates a function that applies `function` to its single numeric argument.
+fn float_function(function: fn(FloatType) -> FloatType) -> Function {
+    Function::new(
+        1,
+        Box::new(move |arguments| {
+            expect_number(&arguments[0])?;
+            Ok(Value::Float(function(arguments[0].as_float().unwrap())))
+        }),
+    )
+}
+
+/// Returns the greatest common divisor of the given numbers with the Euclidean algorithm, which is 0 if both are 0.
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let remainder = a % b;
+        a = b;
+        b = remainder;
+    }
+    a
+}
+
+/// Formats the number with exactly `decimal_places` decimal digits, rounding half away from zero.
+/// Negative decimal places round to the corresponding power of ten, such that `-2` rounds to hundreds.
+fn number_format(number: &Value, decimal_places: IntType) -> Result<String, Error> {
+    if decimal_places < 0 {
+        // Powers of ten that do not fit round every number to zero
+        let exponent = u32::try_from(decimal_places.unsigned_abs()).unwrap_or(u32::MAX);
+        return match number {
+            Value::Int(int) => {
+                let value = i128::from(*int);
+                let rounded = match 10i128.checked_pow(exponent) {
+                    Some(factor) => {
+                        let mut quotient = value / factor;
+                        if 2 * (value % factor).abs() >= factor {
+                            quotient += value.signum();
+                        }
+                        quotient * factor
+                    }
+                    None => 0,
+                };
+                IntType::try_from(rounded)
+                    .map(|rounded| rounded.to_string())
+                    .map_err(|_| Error::integer_overflow("number_format", *int, decimal_places))
+            }
+            number => {
+                let float = expect_finite(number.as_float().unwrap())?;
+                let factor = math::powf(10.0, exponent as FloatType);
+                let rounded = if factor.is_finite() {
+                    math::round(float / factor) * factor
+                } else {
+                    0.0
+                };
+                Ok(format!("{:.0}", rounded))
+            }
+        };
+    }
+
+    let decimal_places = usize::try_from(decimal_places)
+        .ok()
+        .filter(|decimal_places| *decimal_places <= MAX_PRECISION)
+        .ok_or_else(|| {
+            Error::FormatError(format!(
+                "Expected at most {} decimal places, but got {}",
+                MAX_PRECISION, decimal_places
+            ))
+        })?;
+    match number {
+        Value::Int(int) if decimal_places == 0 => Ok(int.to_string()),
+        Value::Int(int) => Ok(format!("{}.{}", int, "0".repeat(decimal_places))),
+        number => {
+            let float = expect_finite(number.as_float().unwrap())?;
+            // Formatting rounds ties to even, so round explicitly where floats are precise enough
+            let factor = math::powf(10.0, decimal_places as FloatType);
+            let scaled = float * factor;
+            let float = if scaled.is_finite() && math::abs(scaled) < (1u64 << 53) as FloatType {
+                math::round(scaled) / factor
+            } else {
+                float
+            };
+            Ok(format!("{:.*}", decimal_places, float))
+        }
+    }
+}
+
+/// Like `float_function`, but returns `Error::DomainError` if `function` is not defined for the argument, i.e. returns NaN.
+/// Unless the `permissive_floats` feature is enabled, infinite results for finite arguments, such as `ln(0)`, are domain errors as well.
+fn partial_float_function(name: &'static str, function: fn(FloatType) -> FloatType) -> Function {
+    Function::new(
+        1,
+        Box::new(move |arguments| {
+            expect_number(&arguments[0])?;
+            let argument = arguments[0].as_float().unwrap();
+            let result = function(argument);
+            if result.is_nan()
+                || (result.is_infinite()
+                    && argument.is_finite()
+                    && !cfg!(feature = "permissive_floats"))
+            {
+                Err(Error::domain_error(name, arguments[0].clone()))
+            } else {
+                Ok(Value::Float(expect_finite(result)?))
+            }
+        }),
+    )
+}
@@ -0,0 +1,62 @@
+use std::{convert::TryFrom, string::ToString, vec::Vec};
+
+use chrono::{DateTime, FixedOffset, NaiveDate, TimeZone, Utc};
+
+use error::{expect_int, expect_string, Error};
+use value::{IntType, Value};
+
+/// Returns the current time as milliseconds since the Unix epoch.
+pub(crate) fn now() -> IntType {
+    Utc::now().timestamp_millis()
+}
+
+/// Returns the timestamp in milliseconds of the given date and time.
+/// The arguments are the year, month, day, hour, minute and second, optionally followed by the timezone, which is UTC by default.
+pub(crate) fn timestamp(arguments: &[Value]) -> Result<IntType, Error> {
+    let components = arguments[..6]
+        .iter()
+        .map(expect_int)
+        .collect::<Result<Vec<IntType>, Error>>()?;
+    let offset = match arguments.get(6) {
+        Some(timezone) => parse_timezone(expect_string(timezone)?)?,
+        None => FixedOffset::east_opt(0).unwrap(),
+    };
+
+    let component = |index: usize| u32::try_from(components[index]).ok();
+    let date_time = i32::try_from(components[0])
+        .ok()
+        .and_then(|year| NaiveDate::from_ymd_opt(year, component(1)?, component(2)?))
+        .and_then(|date| date.and_hms_opt(component(3)?, component(4)?, component(5)?))
+        .ok_or_else(|| date_error(arguments))?;
+    offset
+        .from_local_datetime(&date_time)
+        .single()
+        .map(|date_time| date_time.timestamp_millis())
+        .ok_or_else(|| date_error(arguments))
+}
+
+/// Returns the given timestamp if it is within the range of supported dates.
+pub(crate) fn expect_timestamp(timestamp: IntType) -> Result<IntType, Error> {
+    match DateTime::from_timestamp_millis(timestamp) {
+        Some(_) => Ok(timestamp),
+        None => Err(Error::DateError(format!(
+            "Timestamp {} is out of range",
+            timestamp
+        ))),
+    }
+}
+
+/// Parses `UTC`, `Z` or an offset from UTC such as `+02:00`.
+fn parse_timezone(timezone: &str) -> Result<FixedOffset, Error> {
+    match timezone {
+        "UTC" | "Z" => Ok(FixedOffset::east_opt(0).unwrap()),
+        offset => offset
+            .parse()
+            .map_err(|_| Error::DateError(format!("Unknown timezone {:?}", timezone))),
+    }
+}
+
+fn date_error(arguments: &[Value]) -> Error {
+    let arguments: Vec<_> = arguments.iter().map(ToString::to_string).collect();
+    Error::DateError(format!("Invalid date ({})", arguments.join(", ")))
+}
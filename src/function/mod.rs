@@ -0,0 +1,411 @@
+#[cfg(feature = "async")]
+use error;
+use error::Error;
+#[cfg(feature = "std")]
+use std::sync::{Mutex, PoisonError};
+use std::{
+    any::Any,
+    boxed::Box,
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+#[cfg(feature = "async")]
+use std::{future::Future, pin::Pin};
+use value::{Value, ValueType};
+
+pub(crate) mod builtin;
+#[cfg(feature = "chrono")]
+mod date;
+#[cfg(feature = "regex")]
+mod regex;
+#[cfg(feature = "global_registry")]
+pub(crate) mod registry;
+
+pub type FunctionBody = Box<dyn Fn(&[Value]) -> Result<Value, Error> + Send + Sync>;
+pub type SharedFunctionBody = Arc<dyn Fn(&[Value]) -> Result<Value, Error> + Send + Sync>;
+
+/// A function that can be called from expressions.
+/// Cloning a function is cheap, since the function body is shared between clones.
+#[derive(Clone)]
+pub struct Function {
+    name: Option<String>,
+    argument_amount: usize,
+    /// The maximum amount of arguments, or `None` if any amount that is at least `argument_amount` is accepted.
+    max_argument_amount: Option<usize>,
+    /// The names of the parameters, which are empty unless the function accepts named arguments.
+    parameter_names: Vec<String>,
+    function: SharedFunctionBody,
+}
+
+impl Function {
+    pub fn new(argument_amount: usize, function: FunctionBody) -> Self {
+        Self::new_arc(argument_amount, Arc::from(function))
+    }
+
+    /// Creates a function from a function body that may already be shared with other functions.
+    pub fn new_arc(argument_amount: usize, function: SharedFunctionBody) -> Self {
+        Self {
+            name: None,
+            argument_amount,
+            max_argument_amount: Some(argument_amount),
+            parameter_names: Vec::new(),
+            function,
+        }
+    }
+
+    /// Creates a function with one parameter for each of the given names.
+    /// Besides positionally, its arguments can be given by name, as in `plot(data, color: "red", width: 2)`.
+    pub fn new_named(parameter_names: Vec<&str>, function: FunctionBody) -> Self {
+        Self {
+            parameter_names: parameter_names.iter().map(ToString::to_string).collect(),
+            ..Self::new(parameter_names.len(), function)
+        }
+    }
+
+    /// Creates a function that accepts at least `min_argument_amount` arguments, and at most `max_argument_amount` arguments unless it is `None`.
+    /// Calls with too few or too many arguments result in an `Error::WrongArgumentAmount` with the violated bound as expected amount.
+    ///
+    /// Returns `Error::InvalidArgumentAmountRange` if `max_argument_amount` is lower than `min_argument_amount`.
+    pub fn variadic(
+        min_argument_amount: usize,
+        max_argument_amount: Option<usize>,
+        function: FunctionBody,
+    ) -> Result<Self, Error> {
+        if let Some(max_argument_amount) = max_argument_amount {
+            if max_argument_amount < min_argument_amount {
+                return Err(Error::InvalidArgumentAmountRange {
+                    min: min_argument_amount,
+                    max: max_argument_amount,
+                });
+            }
+        }
+        Ok(Self {
+            max_argument_amount,
+            ..Self::new(min_argument_amount, function)
+        })
+    }
+
+    /// Creates a function that accepts `argument_amount` or more arguments.
+    pub(crate) fn new_variadic(argument_amount: usize, function: FunctionBody) -> Self {
+        Self {
+            max_argument_amount: None,
+            ..Self::new(argument_amount, function)
+        }
+    }
+
+    /// Returns the given function with the given name.
+    /// Errors returned by a named function are wrapped in `Error::FunctionError` with its name.
+    pub fn with_name(name: &str, function: Function) -> Self {
+        Self {
+            name: Some(name.to_string()),
+            ..function
+        }
+    }
+
+    /// Returns the name of this function, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the amount of arguments this function expects, which is the minimum amount for variadic functions.
+    pub fn argument_amount(&self) -> usize {
+        self.argument_amount
+    }
+
+    /// Returns the maximum amount of arguments this function accepts, or `None` if it is unlimited.
+    pub fn max_argument_amount(&self) -> Option<usize> {
+        self.max_argument_amount
+    }
+
+    /// Returns the names of the parameters of this function, which are empty unless it was created with `new_named`.
+    pub fn parameter_names(&self) -> &[String] {
+        &self.parameter_names
+    }
+
+    /// Orders the given arguments of a call of the function `identifier` by the positions of their parameters.
+    /// The positional arguments fill the first parameters in order, and each named argument fills the parameter of its name.
+    pub(crate) fn order_named_arguments(
+        &self,
+        identifier: &str,
+        positional: Vec<Value>,
+        named: Vec<(String, Value)>,
+    ) -> Result<Vec<Value>, Error> {
+        let mut arguments: Vec<Option<Value>> = positional.into_iter().map(Some).collect();
+        for (name, value) in named {
+            let index = self
+                .parameter_names
+                .iter()
+                .position(|parameter| *parameter == name)
+                .ok_or_else(|| {
+                    Error::unknown_argument_name(identifier.to_string(), name.clone())
+                })?;
+            if arguments.len() <= index {
+                arguments.resize(index + 1, None);
+            }
+            if arguments[index].is_some() {
+                return Err(Error::duplicate_argument_name(identifier.to_string(), name));
+            }
+            arguments[index] = Some(value);
+        }
+
+        let actual = arguments
+            .iter()
+            .filter(|argument| argument.is_some())
+            .count();
+        arguments
+            .into_iter()
+            .collect::<Option<Vec<Value>>>()
+            .ok_or_else(|| Error::wrong_argument_amount(actual, self.argument_amount))
+    }
+
+    /// Returns `Error::WrongArgumentAmount` if this function does not accept the given amount of arguments.
+    pub(crate) fn expect_argument_amount(&self, actual: usize) -> Result<(), Error> {
+        if actual < self.argument_amount {
+            Err(Error::wrong_argument_amount(actual, self.argument_amount))
+        } else {
+            match self.max_argument_amount {
+                Some(max) if actual > max => Err(Error::wrong_argument_amount(actual, max)),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    pub fn call(&self, arguments: &[Value]) -> Result<Value, Error> {
+        self.expect_argument_amount(arguments.len())?;
+        (self.function)(arguments).map_err(|error| match &self.name {
+            Some(name) => Error::function_error(name.clone(), error),
+            None => error,
+        })
+    }
+}
+
+pub type FunctionWithContextBody =
+    Box<dyn Fn(&[Value], &dyn Any) -> Result<Value, Error> + Send + Sync>;
+pub type SharedFunctionWithContextBody =
+    Arc<dyn Fn(&[Value], &dyn Any) -> Result<Value, Error> + Send + Sync>;
+
+/// A function that is called with a context in addition to its arguments, such as a database connection or a cache.
+/// The context is given when evaluating, for example with `eval_with_configuration_and_context`.
+/// Cloning a function with context is cheap, since the function body is shared between clones.
+#[derive(Clone)]
+pub struct FunctionWithContext {
+    argument_amount: usize,
+    function: SharedFunctionWithContextBody,
+}
+
+impl FunctionWithContext {
+    pub fn new(argument_amount: usize, function: FunctionWithContextBody) -> Self {
+        Self {
+            argument_amount,
+            function: Arc::from(function),
+        }
+    }
+
+    /// Returns the amount of arguments this function expects.
+    pub fn argument_amount(&self) -> usize {
+        self.argument_amount
+    }
+
+    /// Returns a function with the given name that calls this function with the given context.
+    pub(crate) fn bind(&self, name: &str, context: Arc<dyn Any + Send + Sync>) -> Function {
+        let function = self.function.clone();
+        Function::with_name(
+            name,
+            Function::new(
+                self.argument_amount,
+                Box::new(move |arguments| function(arguments, context.as_ref())),
+            ),
+        )
+    }
+}
+
+/// Builds a `Function` whose arguments are checked against declared types before its body is called, as in
+/// `TypedFunction::new().arg(ValueType::Int).arg(ValueType::Float).returning(ValueType::Boolean).body(|arguments| ...)`.
+///
+/// The function takes one argument per declared type.
+/// Arguments of the wrong type result in an `Error::TypeMismatch`, and results of the wrong type in an `Error::ReturnTypeMismatch`.
+/// The return type is `ValueType::Any` unless declared otherwise.
+#[derive(Clone, Debug)]
+pub struct TypedFunction {
+    argument_types: Vec<ValueType>,
+    return_type: ValueType,
+}
+
+impl TypedFunction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares the type of the next argument.
+    pub fn arg(mut self, argument_type: ValueType) -> Self {
+        self.argument_types.push(argument_type);
+        self
+    }
+
+    /// Declares the type of the result.
+    pub fn returning(mut self, return_type: ValueType) -> Self {
+        self.return_type = return_type;
+        self
+    }
+
+    /// Creates the function, which calls the given body with arguments of the declared types.
+    pub fn body<F>(self, body: F) -> Function
+    where
+        F: Fn(&[Value]) -> Result<Value, Error> + Send + Sync + 'static,
+    {
+        let TypedFunction {
+            argument_types,
+            return_type,
+        } = self;
+        Function::new(
+            argument_types.len(),
+            Box::new(move |arguments| {
+                for (argument_index, (argument, argument_type)) in
+                    arguments.iter().zip(&argument_types).enumerate()
+                {
+                    if !argument_type.matches(argument) {
+                        return Err(Error::type_mismatch(
+                            *argument_type,
+                            argument.clone(),
+                            argument_index,
+                        ));
+                    }
+                }
+                let result = body(arguments)?;
+                if return_type.matches(&result) {
+                    Ok(result)
+                } else {
+                    Err(Error::ReturnTypeMismatch {
+                        expected: return_type,
+                        actual: result,
+                    })
+                }
+            }),
+        )
+    }
+}
+
+impl Default for TypedFunction {
+    fn default() -> Self {
+        Self {
+            argument_types: Vec::new(),
+            return_type: ValueType::Any,
+        }
+    }
+}
+
+/// A function with state that is kept between calls, such as a counter that generates unique identifiers.
+/// The state is shared between all clones of the function, and calls lock it, such that concurrent calls are executed one after another.
+///
+/// Convert it into a `Function` to insert it into a configuration.
+#[cfg(feature = "std")]
+pub struct StatefulFunction<S> {
+    argument_amount: usize,
+    state: Arc<Mutex<S>>,
+    function: fn(&mut S, &[Value]) -> Result<Value, Error>,
+}
+
+#[cfg(feature = "std")]
+impl<S: Send + 'static> StatefulFunction<S> {
+    pub fn new(
+        argument_amount: usize,
+        initial_state: S,
+        function: fn(&mut S, &[Value]) -> Result<Value, Error>,
+    ) -> Self {
+        Self {
+            argument_amount,
+            state: Arc::new(Mutex::new(initial_state)),
+            function,
+        }
+    }
+
+    /// Returns the state of this function, which is shared with the functions created from it.
+    pub fn state(&self) -> Arc<Mutex<S>> {
+        self.state.clone()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: Send + 'static> From<StatefulFunction<S>> for Function {
+    fn from(stateful_function: StatefulFunction<S>) -> Self {
+        let StatefulFunction {
+            argument_amount,
+            state,
+            function,
+        } = stateful_function;
+        Function::new(
+            argument_amount,
+            Box::new(move |arguments| {
+                // A panic in an earlier call does not make the state unusable
+                let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+                function(&mut state, arguments)
+            }),
+        )
+    }
+}
+
+/// Returns the function with the given identifier that is available without configuration.
+/// These are the globally registered functions, followed by the builtin functions.
+pub(crate) fn default_function(identifier: &str) -> Option<Function> {
+    #[cfg(feature = "global_registry")]
+    {
+        if let Some(function) = registry::global_function(identifier) {
+            return Some(function);
+        }
+    }
+    builtin::builtin_function(identifier)
+}
+
+/// True if the function that is called with the given identifier when the configuration does not define it always returns the same result for the same arguments.
+pub(crate) fn is_pure_default_function(identifier: &str) -> bool {
+    #[cfg(feature = "global_registry")]
+    {
+        if registry::global_function(identifier).is_some() {
+            return false;
+        }
+    }
+    builtin::is_pure_builtin_function(identifier)
+}
+
+/// A boxed future that can be sent between threads.
+#[cfg(feature = "async")]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+#[cfg(feature = "async")]
+pub type AsyncFunctionBody =
+    Box<dyn Fn(Vec<Value>) -> BoxFuture<'static, Result<Value, Error>> + Send + Sync>;
+#[cfg(feature = "async")]
+pub type SharedAsyncFunctionBody =
+    Arc<dyn Fn(Vec<Value>) -> BoxFuture<'static, Result<Value, Error>> + Send + Sync>;
+
+/// A function that can be called from expressions evaluated with `eval_async`, and whose result is computed asynchronously.
+/// Cloning an async function is cheap, since the function body is shared between clones.
+#[cfg(feature = "async")]
+#[derive(Clone)]
+pub struct AsyncFunction {
+    argument_amount: usize,
+    function: SharedAsyncFunctionBody,
+}
+
+#[cfg(feature = "async")]
+impl AsyncFunction {
+    pub fn new(argument_amount: usize, function: AsyncFunctionBody) -> Self {
+        Self {
+            argument_amount,
+            function: Arc::from(function),
+        }
+    }
+
+    /// Returns the amount of arguments this function expects.
+    pub fn argument_amount(&self) -> usize {
+        self.argument_amount
+    }
+
+    pub fn call(&self, arguments: Vec<Value>) -> BoxFuture<'static, Result<Value, Error>> {
+        match error::expect_argument_amount(arguments.len(), self.argument_amount) {
+            Ok(()) => (self.function)(arguments),
+            Err(error) => Box::pin(std::future::ready(Err(error))),
+        }
+    }
+}
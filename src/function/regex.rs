@@ -0,0 +1,34 @@
+use std::{
+    string::{String, ToString},
+    sync::{Mutex, PoisonError},
+    vec::Vec,
+};
+
+use regex::Regex;
+
+use error::Error;
+
+/// The amount of compiled patterns that are kept for reuse.
+const CACHE_CAPACITY: usize = 16;
+
+/// The most recently used compiled patterns, with the most recently used one last.
+static CACHE: Mutex<Vec<(String, Regex)>> = Mutex::new(Vec::new());
+
+/// Returns the compiled pattern, compiling it only if it is not cached.
+/// Compiled patterns are cheap to clone, since their internals are shared.
+pub(crate) fn compile(pattern: &str) -> Result<Regex, Error> {
+    let mut cache = CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(position) = cache.iter().position(|(cached, _)| cached == pattern) {
+        let entry = cache.remove(position);
+        let regex = entry.1.clone();
+        cache.push(entry);
+        return Ok(regex);
+    }
+
+    let regex = Regex::new(pattern).map_err(|error| Error::RegexError(error.to_string()))?;
+    if cache.len() == CACHE_CAPACITY {
+        cache.remove(0);
+    }
+    cache.push((pattern.to_string(), regex.clone()));
+    Ok(regex)
+}
@@ -0,0 +1,40 @@
+use std::{
+    collections::BTreeMap,
+    string::String,
+    sync::{PoisonError, RwLock},
+};
+
+use function::Function;
+
+/// The functions that are available in all expressions, unless shadowed by the configuration.
+static GLOBAL_FUNCTIONS: RwLock<BTreeMap<String, Function>> = RwLock::new(BTreeMap::new());
+
+/// Registers a function that is available in all expressions, even if they are evaluated without configuration.
+/// Functions of the configuration shadow global functions with the same identifier, and global functions shadow builtin functions.
+/// If the function has no name, it is named after the given identifier.
+pub fn register_global_function(identifier: &str, function: Function) {
+    let function = match function.name() {
+        Some(_) => function,
+        None => Function::with_name(identifier, function),
+    };
+    GLOBAL_FUNCTIONS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(identifier.into(), function);
+}
+
+/// Removes the global function with the given identifier, and returns it if it was registered.
+pub fn unregister_global_function(identifier: &str) -> Option<Function> {
+    GLOBAL_FUNCTIONS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .remove(identifier)
+}
+
+pub(crate) fn global_function(identifier: &str) -> Option<Function> {
+    GLOBAL_FUNCTIONS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(identifier)
+        .cloned()
+}
@@ -10,136 +10,681 @@
 //! | * | Product |
 //! | / | Division |
 //! | % | Modulo |
+//! | ** | Exponentiation |
 //! | < | Lower than |
 //! | > | Greater than |
 //! | <= | Lower than or equal |
 //! | >= | Greater than or equal |
 //! | == | Equal |
 //! | != | Not equal |
-//! | && | Logical and |
-//! | || | Logical or |
-//! 
-//!Supported binary operators: `!` `!=` `""` `''` `()` `[]` `,` `>` `<` `>=` `<=` `==`
-//!`+` unary/binary `-` `*` `/` `%` `&&` `||` `n..m`.
+//! | &&, and | Logical and |
+//! | &#124;&#124;, or | Logical or |
+//! | ^^, xor | Logical exclusive or |
+//! | ?? | Null coalescing |
+//! | in | Membership |
+//! | .. | Integer range |
+//! | & | Bitwise and |
+//! | &#124; | Bitwise or |
+//! | ^ | Bitwise xor |
+//! | << | Left shift |
+//! | >> | Right shift |
 //!
-//!Supported unary operators: ``
+//! Supported unary operators:
 //!
-//!Built-in functions: `min()` `max()` `len()` `is_empty()` `array()` `converge()`.
-//!See the `builtin` module for a detailed description of each.
+//! | Operator | Description |
+//! |----------|-------------|
+//! | - | Negation |
+//! | !, not | Logical not |
+//! | ~ | Bitwise not |
+//!
+//! The null-coalescing operator `a ?? b` evaluates to `a` unless `a` is `null`, in which case it evaluates to `b`.
+//! Its precedence is lower than `||`, and `b` is only evaluated if `a` is `null`.
+//!
+//! The ternary operator `condition ? a : b` evaluates to `a` if `condition` is true and to `b` otherwise.
+//! It has the lowest precedence of all operators except `;`, and only the selected branch is evaluated.
+//!
+//! Several expressions can be separated by semicolons, such as `1; 2; 3`.
+//! They are evaluated in order, and the value of the last expression is returned.
+//! A trailing semicolon ends the sequence with `null`, such that `1;` is `null`.
+//!
+//! The assignment operator `a = b` sets the variable `a` to the value of `b` and evaluates to that value.
+//! It is right-associative, and its precedence is between the ternary operator and the comma.
+//! The compound assignments `+=`, `-=`, `*=`, `/=` and `%=` have the same precedence, and `a += b` is evaluated like `a = a + b`.
+//! Their variable must already exist.
+//! Assignments require a `MutableConfiguration`, such as a `HashMapConfiguration` passed to `eval_with_mutable_configuration`.
+//!
+//! Conditionals can also be written as `if condition { a } else { b }`, where `a` and `b` can be sequences separated by semicolons.
+//! Chains such as `if a { 1 } else if b { 2 } else { 3 }` are supported, and a missing else-branch evaluates to `null`.
+//! Like the ternary operator, only the selected branch is evaluated.
+//! The branch is evaluated in a new scope of the configuration, such that variables assigned in it are discarded afterwards if the configuration is a `ScopedConfiguration`.
+//! Other configurations, such as `HashMapConfiguration`, keep them.
+//!
+//! The special form `if_error(a, b)` evaluates to `a` unless the evaluation of `a` results in an error, in which case it evaluates to `b`.
+//! For example, `if_error(1 / 0, 0)` is `0`, and `if_error(x, 5)` is `5` if the variable `x` does not exist.
+//! Like a function call, it is written with braces, but `b` is only evaluated if `a` fails, and errors of `b` are returned as usual.
+//! It cannot be overridden by a function of the configuration.
+//!
+//! The expression `let x = a in b` evaluates `b` with the variable `x` bound to the value of `a`, such that `let x = 2 * pi in x * x` computes `a` only once.
+//! Bindings can be chained, such as `let x = 1 in let y = 2 in x + y`, and inner bindings shadow outer ones and variables of the configuration.
+//! The binding is not written to the configuration, so `let` also works with immutable configurations.
+//! The body extends up to the closing brace or block around the `let`, or up to the next comma, semicolon or colon outside of braces.
+//! A membership test `in` inside the bound value has to be enclosed in braces, such as `let b = (1 in a) in b`.
+//!
+//! Integer arithmetic is checked: if `+`, `-`, `*`, `/`, `%`, `**` or negation overflows, the result is an `Error::IntegerOverflow`,
+//! and dividing an integer by zero or computing its remainder is an `Error::DivisionByZero`.
+//! Shifting by a negative amount or by 64 or more bits with `<<` or `>>` is an `Error::IntegerOverflow` as well.
+//! Float arithmetic does not silently produce non-finite numbers either: dividing a float by zero is an `Error::DivisionByZero`,
+//! and results that are not a number or infinite are an `Error::NaN` or an `Error::Infinity`.
+//! Math functions such as `sqrt` or `ln` return an `Error::DomainError` for arguments they are not defined for, including `ln(0)`.
+//!
+//! String literals are enclosed in double quotes (`"foo"`) or single quotes (`'foo'`).
+//! They may contain the escape sequences `\n`, `\t`, `\r`, `\\`, `\"` and `\'`, as well as unicode scalar values as `\u00e9` or `\u{1F600}`.
+//! Other characters after a backslash result in an `Error::UnknownEscapeSequence`.
+//!
+//! Multiplying a string with a non-negative integer repeats the string, such that `"ab" * 3` is `"ababab"`.
+//! Adding two strings concatenates them, such that `"ab" + "cd"` is `"abcd"`.
+//! Other values are deliberately never converted to strings implicitly, so `"a" + 1` is an `Error::ExpectedNumber`; use `to_string` to convert them explicitly.
+//!
+//! The membership operator `a in b` checks if the array `b` contains an element equal to `a`, or if the string `b` contains the string `a`.
+//! It has the same precedence as the comparison operators.
+//!
+//! The range operator `a..b` evaluates to the array of integers from `a` inclusive to `b` exclusive, such that `-1..2` is `[-1, 0, 1]`.
+//! Both bounds can be arbitrary integer expressions, and the range is empty if `b` is not greater than `a`.
+//! Its precedence is between the additive and the comparison operators, so `1..n + 1` ends at `n + 1`.
+//!
+//! Elements of arrays are accessed by their index starting at zero, such as `a[0]`, and indices that are out of bounds are an error.
+//! Values of objects are accessed by their key, such as `o['key']`, and missing keys are `null`.
+//! Indexing binds tighter than all operators except function calls, so `-a[0]` is `-(a[0])`.
+//!
+//! Comma-separated values outside of function calls are tuples, such as `(1, 2)`.
+//! A tuple with a single element needs a trailing comma, such as `(1,)`.
+//! Tuples support `len`, indexing and `in` like arrays.
+//!
+//! The exclusive or `a ^^ b`, which can also be written as `a xor b`, is true if exactly one of the booleans is true.
+//! Its precedence is between `&&` and `||`.
+//!
+//! The bitwise operators are only defined for integers.
+//! Expressions may contain line comments starting with `//` and block comments enclosed in `/*` and `*/`.
+//! Block comments can be nested.
+//!
+//! Float literals can be written in scientific notation, such as `1.5e10` or `3.2E-4`.
+//! Integer literals can also be written in hexadecimal (`0xFF`), octal (`0o17`) or binary (`0b1010`) notation.
+//! Exponentiation is right-associative and binds tighter than unary minus, so `-2 ** 2` is `-4`.
+//!
+//! ### Builtin functions
+//!
+//! The following functions are available in all expressions, unless the configuration defines a function with the same identifier.
+//!
+//! | Function | Description |
+//! |----------|-------------|
+//! | abs(x) | Absolute value, an integer for integer arguments |
+//! | floor(x) | Largest integer less than or equal to `x`, as float |
+//! | ceil(x) | Smallest integer greater than or equal to `x`, as float |
+//! | round(x) | Nearest integer to `x`, rounding half-way cases away from zero, as float |
+//! | sqrt(x) | Square root |
+//! | ln(x) | Natural logarithm |
+//! | log2(x) | Base 2 logarithm |
+//! | log10(x) | Base 10 logarithm |
+//! | sin(x), cos(x), tan(x) | Trigonometric functions of an angle in radians |
+//! | asin(x), acos(x), atan(x) | Inverse trigonometric functions, returning an angle in radians |
+//! | atan2(y, x) | Angle in radians of the point `(x, y)`, in the correct quadrant |
+//! | pow(x, y) | `x` to the power of `y`, an integer if both are integers and `y` is non-negative, and an error if both are integers and `y` is negative |
+//! | gcd(a, b), lcm(a, b) | Greatest common divisor and least common multiple of two integers, which are non-negative |
+//! | clamp(x, min, max) | `min` if `x` is less than `min`, `max` if `x` is greater than `max`, and `x` otherwise, as a float if any argument is a float |
+//! | number_format(x, decimal_places) | The number as string with exactly `decimal_places` decimal digits, rounded half away from zero, where negative decimal places round to a power of ten and at most 65535 decimal places are allowed |
+//! | sum(a), product(a) | Sum and product of an array of numbers, an integer if all elements are integers |
+//! | avg(a) | Arithmetic mean of an array of numbers |
+//! | stdev(a) | Population standard deviation of an array of numbers |
+//! | len(x) | Amount of characters of a string, or amount of elements of an array, tuple or object |
+//! | char_at(s, i) | The character at the index of the string as string, where negative indices count from the end |
+//! | char_code(c), from_char_code(n) | The unicode code point of the single-character string as integer, and the character of the code point as string |
+//! | trim(s), to_upper(s), to_lower(s) | The string without leading and trailing whitespace, in upper case or in lower case |
+//! | starts_with(s, prefix), ends_with(s, suffix), contains(s, substring) | True if the string starts with, ends with or contains the other string |
+//! | replace(s, from, to) | The string with all occurrences of `from` replaced by `to` |
+//! | split(s, delimiter) | The array of parts of the string separated by the delimiter |
+//! | sprintf(template, ...) | The template with its placeholders replaced by the following arguments in order, see below |
+//! | sort(a) | The array of numbers or strings in ascending order |
+//! | reverse(a) | The array in reverse order |
+//! | unique(a) | The array without duplicate elements, compared with `Value::deep_equal` such that `NaN` is a duplicate of `NaN` |
+//! | flatten(a) | The array with all elements that are arrays replaced by their elements |
+//! | slice(a, start, end) | The elements from `start` inclusive to `end` exclusive, where negative indices count from the end |
+//! | first(a), last(a) | The first or last element of the non-empty array |
+//! | type_of(x) | The type of the value as string, one of `"int"`, `"float"`, `"boolean"`, `"string"`, `"array"`, `"tuple"`, `"object"` and `"null"` |
+//! | to_int(x) | The value as integer, truncating floats, converting booleans to 0 or 1 and parsing strings |
+//! | to_float(x) | The value as float, converting integers and parsing strings |
+//! | to_string(x) | The value as string |
+//! | to_bool(x) | True for non-zero numbers, non-empty strings, arrays and objects, and `true` |
+//! | map(a, f) | The array of the results of calling the function named `f` on each element |
+//! | filter(a, f) | The array of elements for which the function named `f` returns `true` |
+//! | reduce(a, f, initial) | The result of calling the function named `f` with the accumulator and each element in turn, starting with `initial` |
+//! | converge(x, f), converge(x, f, max_iterations) | The fixed point reached by calling the function named `f` repeatedly, starting with `x` |
+//! | recurse(f, ...) | The result of calling the function named `f` with the following arguments, requires the `std` feature |
+//!
+//! `converge` gives up with `Error::ConvergenceFailure` after `max_iterations` calls, which are 1000 by default.
+//! If a value repeats one of the last 16 values without being a fixed point, it fails with `Error::CircularDependency` instead.
+//!
+//! `recurse` allows functions of the configuration to call themselves or each other, if they evaluate expressions containing `recurse` with the same configuration.
+//! The calls of `recurse` that are evaluated at the same time on a thread are limited by `Configuration::max_depth`, or to 64 if the configuration has no limit, and exceeding it results in an `Error::MaxDepthExceeded`.
+//!
+//! If a function is not defined for its argument, such as the square root of a negative number, it returns an `Error::DomainError`.
+//!
+//! The template of `sprintf` may contain the placeholders `%s` for any value, `%d` for integers, `%f` for numbers with six decimals, `%.2f` for numbers with the given amount of decimals, and `%%` for a percent sign.
+//! Strings are inserted without quotes, such that `sprintf("%s has %d items", "cart", 3)` is `"cart has 3 items"`.
+//! Invalid placeholders, precisions above 65535, arguments of the wrong type and a mismatch between the amounts of placeholders and arguments result in an `Error::FormatError`.
+//!
+//! Arguments of functions are separated by commas, such as in `atan2(1, 2)`, and functions without arguments are called with empty braces, such as `f()`.
+//! The spread operator `...` passes the elements of an array or tuple as separate arguments, such that `atan2(...a)` is `atan2(a[0], a[1])`.
+//!
+//! To protect against stack overflows, expressions are evaluated up to a maximum nesting depth, which is 512 for `eval`.
+//! Deeper expressions fail with `Error::MaxDepthExceeded`.
+//! Configurations can set their own limit with `Configuration::max_depth`, and `HashMapConfiguration::set_max_depth` sets it for hash map configurations, which are unlimited by default.
+//! Similarly, `Configuration::max_node_count` limits the size of evaluated expressions, as counted by `Node::node_count`.
+//! `Configuration::max_string_length` and `Configuration::max_array_length` limit the length of strings and arrays created during evaluation, such as by `'ab' * 1000` or `0..1000`.
+//! Exceeding them results in an `Error::StringTooLong` or an `Error::ArrayTooLong`, and `HashMapConfiguration::set_max_string_length` and `HashMapConfiguration::set_max_array_length` set them for hash map configurations.
+//! Without a limit on the length of arrays, ranges are limited to 2^24 elements, and without a limit on the length of strings, repeated strings are limited to 2^30 bytes.
+//!
+//! Functions that accept a varying amount of arguments can be created with `Function::variadic(min_argument_amount, max_argument_amount, function)`, where a maximum of `None` means that there is no limit.
+//! Calls with an amount outside of this range result in an `Error::WrongArgumentAmount`, and a maximum that is lower than the minimum is an `Error::InvalidArgumentAmountRange`.
+//!
+//! Functions created with `Function::new_named(parameter_names, function)` also accept their arguments by name, such as `plot(x: data, color: "red", width: 2)`.
+//! Positional arguments fill the first parameters in order, regardless of where they appear in the call, and named arguments fill the parameters of their names.
+//! Names that are not parameters of the function result in an `Error::UnknownArgumentName`, and parameters that are given twice in an `Error::DuplicateArgumentName`.
+//! Builtin functions and functions created otherwise have no parameter names.
+//!
+//! Functions that need shared state, such as a database connection, can be inserted with `HashMapConfiguration::insert_function_with_context`.
+//! They receive a context of any type in addition to their arguments, which is given to `eval_with_configuration_and_context`.
+//! Functions that keep state between calls, such as counters, can be created with `StatefulFunction::new(argument_amount, initial_state, function)` and converted into a `Function`.
+//! The function receives a mutable reference to the state, which is shared between all clones of the function.
+//! Functions whose arguments have fixed types can be created with `TypedFunction`, as in `TypedFunction::new().arg(ValueType::Int).returning(ValueType::String).body(|arguments| ...)`.
+//! Its body is only called with arguments of the declared types, and other arguments result in an `Error::TypeMismatch`.
+//!
+//! Where can eval be used?
+//! -----------------------
+//!
+//! * Template engine
+//! * Scripting language
+//! * ...
+//!
+//! Usage
+//! -----
+//!
+//! Add dependency to Cargo.toml
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = "0.5"
+//! ```
+//!
+//! In your `main.rs` or `lib.rs`:
+//!
+//! ```rust
+//! extern crate evalexpr;
+//! ```
+//!
+//! To serialize and deserialize values and configurations with [serde](https://serde.rs), enable the `serde` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", features = ["serde"] }
+//! ```
+//!
+//! Values are serialized as the corresponding primitive, and `null` as unit.
+//! Configurations are serialized as a map of their variables, functions are skipped.
+//!
+//! Operator trees can be serialized as well, for example to cache compiled expressions with a binary format such as [postcard](https://crates.io/crates/postcard).
+//! Function calls are stored by the identifier of the function, which is looked up when the deserialized tree is evaluated.
+//! The serialized trees contain the version of their format, and deserializing a tree of another version fails.
+//!
+//! To call async functions from expressions, enable the `async` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", features = ["async"] }
+//! ```
+//!
+//! Async functions are inserted with `HashMapConfiguration::insert_async_function`, and expressions that call them are evaluated with `eval_async`, which returns a future.
+//!
+//! To use evalexpr from JavaScript with [wasm-bindgen](https://rustwasm.github.io/docs/wasm-bindgen/), enable the `wasm` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", features = ["wasm"] }
+//! ```
+//!
+//! This exports the functions `eval(expression)` and `evalWithConfiguration(expression, variables)` to JavaScript, where `variables` is a plain object whose properties are the variables.
+//! Numbers are converted to JavaScript numbers, arrays and tuples to arrays, objects to plain objects, and errors are thrown as `Error`s.
+//!
+//! To register functions that are available in all expressions, even those evaluated without configuration, enable the `global_registry` feature and call `register_global_function`.
+//! Functions of the configuration shadow global functions with the same identifier.
+//!
+//! To create configurations from structs, add the `evalexpr-derive` crate and derive `IntoConfiguration`:
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr-derive = "0.5"
+//! ```
+//!
+//! `#[derive(IntoConfiguration)]` on a struct with named fields implements `IntoConfiguration::into_configuration`, which returns a `HashMapConfiguration` with a variable for each field.
+//! Fields are converted with `Into<Value>`, and fields marked with `#[evalexpr(skip)]` are left out.
+//!
+//! To match strings against regular expressions, enable the `regex` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", features = ["regex"] }
+//! ```
+//!
+//! This adds the builtin functions `regex_match(s, pattern)`, which is true if the pattern matches anywhere in the string, and `regex_replace(s, pattern, replacement)`, which replaces all matches.
+//! The replacement can refer to capture groups, such as `$1`.
+//! Patterns use the syntax of the [regex](https://crates.io/crates/regex) crate, recently used patterns are cached, and invalid patterns result in an `Error::RegexError`.
+//! Backslashes in patterns must be escaped in string literals, such as in `regex_match(s, '\\d+')`.
+//!
+//! To parse JSON, enable the `json` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", features = ["json"] }
+//! ```
+//!
+//! This adds the builtin function `parse_json(s)`, which converts JSON null, booleans, strings, arrays and objects to the corresponding values.
+//! Numbers are integers if they fit into an integer, and floats otherwise.
+//! The keys of objects keep their order, and the parsed values can be accessed with the index operator, such as `parse_json(s)['a'][0]`.
+//! Invalid JSON results in an `Error::JsonError`.
+//!
+//! To encode and decode strings as base64, enable the `encoding` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", features = ["encoding"] }
+//! ```
+//!
+//! This adds the builtin functions `base64_encode(s)` and `base64_decode(s)` with the standard alphabet of RFC 4648,
+//! as well as `base64_url_encode(s)` and `base64_url_decode(s)` with its URL-safe alphabet.
+//! Encoded strings are padded, and strings are encoded as UTF-8.
+//! Invalid base64 and decoded bytes that are not valid UTF-8 result in an `Error::DecodeError`.
+//!
+//! To compute hashes of strings, enable the `crypto` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", features = ["crypto"] }
+//! ```
 //!
-//!Where can eval be used?
-//!-----------------------
+//! This adds the builtin function `hash(s, algorithm)`, which returns the hash of the UTF-8 encoding of the string as lowercase hexadecimal string.
+//! The algorithm is one of `"sha256"`, `"sha1"`, `"md5"`, `"fnv"` for the 64-bit FNV-1a hash and `"xxhash"` for the 64-bit xxHash with seed 0.
+//! MD5 and SHA-1 are broken, and should only be used where security does not matter.
+//! Other algorithms result in an `Error::UnknownHashAlgorithm`.
 //!
-//!* Template engine
-//!* Scripting language
-//!* ...
+//! To generate random identifiers, enable the `uuid` feature:
 //!
-//!Usage
-//!-----
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", features = ["uuid"] }
+//! ```
 //!
-//!Add dependency to Cargo.toml
+//! This adds the builtin function `uuid()`, which returns a random UUID of version 4 in the hyphenated format, such as `"67e55044-10b1-426f-9247-bb680e5fe0c8"`.
+//! Since its result differs between calls, expressions calling it are not constant according to `Node::is_const`.
 //!
-//!```toml
-//![dependencies]
-//!evalexpr = "0.4"
-//!```
+//! To read environment variables, enable the `env_access` feature:
 //!
-//!In your `main.rs` or `lib.rs`:
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", features = ["env_access"] }
+//! ```
 //!
-//!```rust
-//!extern crate evalexpr as eval;
-//!```
+//! This adds the builtin functions `env(name)`, which returns the value of the environment variable as string, or `null` if it is not set,
+//! and `env_or(name, default)`, which returns `default` instead of `null`.
+//! Variables whose value is not valid unicode are treated as not set.
 //!
-//!Examples
-//!--------
+//! **Security note:** with this feature, every expression can read all environment variables of the process, which often contain secrets.
+//! Do not enable it if expressions come from untrusted sources.
+//! Since builtin functions cannot be disabled, this holds for all expressions evaluated by the program.
 //!
-//!You can do mathematical calculations with supported operators:
+//! To work with dates and times, enable the `chrono` feature:
 //!
-//!```rust
-//!use eval::{eval, to_value};
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", features = ["chrono"] }
+//! ```
 //!
-//!assert_eq!(eval("1 + 2 + 3"), Ok(to_value(6)));
-//!assert_eq!(eval("2 * 2 + 3"), Ok(to_value(7)));
-//!assert_eq!(eval("2 / 2 + 3"), Ok(to_value(4.0)));
-//!assert_eq!(eval("2 / 2 + 3 / 3"), Ok(to_value(2.0)));
-//!```
+//! This adds builtin functions that represent points in time as integer timestamps in milliseconds since the Unix epoch:
 //!
-//!You can eval with context:
+//! | Function | Description |
+//! |----------|-------------|
+//! | now() | The current time |
+//! | timestamp(year, month, day, hour, minute, second) | The given date and time in UTC |
+//! | timestamp(year, month, day, hour, minute, second, timezone) | The given date and time in the timezone `"UTC"` or an offset such as `"+02:00"` |
+//! | date_add(t, ms) | The timestamp `ms` milliseconds after `t` |
+//! | date_diff(t1, t2) | The milliseconds from `t2` to `t1` |
+//!
+//! Invalid dates, unknown timezones and timestamps outside of the supported range result in an `Error::DateError`.
+//! Like all builtin functions, `now` can be replaced by a function of the configuration, for example to fix the time in tests.
+//!
+//! To make `+`, `-`, `*`, `/`, `%` and negation of integers wrap around on overflow instead, enable the `wrapping_arithmetic` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", features = ["wrapping_arithmetic"] }
+//! ```
+//!
+//! Exponentiation and division by zero remain errors with this feature.
+//!
+//! To get the IEEE 754 results of float arithmetic, such as infinity for `1.0 / 0` and NaN for `0.0 / 0`, enable the `permissive_floats` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", features = ["permissive_floats"] }
+//! ```
+//!
+//! Math functions still return an `Error::DomainError` for NaN results with this feature.
+//!
+//! To evaluate independent sub-expressions in parallel with [rayon](https://crates.io/crates/rayon), enable the `rayon` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", features = ["rayon"] }
+//! ```
+//!
+//! Then `Node::eval_parallel` evaluates the two operands of binary operators in parallel if neither of them is a leaf, such that the functions in `compute_a() + compute_b()` are called in parallel.
+//! It requires a configuration that can be shared between threads, and functions may be called concurrently.
+//!
+//! To measure which sub-expressions take the most time, enable the `tracing` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", features = ["tracing"] }
+//! ```
+//!
+//! Then each evaluated node is wrapped in a [tracing](https://crates.io/crates/tracing) span named `eval_node` at the trace level, with the fields `operator` and `node` that display the operator and the sub-expression.
+//! The spans are nested like the operator tree, such that subscribers like `tracing-subscriber` or flamegraph exporters show the time spent in each sub-expression.
+//!
+//! To cache the results of expensive variable lookups, enable the `lru` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", features = ["lru"] }
+//! ```
+//!
+//! Then `LruCachedConfiguration::new(&configuration, capacity)` borrows a configuration and looks up each variable only once until it is evicted from the cache or invalidated with `invalidate` or `invalidate_all`.
+//!
+//! To use evalexpr in `no_std` environments that provide `alloc`, disable the default `std` feature:
+//!
+//! ```toml
+//! [dependencies]
+//! evalexpr = { version = "0.5", default-features = false }
+//! ```
+//!
+//! Without `std`, `HashMapConfiguration` stores its entries in B-tree maps, the keys of objects are hashed with FNV-1a, and float functions are computed with [libm](https://crates.io/crates/libm).
+//!
+//! Examples
+//! --------
+//!
+//! You can do mathematical calculations with supported operators:
+//!
+//! ```rust
+//! use evalexpr::{eval, Value};
+//!
+//! assert_eq!(eval("1 + 2 + 3"), Ok(Value::Int(6)));
+//! assert_eq!(eval("2 * 2 + 3"), Ok(Value::Int(7)));
+//! assert_eq!(eval("2 / 2 + 3"), Ok(Value::Int(4)));
+//! assert_eq!(eval("2 / 2 + 3 / 2.0"), Ok(Value::Float(2.5)));
+//! assert_eq!(eval("6 & 3 | 8"), Ok(Value::Int(10)));
+//! ```
+//!
+//! If the type of the result is known, it can be extracted directly:
+//!
+//! ```rust
+//! use evalexpr::{eval_as_bool, eval_as_int, Error, Value};
+//!
+//! assert_eq!(eval_as_int("1 + 2 * 3"), Ok(7));
+//! assert_eq!(eval_as_bool("1 < 2"), Ok(true));
+//! assert_eq!(eval_as_bool("1 + 2"), Err(Error::expected_boolean(Value::Int(3))));
+//! ```
+//!
+//! With the `std` feature, `Error` implements `std::error::Error`, such that it can be propagated with `?` into boxed errors:
+//!
+//! ```rust
+//! # #[cfg(feature = "std")]
+//! # fn main() {
+//! use evalexpr::{eval, EvalResult};
+//!
+//! fn double(expression: &str) -> Result<i64, Box<dyn std::error::Error + Send + Sync>> {
+//!     let value: EvalResult = eval(expression);
+//!     Ok(value?.into_int()? * 2)
+//! }
+//!
+//! assert_eq!(double("1 + 2").unwrap(), 6);
+//! assert!(double("1 + true").is_err());
+//! # }
+//! # #[cfg(not(feature = "std"))]
+//! # fn main() {}
+//! ```
+//!
+//! Values support the arithmetic operators of Rust with the same semantics as in expressions.
+//! The operators panic on errors, such as on a type mismatch or a division by zero, while methods like `Value::checked_add` return them:
 //!
-//!```rust
-//!use eval::{Expr, to_value};
+//! ```rust
+//! use evalexpr::{Error, Value};
 //!
-//!assert_eq!(Expr::new("foo == bar")
-//!               .value("foo", true)
-//!               .value("bar", true)
-//!               .exec(),
-//!           Ok(to_value(true)));
-//!```
+//! assert_eq!(Value::Int(2) + Value::Float(0.5), Value::Float(2.5));
+//! assert_eq!(Value::from("ab") * Value::Int(2), Value::from("abab"));
+//! assert_eq!(Value::Int(1).checked_div(Value::Int(0)), Err(Error::DivisionByZero));
+//! ```
 //!
-//!You can access data like javascript by using `.` and `[]`. `[]` supports expression.
+//! You can eval with configuration:
 //!
-//!```rust
-//!use eval::{Expr, to_value};
-//!use std::collections::HashMap;
+//! ```rust
+//! use evalexpr::{eval_with_configuration, HashMapConfiguration, Value};
 //!
-//!let mut object = HashMap::new();
-//!object.insert("foos", vec!["Hello", "world", "!"]);
+//! let mut configuration = HashMapConfiguration::new();
+//! configuration.insert_variable("foo".to_string(), Value::Boolean(true));
+//! // Primitive Rust values are converted to values automatically
+//! configuration.insert_variable("bar".to_string(), true);
 //!
-//!assert_eq!(Expr::new("object.foos[1-1] == 'Hello'")
-//!               .value("object", object)
-//!               .exec(),
-//!           Ok(to_value(true)));
-//!```
+//! assert_eq!(eval_with_configuration("foo == bar", &configuration), Ok(Value::Boolean(true)));
+//! ```
 //!
-//!You can eval with function:
+//! You can eval with function:
 //!
-//!```rust
-//!use eval::{Expr, to_value};
+//! ```rust
+//! use evalexpr::{eval_with_configuration, Function, HashMapConfiguration, Value};
 //!
-//!assert_eq!(Expr::new("say_hello()")
-//!               .function("say_hello", |_| Ok(to_value("Hello world!")))
-//!               .exec(),
-//!           Ok(to_value("Hello world!")));
-//!```
+//! let mut configuration = HashMapConfiguration::new();
+//! configuration.insert_function(
+//!     "double".to_string(),
+//!     Function::new(1, Box::new(|arguments| Ok(Value::Int(arguments[0].clone().into_int()? * 2)))),
+//! );
 //!
-//!You can create an array with `array()`:
+//! assert_eq!(eval_with_configuration("double(21)", &configuration), Ok(Value::Int(42)));
+//! ```
 //!
-//!```rust
-//!use eval::{eval, to_value};
+//! Configurations can also be built by chaining calls:
 //!
-//!assert_eq!(eval("array(1, 2, 3, 4, 5)"), Ok(to_value(vec![1, 2, 3, 4, 5])));
-//!```
+//! ```rust
+//! use evalexpr::{eval_with_configuration, HashMapConfiguration, Value};
 //!
-//!You can create an integer array with `n..m`:
+//! let configuration = HashMapConfiguration::builder()
+//!     .var("a", 20)
+//!     .func("inc", 1, |arguments| Ok(Value::Int(arguments[0].clone().into_int()? + 1)))
+//!     .build();
 //!
-//!```rust
-//!use eval::{eval, to_value};
+//! assert_eq!(eval_with_configuration("inc(a) * 2", &configuration), Ok(Value::Int(42)));
+//! ```
 //!
-//!assert_eq!(eval("0..5"), Ok(to_value(vec![0, 1, 2, 3, 4])));
-//!```
+//! Expressions can assign variables of mutable configurations:
 //!
-//!License
-//!-------
+//! ```rust
+//! use evalexpr::{eval_with_mutable_configuration, Configuration, HashMapConfiguration, Value};
 //!
-//!evalexpr is primarily distributed under the terms of the MIT license.
-//!See [LICENSE](LICENSE) for details. 
+//! let mut configuration = HashMapConfiguration::new();
+//! assert_eq!(eval_with_mutable_configuration("a = 3; a + 2", &mut configuration), Ok(Value::Int(5)));
+//! assert_eq!(configuration.get_value("a"), Some(&Value::Int(3)));
+//! ```
 //!
+//! To audit which variables and functions an expression uses, wrap the configuration in an `AuditingConfiguration`.
+//! It notifies its callbacks of every variable access and function call during evaluation, including calls of builtin functions:
+//!
+//! ```rust
+//! use std::sync::{Arc, Mutex};
+//! use evalexpr::{eval_with_configuration, AuditingConfiguration, HashMapConfiguration, Value};
+//!
+//! let accessed = Arc::new(Mutex::new(Vec::new()));
+//! let log = accessed.clone();
+//! let configuration = AuditingConfiguration::new(
+//!     HashMapConfiguration::builder().var("a", 2).build(),
+//!     Arc::new(move |identifier, _value| log.lock().unwrap().push(identifier.to_string())),
+//!     Arc::new(|_identifier, _arguments| {}),
+//! );
+//!
+//! assert_eq!(eval_with_configuration("a * a", &configuration), Ok(Value::Int(4)));
+//! assert_eq!(*accessed.lock().unwrap(), vec!["a", "a"]);
+//! ```
+//!
+//! If an expression is evaluated many times, it can be compiled once:
+//!
+//! ```rust
+//! use evalexpr::{compile, HashMapConfiguration, Value};
+//!
+//! let expression = compile("x * x").unwrap();
+//! for x in 0..10 {
+//!     let mut configuration = HashMapConfiguration::new();
+//!     configuration.insert_variable("x".to_string(), x);
+//!     assert_eq!(expression.eval(&configuration), Ok(Value::Int(x * x)));
+//! }
+//! ```
+//!
+//! A `CachedExpr` wraps a compiled expression and returns the result of its last evaluation without evaluating it again if the variables it reads did not change.
+//! This is useful for expensive expressions that do not depend on anything but their variables.
+//!
+//! Expressions can be split into tokens, for example to highlight them or to rewrite them before parsing:
+//!
+//! ```rust
+//! use evalexpr::{tokenize, tokens_to_operator_tree, HashMapConfiguration, Token, Value};
+//!
+//! let tokens = tokenize("1 + x").unwrap();
+//! assert_eq!(tokens[2], (Token::Identifier("x".to_string()), 4..5));
+//!
+//! // Replace the variable `x` with the constant 2
+//! let tokens = tokens
+//!     .into_iter()
+//!     .map(|(token, span)| match token {
+//!         Token::Identifier(ref identifier) if identifier == "x" => (Token::Int(2), span),
+//!         token => (token, span),
+//!     })
+//!     .collect();
+//! let tree = tokens_to_operator_tree(tokens).unwrap();
+//! assert_eq!(tree.eval(&HashMapConfiguration::new()), Ok(Value::Int(3)));
+//! ```
+//!
+//! To show users where an expression is invalid, `parse_expression` returns a `SyntaxError` with the line and column of the error:
+//!
+//! ```rust
+//! use evalexpr::parse_expression;
+//!
+//! let error = parse_expression("1 +\n(2 3)").unwrap_err();
+//! assert_eq!((error.line, error.column), (2, 4));
+//! assert_eq!(error.to_string(), "Expected an operator between values at line 2, column 4");
+//! ```
+//!
+//! License
+//! -------
+//!
+//! evalexpr is primarily distributed under the terms of the MIT license.
+//! See [LICENSE](LICENSE) for details.
+//!
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(feature = "std"))]
+#[macro_use]
+extern crate alloc;
+#[cfg(feature = "encoding")]
+extern crate base64;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+extern crate indexmap;
+#[cfg(feature = "wasm")]
+extern crate js_sys;
+#[cfg(not(feature = "std"))]
+extern crate libm;
+#[cfg(feature = "lru")]
+extern crate lru;
+#[cfg(feature = "crypto")]
+extern crate md5;
+#[cfg(all(test, feature = "serde"))]
+extern crate postcard;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "regex")]
+extern crate regex;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(any(feature = "json", all(test, feature = "serde")))]
+extern crate serde_json;
+#[cfg(feature = "crypto")]
+extern crate sha1;
+#[cfg(feature = "crypto")]
+extern crate sha2;
+#[cfg(all(test, feature = "async"))]
+extern crate tokio;
+#[cfg(feature = "tracing")]
+extern crate tracing;
+#[cfg(feature = "uuid")]
+extern crate uuid;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
+#[cfg(all(test, feature = "wasm", target_arch = "wasm32"))]
+extern crate wasm_bindgen_test;
+#[cfg(feature = "crypto")]
+extern crate xxhash_rust;
 
 mod configuration;
 mod error;
 mod function;
+mod math;
 mod operator;
 mod token;
 mod tree;
 mod value;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+/// Provides the parts of `std` used by this crate from `core` and `alloc` if the `std` feature is disabled.
+#[cfg(not(feature = "std"))]
+mod std {
+    pub use alloc::{boxed, collections, fmt, string, sync, vec};
+    pub use core::{any, cmp, convert, hash, iter, mem, num, ops, slice, str};
+}
+use std::string::String;
 
 // Exports
 
-pub use configuration::{Configuration, EmptyConfiguration, HashMapConfiguration};
-pub use error::Error;
-pub use function::Function;
-pub use tree::Node;
-pub use value::Value;
+#[cfg(feature = "async")]
+pub use configuration::AsyncConfiguration;
+#[cfg(feature = "lru")]
+pub use configuration::LruCachedConfiguration;
+pub use configuration::{
+    AuditingConfiguration, ChainedConfiguration, Configuration, ConfigurationBuilder,
+    ConfigurationWithContext, EmptyConfiguration, FunctionCallCallback, HashMapConfiguration,
+    IntoConfiguration, MutableConfiguration, ScopedConfiguration, VariableAccessCallback,
+};
+pub use error::{Error, EvalResult, SyntaxError};
+#[cfg(feature = "global_registry")]
+pub use function::registry::{register_global_function, unregister_global_function};
+#[cfg(feature = "std")]
+pub use function::StatefulFunction;
+#[cfg(feature = "async")]
+pub use function::{AsyncFunction, AsyncFunctionBody, BoxFuture, SharedAsyncFunctionBody};
+pub use function::{
+    Function, FunctionBody, FunctionWithContext, FunctionWithContextBody, SharedFunctionBody,
+    SharedFunctionWithContextBody, TypedFunction,
+};
+pub use indexmap::IndexMap;
+pub use token::{tokenize, Span, Token};
+#[cfg(feature = "async")]
+pub use tree::EvalAsync;
+pub use tree::{tokens_to_operator_tree, CachedExpr, CompiledExpr, Node};
+pub use value::{FloatType, FnvHasher, IntType, ObjectMap, Value, ValueKey, ValueType};
 
 pub fn eval(string: &str) -> Result<Value, Error> {
     tree::tokens_to_operator_tree(token::tokenize(string)?)?.eval(&EmptyConfiguration)
@@ -147,22 +692,145 @@ pub fn eval(string: &str) -> Result<Value, Error> {
 
 pub fn eval_with_configuration(
     string: &str,
-    configuration: &Configuration,
+    configuration: &dyn Configuration,
 ) -> Result<Value, Error> {
     tree::tokens_to_operator_tree(token::tokenize(string)?)?.eval(configuration)
 }
 
+/// Evaluates the given expression with the given configuration, and passes the given context to the functions with context of the configuration.
+pub fn eval_with_configuration_and_context(
+    string: &str,
+    configuration: &HashMapConfiguration,
+    context: std::sync::Arc<dyn std::any::Any + Send + Sync>,
+) -> Result<Value, Error> {
+    eval_with_configuration(
+        string,
+        &ConfigurationWithContext::new(configuration, context),
+    )
+}
+
+/// Evaluates the given expression with the given configuration, and writes all variables assigned by the expression to the configuration.
+pub fn eval_with_mutable_configuration(
+    string: &str,
+    configuration: &mut dyn MutableConfiguration,
+) -> Result<Value, Error> {
+    tree::tokens_to_operator_tree(token::tokenize(string)?)?
+        .eval_with_mutable_configuration(configuration)
+}
+
+/// Evaluates the given expression with the given configuration, and allows it to call the async functions of the configuration.
+/// The returned future resolves to the value of the expression.
+#[cfg(feature = "async")]
+pub fn eval_async<'a>(string: &str, configuration: &'a dyn AsyncConfiguration) -> EvalAsync<'a> {
+    EvalAsync::new(
+        build_operator_tree(string).map(std::borrow::Cow::Owned),
+        configuration,
+    )
+}
+
+/// Evaluates the given expression and returns the result as a bool, or an error if the result has a different type.
+pub fn eval_as_bool(string: &str) -> Result<bool, Error> {
+    eval(string)?.into_bool()
+}
+
+/// Evaluates the given expression with the given configuration and returns the result as a bool, or an error if the result has a different type.
+pub fn eval_with_configuration_as_bool(
+    string: &str,
+    configuration: &dyn Configuration,
+) -> Result<bool, Error> {
+    eval_with_configuration(string, configuration)?.into_bool()
+}
+
+/// Evaluates the given expression and returns the result as an int, or an error if the result has a different type.
+pub fn eval_as_int(string: &str) -> Result<IntType, Error> {
+    eval(string)?.into_int()
+}
+
+/// Evaluates the given expression with the given configuration and returns the result as an int, or an error if the result has a different type.
+pub fn eval_with_configuration_as_int(
+    string: &str,
+    configuration: &dyn Configuration,
+) -> Result<IntType, Error> {
+    eval_with_configuration(string, configuration)?.into_int()
+}
+
+/// Evaluates the given expression and returns the result as a float, or an error if the result has a different type.
+pub fn eval_as_float(string: &str) -> Result<FloatType, Error> {
+    eval(string)?.into_float()
+}
+
+/// Evaluates the given expression with the given configuration and returns the result as a float, or an error if the result has a different type.
+pub fn eval_with_configuration_as_float(
+    string: &str,
+    configuration: &dyn Configuration,
+) -> Result<FloatType, Error> {
+    eval_with_configuration(string, configuration)?.into_float()
+}
+
+/// Evaluates the given expression and returns the result as a string, or an error if the result has a different type.
+pub fn eval_as_string(string: &str) -> Result<String, Error> {
+    eval(string)?.into_string()
+}
+
+/// Evaluates the given expression with the given configuration and returns the result as a string, or an error if the result has a different type.
+pub fn eval_with_configuration_as_string(
+    string: &str,
+    configuration: &dyn Configuration,
+) -> Result<String, Error> {
+    eval_with_configuration(string, configuration)?.into_string()
+}
+
+/// Parses the given expression into an operator tree.
 pub fn build_operator_tree(string: &str) -> Result<Node, Error> {
     tree::tokens_to_operator_tree(token::tokenize(string)?)
 }
 
-#[cfg(test)]
+/// Parses the given expression like `build_operator_tree`, but returns a `SyntaxError` with the line, column and surrounding fragment of the error.
+pub fn parse_expression(string: &str) -> Result<Node, SyntaxError> {
+    build_operator_tree(string).map_err(|error| SyntaxError::new(string, &error))
+}
+
+/// Parses the given expression once, such that it can be evaluated many times with different configurations.
+pub fn compile(string: &str) -> Result<CompiledExpr, Error> {
+    Ok(CompiledExpr::new(build_operator_tree(string)?))
+}
+
+#[cfg(all(test, feature = "std"))]
 mod test {
     use crate::{eval, value::Value};
-    use configuration::HashMapConfiguration;
-    use error::Error;
+    use build_operator_tree;
+    use compile;
+    use configuration::{
+        AuditingConfiguration, ChainedConfiguration, Configuration, ConfigurationWithContext,
+        EmptyConfiguration, HashMapConfiguration, MutableConfiguration, ScopedConfiguration,
+    };
+    use error::{expect_int, Error, EvalResult, SyntaxError};
     use eval_with_configuration;
+    use eval_with_configuration_and_context;
+    use eval_with_mutable_configuration;
+    use parse_expression;
+    use std::cell::RefCell;
+    use std::cmp::Ordering;
+    use std::collections::{BTreeMap, HashMap, HashSet};
+    use std::convert::{TryFrom, TryInto};
+    use std::sync::Arc;
+    use CachedExpr;
+    use FloatType;
     use Function;
+    use FunctionWithContext;
+    use IntType;
+    use Node;
+    use ObjectMap;
+    use StatefulFunction;
+    use TypedFunction;
+    use ValueKey;
+    use ValueType;
+    use {
+        eval_as_bool, eval_as_float, eval_as_int, eval_as_string, eval_with_configuration_as_bool,
+        eval_with_configuration_as_float, eval_with_configuration_as_int,
+        eval_with_configuration_as_string,
+    };
+    use {tokenize, tokens_to_operator_tree, Token};
 
     #[test]
     fn test_unary_examples() {
@@ -172,7 +840,10 @@ mod test {
         assert_eq!(eval("false"), Ok(Value::Boolean(false)));
         assert_eq!(
             eval("blub"),
-            Err(Error::VariableIdentifierNotFound("blub".to_string()))
+            Err(Error::variable_identifier_not_found(
+                "blub".to_string(),
+                Some(0..4)
+            ))
         );
         assert_eq!(eval("-3"), Ok(Value::Int(-3)));
         assert_eq!(eval("-3.6"), Ok(Value::Float(-3.6)));
@@ -203,6 +874,90 @@ mod test {
         assert_eq!(eval("-5--3"), Ok(Value::Int(-2)));
     }
 
+    #[test]
+    fn test_prefixed_int_literals() {
+        assert_eq!(eval("0xFF"), Ok(Value::Int(255)));
+        assert_eq!(eval("0Xff"), Ok(Value::Int(255)));
+        assert_eq!(eval("0b1010"), Ok(Value::Int(10)));
+        assert_eq!(eval("0B11"), Ok(Value::Int(3)));
+        assert_eq!(eval("0o17"), Ok(Value::Int(15)));
+        assert_eq!(eval("0O17"), Ok(Value::Int(15)));
+        assert_eq!(eval("0x10 + 10 * 0b10"), Ok(Value::Int(36)));
+        assert_eq!(eval("-0x7FFFFFFFFFFFFFFF"), Ok(Value::Int(-IntType::MAX)));
+        assert_eq!(eval("0x0F & 0b1100"), Ok(Value::Int(12)));
+
+        for (string, span) in &[
+            ("0x10000000000000000", 0..19),
+            ("1 + 0b102", 4..9),
+            ("0x", 0..2),
+        ] {
+            match eval(string) {
+                Err(Error::ParseIntError {
+                    span: error_span, ..
+                }) => {
+                    assert_eq!(error_span, Some(span.clone()))
+                }
+                result => panic!("Expected a parse int error, got {:?}", result),
+            }
+        }
+    }
+
+    #[test]
+    fn test_scientific_notation() {
+        assert_eq!(eval("1.5e10"), Ok(Value::Float(1.5e10)));
+        assert_eq!(eval("3.2E-4"), Ok(Value::Float(3.2e-4)));
+        assert_eq!(eval("2e+3"), Ok(Value::Float(2000.0)));
+        assert_eq!(eval("1e10"), Ok(Value::Float(1e10)));
+        assert_eq!(eval("1.0e0"), Ok(Value::Float(1.0)));
+        assert_eq!(eval("-1e-1"), Ok(Value::Float(-0.1)));
+        assert_eq!(eval("1e-1-1e-1"), Ok(Value::Float(0.0)));
+        assert_eq!(eval("2 * 1e2 + 1"), Ok(Value::Float(201.0)));
+
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("e".to_string(), Value::Int(2));
+        configuration.insert_variable("xe".to_string(), Value::Int(3));
+        assert_eq!(
+            eval_with_configuration("e-1", &configuration),
+            Ok(Value::Int(1))
+        );
+        assert_eq!(
+            eval_with_configuration("xe-1", &configuration),
+            Ok(Value::Int(2))
+        );
+        assert_eq!(eval("0x1E-1"), Ok(Value::Int(29)));
+    }
+
+    #[test]
+    fn test_comments() {
+        assert_eq!(eval("1 /* add */ + /* two */ 2"), Ok(Value::Int(3)));
+        assert_eq!(eval("1 + 2 // ignored"), Ok(Value::Int(3)));
+        assert_eq!(eval("1 + // ignored\n 2"), Ok(Value::Int(3)));
+        assert_eq!(
+            eval("1 /* outer /* inner */ still outer */ + 2"),
+            Ok(Value::Int(3))
+        );
+        assert_eq!(eval("1 /* // */ + 2"), Ok(Value::Int(3)));
+        assert_eq!(eval("4 // /* \n / 2"), Ok(Value::Int(2)));
+        assert_eq!(eval("1/**/+2/*ö*/"), Ok(Value::Int(3)));
+        assert_eq!(eval("/* only a comment */"), Err(Error::EmptyExpression));
+
+        assert_eq!(
+            eval("1 + /* 2"),
+            Err(Error::UnmatchedComment { span: Some(4..6) })
+        );
+        assert_eq!(
+            eval("/* a /* b */ 1"),
+            Err(Error::UnmatchedComment { span: Some(0..2) })
+        );
+        assert_eq!(
+            eval("/*ö*/ a"),
+            Err(Error::variable_identifier_not_found(
+                "a".to_string(),
+                Some(7..8)
+            ))
+        );
+    }
+
     #[test]
     fn test_arithmetic_precedence_examples() {
         assert_eq!(eval("1+3-2"), Ok(Value::Int(2)));
@@ -250,92 +1005,5263 @@ mod test {
     }
 
     #[test]
-    fn test_with_configuration() {
-        let mut configuration = HashMapConfiguration::new();
-        configuration.insert_variable("tr".to_string(), Value::Boolean(true));
-        configuration.insert_variable("fa".to_string(), Value::Boolean(false));
-        configuration.insert_variable("five".to_string(), Value::Int(5));
-        configuration.insert_variable("six".to_string(), Value::Int(6));
-        configuration.insert_variable("half".to_string(), Value::Float(0.5));
-        configuration.insert_variable("zero".to_string(), Value::Int(0));
+    fn test_keyword_operators() {
+        assert_eq!(eval("not true"), Ok(Value::Boolean(false)));
+        assert_eq!(eval("not(1 > 2)"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("true and false"), Ok(Value::Boolean(false)));
+        assert_eq!(eval("false or true"), Ok(Value::Boolean(true)));
+        // The keywords have the precedence of their symbols
+        assert_eq!(
+            eval("true or true and false"),
+            eval("true || true && false")
+        );
+        assert_eq!(eval("not false and false"), Ok(Value::Boolean(false)));
+        assert_eq!(
+            build_operator_tree("not a or b and c").unwrap().to_string(),
+            "((!a) || (b && c))"
+        );
 
+        // Identifiers that start with keywords are not affected
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("not_x".to_string(), true);
+        configuration.insert_variable("and_y".to_string(), false);
+        configuration.insert_variable("org".to_string(), false);
         assert_eq!(
-            eval_with_configuration("tr", &configuration),
+            eval_with_configuration("not_x and not and_y or org", &configuration),
             Ok(Value::Boolean(true))
         );
+    }
+
+    #[test]
+    fn test_xor_examples() {
+        assert_eq!(eval("true ^^ false"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("true ^^ true"), Ok(Value::Boolean(false)));
+        assert_eq!(eval("false ^^ false"), Ok(Value::Boolean(false)));
+        assert_eq!(eval("false xor true"), Ok(Value::Boolean(true)));
+        // `&&` binds tighter and `||` binds looser than `^^`
+        assert_eq!(eval("true ^^ true && false"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("true || true ^^ true"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("1 < 2 ^^ 2 < 3"), Ok(Value::Boolean(false)));
+        assert_eq!(eval("5 ^ 3"), Ok(Value::Int(6)));
         assert_eq!(
-            eval_with_configuration("fa", &configuration),
-            Ok(Value::Boolean(false))
+            eval("1 ^^ true"),
+            Err(Error::expected_boolean(Value::Int(1)))
         );
         assert_eq!(
-            eval_with_configuration("tr && false", &configuration),
-            Ok(Value::Boolean(false))
+            build_operator_tree("a xor b").unwrap().to_string(),
+            "(a ^^ b)"
         );
+    }
+
+    #[test]
+    fn test_exponentiation_examples() {
+        assert_eq!(eval("2 ** 10"), Ok(Value::Int(1024)));
+        assert_eq!(eval("2 ** 0"), Ok(Value::Int(1)));
+        assert_eq!(eval("2 ** -1"), Ok(Value::Float(0.5)));
+        assert_eq!(eval("4 ** 0.5"), Ok(Value::Float(2.0)));
+        assert_eq!(eval("0.5 ** 2"), Ok(Value::Float(0.25)));
+        assert_eq!(eval("2.0 ** 3.0"), Ok(Value::Float(8.0)));
+        assert_eq!(eval("2 ** 3 ** 2"), Ok(Value::Int(512)));
+        assert_eq!(eval("(2 ** 3) ** 2"), Ok(Value::Int(64)));
+        assert_eq!(eval("-2 ** 2"), Ok(Value::Int(-4)));
+        assert_eq!(eval("(-2) ** 2"), Ok(Value::Int(4)));
+        assert_eq!(eval("2 * 3 ** 2"), Ok(Value::Int(18)));
+        assert_eq!(eval("2 ** 2 * 3"), Ok(Value::Int(12)));
+        assert_eq!(eval("2 ** -1 * 4"), Ok(Value::Float(2.0)));
+        assert_eq!(eval("3 * 3 ** -1"), Ok(Value::Float(1.0)));
+        assert_eq!(eval("2 ** 63"), Err(Error::integer_overflow("**", 2, 63)));
         assert_eq!(
-            eval_with_configuration("five + six", &configuration),
-            Ok(Value::Int(11))
+            eval("true ** 2"),
+            Err(Error::expected_number(Value::Boolean(true)))
+        );
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        assert_eq!(eval("1 / 0"), Err(Error::DivisionByZero));
+        assert_eq!(eval("1 % 0"), Err(Error::DivisionByZero));
+        assert_eq!(eval("7 / 2"), Ok(Value::Int(3)));
+        assert_eq!(eval("-7 % 2"), Ok(Value::Int(-1)));
+    }
+
+    #[cfg(not(feature = "permissive_floats"))]
+    #[test]
+    fn test_float_errors() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("inf".to_string(), Value::Float(FloatType::INFINITY));
+        configuration.insert_variable("nan".to_string(), Value::Float(FloatType::NAN));
+        configuration.insert_variable(
+            "large".to_string(),
+            Value::Array(vec![Value::Float(1e308), Value::Float(1e308)]),
         );
+        let eval = |string| eval_with_configuration(string, &configuration);
+
+        assert_eq!(eval("1.0 / 0"), Err(Error::DivisionByZero));
+        assert_eq!(eval("0.0 / 0.0"), Err(Error::DivisionByZero));
+        assert_eq!(eval("1.5 % 0.0"), Err(Error::DivisionByZero));
+        assert_eq!(eval("1e308 * 10"), Err(Error::Infinity));
+        assert_eq!(eval("-1e308 - 1e308"), Err(Error::Infinity));
+        assert_eq!(eval("10.0 ** 400"), Err(Error::Infinity));
+        assert_eq!(eval("0 ** -1"), Err(Error::Infinity));
+        assert_eq!(eval("(-8.0) ** (1 / 3.0)"), Err(Error::NaN));
+        assert_eq!(eval("inf - inf"), Err(Error::NaN));
+        assert_eq!(eval("nan + 1"), Err(Error::NaN));
+        assert_eq!(eval("-inf"), Err(Error::Infinity));
+        assert_eq!(eval("sum(large)"), Err(Error::Infinity));
+        assert_eq!(eval("avg(large)"), Err(Error::Infinity));
+        assert_eq!(eval("ln(0)"), Err(Error::domain_error("ln", Value::Int(0))));
         assert_eq!(
-            eval_with_configuration("five * half", &configuration),
-            Ok(Value::Float(2.5))
+            eval("log10(0.0)"),
+            Err(Error::domain_error("log10", Value::Float(0.0)))
         );
+        assert_eq!(eval("sqrt(inf)"), Err(Error::Infinity));
+        assert_eq!(eval("1.5 / 0.5 + 2 ** -1"), Ok(Value::Float(3.5)));
+        assert_eq!(eval("-7.5 % 2"), Ok(Value::Float(-1.5)));
+        assert_eq!(Error::NaN.to_string(), "The result is not a number");
+        assert_eq!(Error::Infinity.to_string(), "The result is infinite");
+    }
+
+    #[cfg(feature = "permissive_floats")]
+    #[test]
+    fn test_permissive_floats() {
+        assert_eq!(eval("1.0 / 0"), Ok(Value::Float(FloatType::INFINITY)));
+        assert!(eval("0.0 / 0.0").unwrap().as_float().unwrap().is_nan());
+        assert_eq!(eval("1e308 * 10"), Ok(Value::Float(FloatType::INFINITY)));
+        assert_eq!(eval("0 ** -1"), Ok(Value::Float(FloatType::INFINITY)));
+        assert_eq!(eval("ln(0)"), Ok(Value::Float(FloatType::NEG_INFINITY)));
         assert_eq!(
-            eval_with_configuration("five < six && true", &configuration),
-            Ok(Value::Boolean(true))
+            eval("sqrt(-1)"),
+            Err(Error::domain_error("sqrt", Value::Int(-1)))
         );
+        assert_eq!(eval("1 / 0"), Err(Error::DivisionByZero));
     }
 
+    #[cfg(not(feature = "wrapping_arithmetic"))]
     #[test]
-    fn test_functions() {
+    fn test_integer_overflow() {
         let mut configuration = HashMapConfiguration::new();
-        configuration.insert_function(
-            "sub2".to_string(),
-            Function::new(
-                1,
-                Box::new(|arguments| {
-                    if let Value::Int(int) = arguments[0] {
-                        Ok(Value::Int(int - 2))
-                    } else {
-                        Err(Error::expected_number(arguments[0].clone()))
-                    }
-                }),
-            ),
-        );
-        configuration.insert_variable("five".to_string(), Value::Int(5));
+        configuration.insert_variable("max".to_string(), Value::Int(IntType::MAX));
+        configuration.insert_variable("min".to_string(), Value::Int(IntType::MIN));
+        let eval = |string| eval_with_configuration(string, &configuration);
 
         assert_eq!(
-            eval_with_configuration("sub2 5", &configuration),
-            Ok(Value::Int(3))
+            eval("max + 1"),
+            Err(Error::integer_overflow("+", IntType::MAX, 1))
         );
         assert_eq!(
-            eval_with_configuration("sub2(5)", &configuration),
-            Ok(Value::Int(3))
+            eval("min - 1"),
+            Err(Error::integer_overflow("-", IntType::MIN, 1))
         );
         assert_eq!(
-            eval_with_configuration("sub2 five", &configuration),
-            Ok(Value::Int(3))
+            eval("max * 2"),
+            Err(Error::integer_overflow("*", IntType::MAX, 2))
         );
         assert_eq!(
-            eval_with_configuration("sub2(five)", &configuration),
-            Ok(Value::Int(3))
+            eval("min / -1"),
+            Err(Error::integer_overflow("/", IntType::MIN, -1))
         );
         assert_eq!(
-            eval_with_configuration("sub2(3) + five", &configuration),
-            Ok(Value::Int(6))
+            eval("min % -1"),
+            Err(Error::integer_overflow("%", IntType::MIN, -1))
         );
-    }
-
-    #[test]
-    fn test_errors() {
         assert_eq!(
-            eval("-true"),
-            Err(Error::expected_number(Value::Boolean(true)))
+            eval("-min"),
+            Err(Error::integer_overflow("-", 0, IntType::MIN))
         );
+        assert_eq!(eval("max - 1 + 1"), Ok(Value::Int(IntType::MAX)));
         assert_eq!(
-            eval("1-true"),
-            Err(Error::expected_number(Value::Boolean(true)))
+            eval("max + 1").unwrap_err().to_string(),
+            format!("Integer overflow when computing {} + 1", IntType::MAX)
+        );
+    }
+
+    #[cfg(feature = "wrapping_arithmetic")]
+    #[test]
+    fn test_wrapping_arithmetic() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("max".to_string(), Value::Int(IntType::MAX));
+        configuration.insert_variable("min".to_string(), Value::Int(IntType::MIN));
+        let eval = |string| eval_with_configuration(string, &configuration);
+
+        assert_eq!(eval("max + 1"), Ok(Value::Int(IntType::MIN)));
+        assert_eq!(eval("min - 1"), Ok(Value::Int(IntType::MAX)));
+        assert_eq!(eval("max * 2"), Ok(Value::Int(-2)));
+        assert_eq!(eval("min / -1"), Ok(Value::Int(IntType::MIN)));
+        assert_eq!(eval("min % -1"), Ok(Value::Int(0)));
+        assert_eq!(eval("-min"), Ok(Value::Int(IntType::MIN)));
+        assert_eq!(eval("1 / 0"), Err(Error::DivisionByZero));
+        assert_eq!(eval("2 ** 63"), Err(Error::integer_overflow("**", 2, 63)));
+    }
+
+    #[test]
+    fn test_bitwise_examples() {
+        assert_eq!(eval("5 & 3"), Ok(Value::Int(1)));
+        assert_eq!(eval("5 | 3"), Ok(Value::Int(7)));
+        assert_eq!(eval("5 ^ 3"), Ok(Value::Int(6)));
+        assert_eq!(eval("~0"), Ok(Value::Int(-1)));
+        assert_eq!(eval("~~5"), Ok(Value::Int(5)));
+        assert_eq!(eval("1 << 4"), Ok(Value::Int(16)));
+        assert_eq!(eval("16 >> 2"), Ok(Value::Int(4)));
+        assert_eq!(eval("-16 >> 2"), Ok(Value::Int(-4)));
+        assert_eq!(eval("1 << 63"), Ok(Value::Int(IntType::MIN)));
+        assert_eq!(eval("-1 >> 63"), Ok(Value::Int(-1)));
+        assert_eq!(eval("1 << 64"), Err(Error::integer_overflow("<<", 1, 64)));
+        assert_eq!(eval("1 >> 64"), Err(Error::integer_overflow(">>", 1, 64)));
+        assert_eq!(eval("1 << -1"), Err(Error::integer_overflow("<<", 1, -1)));
+        assert_eq!(eval("1 >> -1"), Err(Error::integer_overflow(">>", 1, -1)));
+    }
+
+    #[test]
+    fn test_bitwise_precedence_examples() {
+        assert_eq!(eval("(5 & 3) == 1"), Ok(Value::Boolean(true)));
+        assert_eq!(
+            eval("5 & 3 == 1"),
+            Err(Error::expected_int(Value::Boolean(false)))
+        );
+        assert_eq!(eval("8 | 1 ^ 6 & 3"), Ok(Value::Int(11)));
+        assert_eq!(eval("1 + 1 << 2"), Ok(Value::Int(5)));
+        assert_eq!(eval("2 * 1 << 2"), Ok(Value::Int(8)));
+        assert_eq!(eval("1 << 2 + 1"), Ok(Value::Int(5)));
+        assert_eq!(eval("~1 + 1"), Ok(Value::Int(-1)));
+        assert_eq!(
+            eval("1 < 2 && 6 & 3 > 1"),
+            Err(Error::expected_int(Value::Boolean(true)))
+        );
+        assert_eq!(eval("1 < 2 && (6 & 3) > 1"), Ok(Value::Boolean(true)));
+    }
+
+    #[test]
+    fn test_ternary_examples() {
+        assert_eq!(eval("true ? 1 : 2"), Ok(Value::Int(1)));
+        assert_eq!(eval("false ? 1 : 2"), Ok(Value::Int(2)));
+        assert_eq!(eval("1 < 2 || false ? 3 : 4"), Ok(Value::Int(3)));
+        assert_eq!(eval("false ? 1 : 2 + 3"), Ok(Value::Int(5)));
+        assert_eq!(eval("true ? 1 + 1 : 2"), Ok(Value::Int(2)));
+        assert_eq!(eval("(true ? 1 : 2) + 3"), Ok(Value::Int(4)));
+        assert_eq!(eval("false ? 1 : true ? 2 : 3"), Ok(Value::Int(2)));
+        assert_eq!(eval("false ? 1 : false ? 2 : 3"), Ok(Value::Int(3)));
+        assert_eq!(eval("true ? false ? 1 : 2 : 3"), Ok(Value::Int(2)));
+        assert_eq!(eval("true ? true ? 1 : 2 : 3"), Ok(Value::Int(1)));
+        assert_eq!(eval("false ? true ? 1 : 2 : 3"), Ok(Value::Int(3)));
+        assert_eq!(eval("true ? -1 : -2"), Ok(Value::Int(-1)));
+
+        // Only the selected branch is evaluated
+        assert_eq!(eval("true ? 1 : blub"), Ok(Value::Int(1)));
+        assert_eq!(eval("false ? blub : 2"), Ok(Value::Int(2)));
+    }
+
+    #[test]
+    fn test_ternary_errors() {
+        assert_eq!(
+            eval("true ? 1"),
+            Err(Error::UnmatchedQuestionMark { span: Some(5..6) })
+        );
+        assert_eq!(
+            eval("(true ? 1) : 2"),
+            Err(Error::UnmatchedQuestionMark { span: Some(6..7) })
+        );
+        assert_eq!(
+            eval("true : 1"),
+            Err(Error::UnmatchedColon { span: Some(5..6) })
+        );
+        assert_eq!(
+            eval("true ? 1 : 2 : 3"),
+            Err(Error::UnmatchedColon { span: Some(13..14) })
+        );
+        assert_eq!(eval("true ? 1 :"), Err(Error::wrong_argument_amount(2, 3)));
+        assert_eq!(
+            eval("1 ? 2 : 3"),
+            Err(Error::expected_boolean(Value::Int(1)))
+        );
+    }
+
+    #[test]
+    fn test_null_coalescing_examples() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("null_var".to_string(), Value::Null);
+        configuration.insert_variable("non_null_var".to_string(), Value::Int(3));
+
+        assert_eq!(
+            eval_with_configuration("null_var ?? 42", &configuration),
+            Ok(Value::Int(42))
+        );
+        assert_eq!(
+            eval_with_configuration("non_null_var ?? 42", &configuration),
+            Ok(Value::Int(3))
+        );
+        assert_eq!(eval("null"), Ok(Value::Null));
+        assert_eq!(eval("null ?? null ?? 5"), Ok(Value::Int(5)));
+        assert_eq!(eval("null ?? null"), Ok(Value::Null));
+        assert_eq!(eval("null ?? 1 + 2"), Ok(Value::Int(3)));
+        assert_eq!(eval("null ?? false || true"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("null ?? true ? 1 : 2"), Ok(Value::Int(1)));
+
+        // The right side is only evaluated if the left side is null
+        assert_eq!(eval("1 ?? blub"), Ok(Value::Int(1)));
+
+        assert_eq!(eval("null == null"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("1 == null"), Ok(Value::Boolean(false)));
+        assert_eq!(eval("false == null"), Ok(Value::Boolean(false)));
+        assert_eq!(eval("null + 1"), Err(Error::expected_number(Value::Null)));
+
+        assert_eq!(configuration.get_value_or_null("null_var"), Value::Null);
+        assert_eq!(
+            configuration.get_value_or_null("non_null_var"),
+            Value::Int(3)
+        );
+        assert_eq!(configuration.get_value_or_null("blub"), Value::Null);
+    }
+
+    #[test]
+    fn test_error_spans() {
+        assert_eq!(eval("1 + blub").unwrap_err().span(), Some(4..8));
+        assert_eq!(eval("1 +\n  blüb * 2").unwrap_err().span(), Some(6..11));
+        assert_eq!(eval("foo(1)").unwrap_err().span(), Some(0..3));
+        assert_eq!(
+            eval("(1 + 2"),
+            Err(Error::UnmatchedLBrace { span: Some(0..1) })
+        );
+        assert_eq!(
+            eval("((1) + 2"),
+            Err(Error::UnmatchedLBrace { span: Some(0..1) })
+        );
+        assert_eq!(
+            eval("1 + 2)"),
+            Err(Error::UnmatchedRBrace { span: Some(5..6) })
+        );
+        assert_eq!(eval("1 + a").unwrap_err().span(), Some(4..5));
+        assert_eq!(eval("1 2").unwrap_err().span(), Some(2..3));
+        assert_eq!(eval("1 + true").unwrap_err().span(), None);
+        assert_eq!(eval("").unwrap_err().span(), None);
+    }
+
+    #[test]
+    fn test_parse_expression() {
+        assert_eq!(
+            parse_expression("1 + 2").unwrap().eval(&EmptyConfiguration),
+            Ok(Value::Int(3))
+        );
+
+        let error = parse_expression("1 + 2)").unwrap_err();
+        assert_eq!(
+            error,
+            SyntaxError {
+                message: "Unmatched closing brace".to_string(),
+                position: 5,
+                line: 1,
+                column: 6,
+                fragment: "1 + 2)".to_string(),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Unmatched closing brace at line 1, column 6"
+        );
+        assert_eq!(
+            Error::from(error.clone()).to_string(),
+            "Unmatched closing brace at line 1, column 6"
+        );
+        assert_eq!(Error::from(error.clone()), Error::SyntaxError(error));
+
+        let error = parse_expression("a = 1;\n\"é\" + b +\n(2 * 3 4) + 123456789012").unwrap_err();
+        assert_eq!(error.position, 25);
+        assert_eq!((error.line, error.column), (3, 8));
+        assert_eq!(error.fragment, " +\n(2 * 3 4) + 12345");
+
+        // Columns are counted in characters
+        let error = parse_expression("'é' 1").unwrap_err();
+        assert_eq!((error.position, error.line, error.column), (5, 1, 5));
+
+        // Errors without a span are positioned at the end
+        let error = parse_expression("").unwrap_err();
+        assert_eq!((error.position, error.line, error.column), (0, 1, 1));
+        assert_eq!(error.fragment, "");
+    }
+
+    #[test]
+    fn test_string_literals() {
+        assert_eq!(eval("\"foo\""), Ok(Value::String("foo".to_string())));
+        assert_eq!(eval("'foo'"), Ok(Value::String("foo".to_string())));
+        assert_eq!(
+            eval("'a \"b\" c'"),
+            Ok(Value::String("a \"b\" c".to_string()))
+        );
+        assert_eq!(eval("\"it's\""), Ok(Value::String("it's".to_string())));
+        assert_eq!(
+            eval("'/* // */'"),
+            Ok(Value::String("/* // */".to_string()))
+        );
+        assert_eq!(eval("'' == \"\""), Ok(Value::Boolean(true)));
+        assert_eq!(eval("\"ö\" == 'ö' // 'comment"), Ok(Value::Boolean(true)));
+        assert_eq!(
+            eval("'abc"),
+            Err(Error::UnmatchedQuote { span: Some(0..1) })
+        );
+        assert_eq!(
+            eval("1 + \"abc' "),
+            Err(Error::UnmatchedQuote { span: Some(4..5) })
+        );
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        assert_eq!(eval(r#""a\nb\tc\rd""#), Ok(Value::from("a\nb\tc\rd")));
+        assert_eq!(
+            eval(r#""\\ \" \'" + '\' \"'"#),
+            Ok(Value::from("\\ \" '' \""))
+        );
+        assert_eq!(eval(r#"'\\n'"#), Ok(Value::from("\\n")));
+        assert_eq!(eval(r#"len('\\n')"#), Ok(Value::Int(2)));
+        assert_eq!(
+            eval(r#"'\u00e9\u{e9}\u{1F600}\u{0}'"#),
+            Ok(Value::from("éé\u{1F600}\u{0}"))
+        );
+        assert_eq!(eval(r#"'\u00411'"#), Ok(Value::from("A1")));
+        // Escaped quotes and comment markers do not end the literal
+        assert_eq!(
+            eval(r#""\" // \"" + '/* \''"#),
+            Ok(Value::from("\" // \"/* '"))
+        );
+
+        assert_eq!(
+            eval(r#"1 + 'a\qb'"#),
+            Err(Error::UnknownEscapeSequence {
+                character: 'q',
+                span: Some(6..8)
+            })
+        );
+        assert_eq!(
+            eval(r#"'\ö'"#),
+            Err(Error::UnknownEscapeSequence {
+                character: 'ö',
+                span: Some(1..4)
+            })
+        );
+        assert_eq!(
+            eval(r#"'\u12'"#),
+            Err(Error::InvalidUnicodeEscape { span: Some(1..5) })
+        );
+        assert_eq!(
+            eval(r#"'\u{}'"#),
+            Err(Error::InvalidUnicodeEscape { span: Some(1..5) })
+        );
+        assert_eq!(
+            eval(r#"'\u{1234567}'"#),
+            Err(Error::InvalidUnicodeEscape { span: Some(1..10) })
+        );
+        assert_eq!(
+            eval(r#"'\uD800'"#),
+            Err(Error::InvalidUnicodeEscape { span: Some(1..7) })
+        );
+        assert_eq!(
+            eval(r#"'\u{110000}'"#),
+            Err(Error::InvalidUnicodeEscape { span: Some(1..11) })
+        );
+        assert_eq!(
+            eval(r#"'abc\"#),
+            Err(Error::UnmatchedQuote { span: Some(0..1) })
+        );
+        assert_eq!(
+            eval(r#"'abc\'"#),
+            Err(Error::UnmatchedQuote { span: Some(0..1) })
+        );
+        assert_eq!(
+            Error::UnknownEscapeSequence {
+                character: 'q',
+                span: None
+            }
+            .to_string(),
+            "Unknown escape sequence \\q"
+        );
+    }
+
+    #[test]
+    fn test_string_concatenation() {
+        assert_eq!(
+            eval("\"Hello, \" + \"World!\""),
+            Ok(Value::String("Hello, World!".to_string()))
+        );
+        assert_eq!(
+            eval("'a' + 'b' + 'c'"),
+            Ok(Value::String("abc".to_string()))
+        );
+        assert_eq!(eval("'' + ''"), Ok(Value::String(String::new())));
+        assert_eq!(
+            eval("'ab' * 2 + 'c'"),
+            Ok(Value::String("ababc".to_string()))
+        );
+        assert_eq!(
+            eval("'n = ' + to_string(1 + 2)"),
+            Ok(Value::String("n = 3".to_string()))
+        );
+        assert_eq!(
+            eval("'a' + 1"),
+            Err(Error::expected_number(Value::String("a".to_string())))
+        );
+        assert_eq!(
+            eval("1 + 'a'"),
+            Err(Error::expected_number(Value::String("a".to_string())))
+        );
+
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("s".to_string(), "ab");
+        assert_eq!(
+            eval_with_mutable_configuration("s += 'c'; s", &mut configuration),
+            Ok(Value::String("abc".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_sprintf() {
+        let string = |string: &str| Ok(Value::String(string.to_string()));
+        assert_eq!(eval("sprintf('plain')"), string("plain"));
+        assert_eq!(
+            eval("sprintf(\"Hello, %s! You have %d messages.\", 'Bob', 3)"),
+            string("Hello, Bob! You have 3 messages.")
+        );
+        assert_eq!(eval("sprintf('%f', 1.5)"), string("1.500000"));
+        assert_eq!(eval("sprintf('%.2f', 3.14159)"), string("3.14"));
+        assert_eq!(eval("sprintf('%.0f', 2)"), string("2"));
+        assert_eq!(eval("sprintf('%d%%', 50)"), string("50%"));
+        assert_eq!(
+            eval("sprintf('%s, %s, %s, %s', 1, 2.5, true, (1, 'a'))"),
+            string("1, 2.5, true, (1, \"a\")")
+        );
+        assert_eq!(eval("sprintf('%s%s', 'ö', '')"), string("ö"));
+
+        let format_error = |message: &str| Err(Error::FormatError(message.to_string()));
+        assert_eq!(
+            eval("sprintf('%s and %s', 1)"),
+            format_error("Too few arguments for \"%s and %s\"")
+        );
+        assert_eq!(
+            eval("sprintf('%s', 1, 2)"),
+            format_error("Too many arguments for \"%s\"")
+        );
+        assert_eq!(
+            eval("sprintf('%d', 1.5)"),
+            format_error("Expected an integer for %d, but got 1.5")
+        );
+        assert_eq!(
+            eval("sprintf('%f', 'a')"),
+            format_error("Expected a number for %f, but got \"a\"")
+        );
+        assert_eq!(
+            eval("sprintf('%x', 1)"),
+            format_error("Invalid placeholder in \"%x\"")
+        );
+        assert_eq!(
+            eval("sprintf('%.2d', 1)"),
+            format_error("Invalid placeholder in \"%.2d\"")
+        );
+        assert_eq!(
+            eval("sprintf('100%')"),
+            format_error("Invalid placeholder in \"100%\"")
+        );
+        assert_eq!(
+            eval("sprintf('%.f', 1)"),
+            format_error("Expected a precision after '%.' in \"%.f\"")
+        );
+        assert_eq!(
+            eval("sprintf('%.65535f', 1)"),
+            Ok(Value::String(format!("1.{}", "0".repeat(65535))))
+        );
+        assert_eq!(
+            eval("sprintf('%.65536f', 1)"),
+            format_error("The precision in \"%.65536f\" exceeds the maximum of 65535")
+        );
+        assert_eq!(
+            eval("sprintf('%.99999999999999999999f', 1)"),
+            format_error(
+                "The precision in \"%.99999999999999999999f\" exceeds the maximum of 65535"
+            )
+        );
+        assert_eq!(
+            eval("sprintf(1)"),
+            Err(Error::expected_string(Value::Int(1)))
+        );
+
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("user_name".to_string(), "Ann");
+        assert_eq!(
+            eval_with_mutable_configuration(
+                "greeting = sprintf('Hi %s', user_name); greeting",
+                &mut configuration
+            ),
+            string("Hi Ann")
+        );
+        assert_eq!(
+            build_operator_tree("sprintf('%s %s', a, b)")
+                .unwrap()
+                .validate(&configuration),
+            Err(Error::MultipleErrors(vec![
+                Error::variable_identifier_not_found("a".to_string(), Some(17..18)),
+                Error::variable_identifier_not_found("b".to_string(), Some(20..21)),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_string_repetition() {
+        assert_eq!(
+            eval("'abc' * 3"),
+            Ok(Value::String("abcabcabc".to_string()))
+        );
+        assert_eq!(eval("2 * 'ab'"), Ok(Value::String("abab".to_string())));
+        assert_eq!(eval("'abc' * 1"), Ok(Value::String("abc".to_string())));
+        assert_eq!(eval("'abc' * 0"), Ok(Value::String(String::new())));
+        assert_eq!(eval("'' * 5"), Ok(Value::String(String::new())));
+        assert_eq!(eval("'ab' * 1000"), Ok(Value::String("ab".repeat(1000))));
+        assert_eq!(eval("'a' * 2 * 3"), Ok(Value::String("aaaaaa".to_string())));
+        assert_eq!(eval("3 * 4"), Ok(Value::Int(12)));
+        assert_eq!(eval("1.5 * 2"), Ok(Value::Float(3.0)));
+
+        assert_eq!(
+            eval("'abc' * -1"),
+            Err(Error::expected_positive_int(Value::Int(-1)))
+        );
+        assert_eq!(
+            eval("1.5 * 'abc'"),
+            Err(Error::expected_positive_int(Value::Float(1.5)))
+        );
+        assert_eq!(
+            eval("'a' * 'b'"),
+            Err(Error::expected_positive_int(Value::String("b".to_string())))
+        );
+        assert_eq!(
+            eval("true * 2"),
+            Err(Error::expected_number(Value::Boolean(true)))
+        );
+    }
+
+    #[test]
+    fn test_in() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable(
+            "arr".to_string(),
+            Value::Array(vec![
+                Value::Int(1),
+                Value::Int(3),
+                Value::String("foo".to_string()),
+            ]),
+        );
+        configuration.insert_variable("empty".to_string(), Value::Array(Vec::new()));
+
+        assert_eq!(
+            eval_with_configuration("3 in arr", &configuration),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval_with_configuration("2 in arr", &configuration),
+            Ok(Value::Boolean(false))
+        );
+        assert_eq!(
+            eval_with_configuration("'foo' in arr && !(1 in empty)", &configuration),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval_with_configuration("1 + 2 in arr == true", &configuration),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(eval("'lo' in 'Hello'"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("'' in 'Hello'"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("'hello' in 'Hello'"), Ok(Value::Boolean(false)));
+
+        assert_eq!(
+            eval("1 in 'Hello'"),
+            Err(Error::expected_string(Value::Int(1)))
+        );
+        assert_eq!(
+            eval("1 in 2"),
+            Err(Error::expected_array_or_string(Value::Int(2)))
+        );
+    }
+
+    #[test]
+    fn test_with_configuration() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("tr".to_string(), Value::Boolean(true));
+        configuration.insert_variable("fa".to_string(), Value::Boolean(false));
+        configuration.insert_variable("five".to_string(), Value::Int(5));
+        configuration.insert_variable("six".to_string(), Value::Int(6));
+        configuration.insert_variable("half".to_string(), Value::Float(0.5));
+        configuration.insert_variable("zero".to_string(), Value::Int(0));
+
+        assert_eq!(
+            eval_with_configuration("tr", &configuration),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval_with_configuration("fa", &configuration),
+            Ok(Value::Boolean(false))
+        );
+        assert_eq!(
+            eval_with_configuration("tr && false", &configuration),
+            Ok(Value::Boolean(false))
+        );
+        assert_eq!(
+            eval_with_configuration("five + six", &configuration),
+            Ok(Value::Int(11))
+        );
+        assert_eq!(
+            eval_with_configuration("five * half", &configuration),
+            Ok(Value::Float(2.5))
+        );
+        assert_eq!(
+            eval_with_configuration("five < six && true", &configuration),
+            Ok(Value::Boolean(true))
+        );
+    }
+
+    #[test]
+    fn test_functions() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_function(
+            "sub2".to_string(),
+            Function::new(
+                1,
+                Box::new(|arguments| {
+                    if let Value::Int(int) = arguments[0] {
+                        Ok(Value::Int(int - 2))
+                    } else {
+                        Err(Error::expected_number(arguments[0].clone()))
+                    }
+                }),
+            ),
+        );
+        configuration.insert_variable("five".to_string(), Value::Int(5));
+
+        assert_eq!(
+            eval_with_configuration("sub2 5", &configuration),
+            Ok(Value::Int(3))
+        );
+        assert_eq!(
+            eval_with_configuration("sub2(5)", &configuration),
+            Ok(Value::Int(3))
+        );
+        assert_eq!(
+            eval_with_configuration("sub2 five", &configuration),
+            Ok(Value::Int(3))
+        );
+        assert_eq!(
+            eval_with_configuration("sub2(five)", &configuration),
+            Ok(Value::Int(3))
+        );
+        assert_eq!(
+            eval_with_configuration("sub2(3) + five", &configuration),
+            Ok(Value::Int(6))
+        );
+        assert_eq!(
+            eval_with_configuration("sub2(five > 3 ? five : 3)", &configuration),
+            Ok(Value::Int(3))
+        );
+    }
+
+    #[test]
+    fn test_builtin_functions() {
+        assert_eq!(eval("abs(-3)"), Ok(Value::Int(3)));
+        assert_eq!(eval("abs 3"), Ok(Value::Int(3)));
+        assert_eq!(eval("abs(-2.5)"), Ok(Value::Float(2.5)));
+        assert_eq!(eval("floor(2.7)"), Ok(Value::Float(2.0)));
+        assert_eq!(eval("floor(-2.5)"), Ok(Value::Float(-3.0)));
+        assert_eq!(eval("ceil(2.2)"), Ok(Value::Float(3.0)));
+        assert_eq!(eval("ceil(2)"), Ok(Value::Float(2.0)));
+        assert_eq!(eval("round(2.5)"), Ok(Value::Float(3.0)));
+        assert_eq!(eval("round(-2.4)"), Ok(Value::Float(-2.0)));
+        assert_eq!(eval("sqrt(16)"), Ok(Value::Float(4.0)));
+        assert_eq!(eval("ln(1)"), Ok(Value::Float(0.0)));
+        assert_eq!(eval("log2(8)"), Ok(Value::Float(3.0)));
+        assert_eq!(eval("log10(1000)"), Ok(Value::Float(3.0)));
+        assert_eq!(eval("sqrt(abs(-4)) + 1"), Ok(Value::Float(3.0)));
+
+        assert_eq!(
+            eval("sqrt(-1)"),
+            Err(Error::domain_error("sqrt", Value::Int(-1)))
+        );
+        assert_eq!(
+            eval("ln(-0.5)"),
+            Err(Error::domain_error("ln", Value::Float(-0.5)))
+        );
+        assert_eq!(
+            eval("log2(-1)"),
+            Err(Error::domain_error("log2", Value::Int(-1)))
+        );
+        assert_eq!(
+            eval("log10(-1)"),
+            Err(Error::domain_error("log10", Value::Int(-1)))
+        );
+        assert_eq!(
+            eval("floor(true)"),
+            Err(Error::expected_number(Value::Boolean(true)))
+        );
+        assert_eq!(
+            eval("abs('a')"),
+            Err(Error::expected_number(Value::String("a".to_string())))
+        );
+
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("x".to_string(), Value::Float(-1.5));
+        configuration.insert_function(
+            "abs".to_string(),
+            Function::new(1, Box::new(|_| Ok(Value::Int(42)))),
+        );
+        assert_eq!(
+            eval_with_configuration("floor(x)", &configuration),
+            Ok(Value::Float(-2.0))
+        );
+        assert_eq!(
+            eval_with_configuration("abs(x)", &configuration),
+            Ok(Value::Int(42))
+        );
+        assert_eq!(
+            build_operator_tree("sqrt(x) + log10(x)")
+                .unwrap()
+                .validate(&configuration),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_trigonometric_functions() {
+        use std::f64::consts::PI;
+
+        assert_eq!(eval("sin(0)"), Ok(Value::Float(0.0)));
+        assert_eq!(eval("cos(0)"), Ok(Value::Float(1.0)));
+        assert_eq!(eval("tan(0.0)"), Ok(Value::Float(0.0)));
+        assert_eq!(eval("asin(1)"), Ok(Value::Float(PI / 2.0)));
+        assert_eq!(eval("acos(1)"), Ok(Value::Float(0.0)));
+        assert_eq!(eval("atan(1)"), Ok(Value::Float(PI / 4.0)));
+        assert_eq!(eval("atan2(1, 1)"), Ok(Value::Float(PI / 4.0)));
+        assert_eq!(eval("atan2(1, -1)"), Ok(Value::Float(3.0 * PI / 4.0)));
+        assert_eq!(eval("atan2(-1.0, -1)"), Ok(Value::Float(-3.0 * PI / 4.0)));
+        assert_eq!(eval("atan2(0, 1 + 1) * 2"), Ok(Value::Float(0.0)));
+
+        assert_eq!(
+            eval("asin(2.0)"),
+            Err(Error::domain_error("asin", Value::Float(2.0)))
+        );
+        assert_eq!(
+            eval("acos(-2)"),
+            Err(Error::domain_error("acos", Value::Int(-2)))
+        );
+        assert_eq!(eval("atan2(1)"), Err(Error::wrong_argument_amount(1, 2)));
+        assert_eq!(eval("sin(1, 2)"), Err(Error::wrong_argument_amount(2, 1)));
+        assert_eq!(
+            eval("atan2(1, true)"),
+            Err(Error::expected_number(Value::Boolean(true)))
+        );
+    }
+
+    #[test]
+    fn test_pow() {
+        assert_eq!(eval("pow(2, 10)"), Ok(Value::Int(1024)));
+        assert_eq!(eval("pow(-3, 3)"), Ok(Value::Int(-27)));
+        assert_eq!(eval("pow(0, 0)"), Ok(Value::Int(1)));
+        assert_eq!(eval("pow(2.0, -1)"), Ok(Value::Float(0.5)));
+        assert_eq!(eval("pow(9.0, 0.5)"), Ok(Value::Float(3.0)));
+        assert_eq!(eval("pow(4, 0.5)"), Ok(Value::Float(2.0)));
+        assert_eq!(eval("pow(2, 3) == 2 ** 3"), Ok(Value::Boolean(true)));
+
+        assert_eq!(
+            eval("pow(2, -1)"),
+            Err(Error::expected_positive_int(Value::Int(-1)))
+        );
+        assert_eq!(
+            eval("pow(2, 63)"),
+            Err(Error::integer_overflow("pow", 2, 63))
+        );
+        assert_eq!(
+            eval("pow(1, 4294967296)"),
+            Err(Error::integer_overflow("pow", 1, 4294967296))
+        );
+        assert_eq!(
+            eval("pow('2', 1)"),
+            Err(Error::expected_number(Value::from("2")))
+        );
+        assert_eq!(eval("pow(2)"), Err(Error::wrong_argument_amount(1, 2)));
+    }
+
+    #[test]
+    fn test_gcd_and_lcm() {
+        assert_eq!(eval("gcd(12, 8)"), Ok(Value::Int(4)));
+        assert_eq!(eval("gcd(0, 5)"), Ok(Value::Int(5)));
+        assert_eq!(eval("gcd(0, 0)"), Ok(Value::Int(0)));
+        assert_eq!(eval("gcd(-12, 18)"), Ok(Value::Int(6)));
+        assert_eq!(eval("gcd(7, 13)"), Ok(Value::Int(1)));
+        assert_eq!(eval("lcm(4, 6)"), Ok(Value::Int(12)));
+        assert_eq!(eval("lcm(-4, 6)"), Ok(Value::Int(12)));
+        assert_eq!(eval("lcm(0, 6)"), Ok(Value::Int(0)));
+        assert_eq!(
+            eval("lcm(9223372036854775807, 9223372036854775807)"),
+            Ok(Value::Int(IntType::MAX))
+        );
+
+        assert_eq!(
+            eval("lcm(9223372036854775807, 2)"),
+            Err(Error::integer_overflow("lcm", IntType::MAX, 2))
+        );
+        let configuration = HashMapConfiguration::builder()
+            .var("min", IntType::MIN)
+            .build();
+        assert_eq!(
+            eval_with_configuration("gcd(min, 0)", &configuration),
+            Err(Error::integer_overflow("gcd", IntType::MIN, 0))
+        );
+        assert_eq!(
+            eval_with_configuration("gcd(min, 6)", &configuration),
+            Ok(Value::Int(2))
+        );
+        assert_eq!(
+            eval("gcd(4.0, 2)"),
+            Err(Error::expected_int(Value::Float(4.0)))
+        );
+        assert_eq!(
+            eval("lcm(4, true)"),
+            Err(Error::expected_int(Value::Boolean(true)))
+        );
+    }
+
+    #[test]
+    fn test_clamp() {
+        assert_eq!(eval("clamp(5, 1, 10)"), Ok(Value::Int(5)));
+        assert_eq!(eval("clamp(-5, 0, 10)"), Ok(Value::Int(0)));
+        assert_eq!(eval("clamp(15, 0, 10)"), Ok(Value::Int(10)));
+        assert_eq!(eval("clamp(3, 3, 3)"), Ok(Value::Int(3)));
+        assert_eq!(eval("clamp(5.5, 5.0, 6.0)"), Ok(Value::Float(5.5)));
+        assert_eq!(eval("clamp(5, 0, 2.5)"), Ok(Value::Float(2.5)));
+        assert_eq!(eval("clamp(1.5, 2, 3)"), Ok(Value::Float(2.0)));
+        assert_eq!(eval("clamp(2, 1, 3.0)"), Ok(Value::Float(2.0)));
+
+        assert_eq!(
+            eval("clamp(1, 5, 3)"),
+            Err(Error::InvalidClampRange { min: 5.0, max: 3.0 })
+        );
+        assert_eq!(
+            eval("clamp(1, 5.5, 3)"),
+            Err(Error::InvalidClampRange { min: 5.5, max: 3.0 })
+        );
+        // Both bounds are the same float, but the minimum is greater than the maximum
+        assert!(matches!(
+            eval("clamp(0, 9007199254740993, 9007199254740992)"),
+            Err(Error::InvalidClampRange { .. })
+        ));
+        assert_eq!(
+            eval("clamp(0, 9007199254740992, 9007199254740993)"),
+            Ok(Value::Int(9007199254740992))
+        );
+        let configuration = HashMapConfiguration::builder()
+            .var("nan", FloatType::NAN)
+            .build();
+        assert!(matches!(
+            eval_with_configuration("clamp(1, nan, 3)", &configuration),
+            Err(Error::InvalidClampRange { .. })
+        ));
+        assert_eq!(
+            eval("clamp('a', 1, 2)"),
+            Err(Error::expected_number(Value::from("a")))
+        );
+        assert_eq!(
+            eval("clamp(1, 5, 3)").unwrap_err().to_string(),
+            "Invalid range for clamp with minimum 5 and maximum 3"
+        );
+    }
+
+    #[test]
+    fn test_if_error() {
+        assert_eq!(eval("if_error(1 / 0, 0)"), Ok(Value::Int(0)));
+        assert_eq!(eval("if_error(missing, 5)"), Ok(Value::Int(5)));
+        assert_eq!(eval("if_error(1 + 2, 5)"), Ok(Value::Int(3)));
+        assert_eq!(
+            eval("if_error(1 / 0, if_error(missing, 7))"),
+            Ok(Value::Int(7))
+        );
+        assert_eq!(eval("2 * if_error(1 / 0, 3) + 1"), Ok(Value::Int(7)));
+
+        // The fallback is only evaluated if the first argument fails, and its errors are returned
+        assert_eq!(eval("if_error(1, 1 / 0)"), Ok(Value::Int(1)));
+        assert_eq!(eval("if_error(1 / 0, 2 / 0)"), Err(Error::DivisionByZero));
+        assert!(matches!(
+            eval("if_error(1 / 0)"),
+            Err(Error::WrongArgumentAmount {
+                expected: 2,
+                actual: 1
+            })
+        ));
+
+        // Errors of the first argument are not validation errors
+        let tree = build_operator_tree("if_error(missing, 5)").unwrap();
+        assert_eq!(tree.validate(&EmptyConfiguration), Ok(()));
+        assert!(build_operator_tree("if_error(1, missing)")
+            .unwrap()
+            .validate(&EmptyConfiguration)
+            .is_err());
+
+        assert_eq!(tree.to_string(), "if_error(missing, 5)");
+        assert_eq!(
+            build_operator_tree(&tree.to_string())
+                .unwrap()
+                .eval(&EmptyConfiguration),
+            Ok(Value::Int(5))
+        );
+    }
+
+    #[test]
+    fn test_let() {
+        assert_eq!(eval("let x = 1 in let y = 2 in x + y"), Ok(Value::Int(3)));
+        assert_eq!(eval("let x = 3 in x * x"), Ok(Value::Int(9)));
+        assert_eq!(eval("let x = 1 in let x = x + 1 in x"), Ok(Value::Int(2)));
+        assert_eq!(
+            eval("let x = let y = 2 in y * 3 in x + 1"),
+            Ok(Value::Int(7))
+        );
+        assert_eq!(eval("(let x = 2 in x * 2) + 1"), Ok(Value::Int(5)));
+        assert_eq!(eval("len((let x = 2 in x, 3))"), Ok(Value::Int(2)));
+        assert_eq!(eval("let x = 2 in x > 1 ? x : 0"), Ok(Value::Int(2)));
+        assert_eq!(eval("true ? let x = 2 in x : 0"), Ok(Value::Int(2)));
+        assert_eq!(
+            eval("let a = (1, 2) in let b = (1 in a) in b"),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval("let x = 1 in if x > 0 { x; x + 1 } else { 0 }"),
+            Ok(Value::Int(2))
+        );
+
+        // The binding shadows the configuration without changing it
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("x".to_string(), Value::Int(10));
+        assert_eq!(
+            eval_with_mutable_configuration("let x = 1 in x + 1", &mut configuration),
+            Ok(Value::Int(2))
+        );
+        assert!(matches!(
+            eval_with_mutable_configuration("(let y = 1 in x + y); y", &mut configuration),
+            Err(Error::VariableIdentifierNotFound { .. })
+        ));
+        assert_eq!(configuration.get_value("x"), Some(&Value::Int(10)));
+
+        for expression in ["let x in 1", "let x = 1", "let = 1 in 2", "let 1 = 1 in 2"] {
+            assert!(matches!(
+                build_operator_tree(expression),
+                Err(Error::UnmatchedLet { .. })
+            ));
+        }
+
+        // The bound variable is known in the body only
+        let tree = build_operator_tree("let x = 2 in x * y").unwrap();
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("y".to_string(), Value::Int(3));
+        assert_eq!(tree.validate(&configuration), Ok(()));
+        assert!(build_operator_tree("let x = x in x")
+            .unwrap()
+            .validate(&EmptyConfiguration)
+            .is_err());
+        assert_eq!(
+            tree.partial_eval(&configuration).to_string(),
+            "(let x = 2 in (x * 3))"
+        );
+
+        assert_eq!(tree.to_string(), "(let x = 2 in (x * y))");
+        assert_eq!(
+            build_operator_tree(&tree.to_string())
+                .unwrap()
+                .eval(&configuration),
+            Ok(Value::Int(6))
+        );
+    }
+
+    #[test]
+    fn test_number_format() {
+        assert_eq!(eval("number_format(3.14159, 2)"), Ok(Value::from("3.14")));
+        assert_eq!(eval("number_format(1000, 0)"), Ok(Value::from("1000")));
+        assert_eq!(eval("number_format(1234, -2)"), Ok(Value::from("1200")));
+        assert_eq!(eval("number_format(5, 2)"), Ok(Value::from("5.00")));
+        assert_eq!(eval("number_format(-1250, -2)"), Ok(Value::from("-1300")));
+        assert_eq!(eval("number_format(1234.5, -2)"), Ok(Value::from("1200")));
+        assert_eq!(eval("number_format(1234, -30)"), Ok(Value::from("0")));
+        assert_eq!(eval("number_format(1e10, -400)"), Ok(Value::from("0")));
+        assert_eq!(eval("number_format(2.5, 0)"), Ok(Value::from("3")));
+        assert_eq!(eval("number_format(0.125, 2)"), Ok(Value::from("0.13")));
+        assert_eq!(
+            eval("number_format(1e20, 1)"),
+            Ok(Value::from("100000000000000000000.0"))
+        );
+        assert_eq!(eval("number_format(-0.5, 3)"), Ok(Value::from("-0.500")));
+
+        assert_eq!(
+            eval("number_format(1.5, 2.0)"),
+            Err(Error::expected_int(Value::Float(2.0)))
+        );
+        assert_eq!(
+            eval("number_format('1', 2)"),
+            Err(Error::expected_number(Value::from("1")))
+        );
+        assert!(matches!(
+            eval("number_format(9223372036854775807, -19)"),
+            Err(Error::IntegerOverflow { .. })
+        ));
+        assert_eq!(
+            eval("number_format(1.0, 65535)"),
+            Ok(Value::String(format!("1.{}", "0".repeat(65535))))
+        );
+        assert_eq!(
+            eval("number_format(1.0, 100000)"),
+            Err(Error::FormatError(
+                "Expected at most 65535 decimal places, but got 100000".to_string()
+            ))
+        );
+        assert_eq!(
+            eval("number_format(1, 9223372036854775807)"),
+            Err(Error::FormatError(
+                "Expected at most 65535 decimal places, but got 9223372036854775807".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_character_functions() {
+        assert_eq!(eval("char_at(\"abc\", 0)"), Ok(Value::from("a")));
+        assert_eq!(eval("char_at(\"abc\", 2)"), Ok(Value::from("c")));
+        assert_eq!(eval("char_at(\"abc\", -1)"), Ok(Value::from("c")));
+        assert_eq!(eval("char_at(\"abc\", -3)"), Ok(Value::from("a")));
+        assert_eq!(eval("char_at(\"häß\", 2)"), Ok(Value::from("ß")));
+        assert_eq!(eval("char_at(\"a😀b\", 1)"), Ok(Value::from("😀")));
+        assert_eq!(eval("char_at(\"a😀b\", -1)"), Ok(Value::from("b")));
+        assert_eq!(
+            eval("char_at(\"abc\", 3)"),
+            Err(Error::index_out_of_bounds(3, 3))
+        );
+        assert_eq!(
+            eval("char_at(\"a😀b\", -4)"),
+            Err(Error::index_out_of_bounds(-4, 3))
+        );
+        assert_eq!(
+            eval("char_at(\"\", 0)"),
+            Err(Error::index_out_of_bounds(0, 0))
+        );
+        assert_eq!(
+            eval("char_at(1, 0)"),
+            Err(Error::expected_string(Value::Int(1)))
+        );
+
+        assert_eq!(eval("char_code(\"A\")"), Ok(Value::Int(65)));
+        assert_eq!(eval("char_code(\"é\")"), Ok(Value::Int(0xe9)));
+        assert_eq!(eval("char_code(\"😀\")"), Ok(Value::Int(0x1f600)));
+        assert_eq!(
+            eval("char_code(\"ab\")"),
+            Err(Error::domain_error("char_code", Value::from("ab")))
+        );
+        assert_eq!(
+            eval("char_code(\"\")"),
+            Err(Error::domain_error("char_code", Value::from("")))
+        );
+
+        assert_eq!(eval("from_char_code(65)"), Ok(Value::from("A")));
+        assert_eq!(eval("from_char_code(233)"), Ok(Value::from("é")));
+        assert_eq!(eval("from_char_code(128512)"), Ok(Value::from("😀")));
+        assert_eq!(
+            eval("from_char_code(char_code(\"ß\"))"),
+            Ok(Value::from("ß"))
+        );
+        assert_eq!(
+            eval("from_char_code(55296)"),
+            Err(Error::domain_error("from_char_code", Value::Int(0xd800)))
+        );
+        assert_eq!(
+            eval("from_char_code(-1)"),
+            Err(Error::domain_error("from_char_code", Value::Int(-1)))
+        );
+        assert_eq!(
+            eval("from_char_code(1114112)"),
+            Err(Error::domain_error("from_char_code", Value::Int(0x110000)))
+        );
+    }
+
+    #[test]
+    fn test_aggregation_functions() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable(
+            "ints".to_string(),
+            Value::Array(vec![
+                Value::Int(2),
+                Value::Int(4),
+                Value::Int(4),
+                Value::Int(4),
+                Value::Int(5),
+                Value::Int(5),
+                Value::Int(7),
+                Value::Int(9),
+            ]),
+        );
+        configuration.insert_variable(
+            "mixed".to_string(),
+            Value::Array(vec![Value::Int(1), Value::Float(0.5), Value::Int(4)]),
+        );
+        configuration.insert_variable("empty".to_string(), Value::Array(Vec::new()));
+        configuration.insert_variable(
+            "strings".to_string(),
+            Value::Array(vec![Value::Int(1), Value::String("a".to_string())]),
+        );
+        configuration.insert_variable(
+            "big".to_string(),
+            Value::Array(vec![Value::Int(IntType::MAX), Value::Int(1)]),
+        );
+        let eval = |string: &str| eval_with_configuration(string, &configuration);
+
+        assert_eq!(eval("sum(ints)"), Ok(Value::Int(40)));
+        assert_eq!(eval("sum(mixed)"), Ok(Value::Float(5.5)));
+        assert_eq!(eval("product(ints)"), Ok(Value::Int(201600)));
+        assert_eq!(eval("product(mixed)"), Ok(Value::Float(2.0)));
+        assert_eq!(eval("avg(ints)"), Ok(Value::Float(5.0)));
+        assert_eq!(eval("avg(mixed)"), Ok(Value::Float(5.5 / 3.0)));
+        assert_eq!(eval("stdev(ints)"), Ok(Value::Float(2.0)));
+        assert_eq!(eval("stdev(mixed) > 0"), Ok(Value::Boolean(true)));
+
+        for function in &["sum", "product", "avg", "stdev"] {
+            assert_eq!(
+                eval(&format!("{}(empty)", function)),
+                Err(Error::EmptyArray)
+            );
+            assert_eq!(
+                eval(&format!("{}(strings)", function)),
+                Err(Error::expected_number(Value::String("a".to_string())))
+            );
+            assert_eq!(
+                eval(&format!("{}(1)", function)),
+                Err(Error::expected_array(Value::Int(1)))
+            );
+        }
+        assert_eq!(
+            eval("sum(big)"),
+            Err(Error::integer_overflow("+", IntType::MAX, 1))
+        );
+    }
+
+    #[test]
+    fn test_string_functions() {
+        assert_eq!(
+            eval("trim('  a b  ')"),
+            Ok(Value::String("a b".to_string()))
+        );
+        assert_eq!(
+            eval("to_upper('aBc')"),
+            Ok(Value::String("ABC".to_string()))
+        );
+        assert_eq!(
+            eval("to_lower('aBc')"),
+            Ok(Value::String("abc".to_string()))
+        );
+        assert_eq!(eval("starts_with('Hello', 'He')"), Ok(Value::Boolean(true)));
+        assert_eq!(
+            eval("starts_with('Hello', 'lo')"),
+            Ok(Value::Boolean(false))
+        );
+        assert_eq!(eval("ends_with('Hello', 'lo')"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("contains('Hello', 'ell')"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("contains('Hello', 'x')"), Ok(Value::Boolean(false)));
+        assert_eq!(
+            eval("replace('a-b-c', '-', '+')"),
+            Ok(Value::String("a+b+c".to_string()))
+        );
+        assert_eq!(
+            eval("split('a,b,,c', ',')"),
+            Ok(Value::Array(vec![
+                Value::String("a".to_string()),
+                Value::String("b".to_string()),
+                Value::String(String::new()),
+                Value::String("c".to_string()),
+            ]))
+        );
+        assert_eq!(eval("len('äöü')"), Ok(Value::Int(3)));
+        assert_eq!(eval("len(split('a b', ' '))"), Ok(Value::Int(2)));
+        assert_eq!(eval("len('')"), Ok(Value::Int(0)));
+
+        assert_eq!(eval("trim(1)"), Err(Error::expected_string(Value::Int(1))));
+        assert_eq!(
+            eval("contains('a', true)"),
+            Err(Error::expected_string(Value::Boolean(true)))
+        );
+        assert_eq!(
+            eval("replace('a', 'b', null)"),
+            Err(Error::expected_string(Value::Null))
+        );
+        assert_eq!(
+            eval("split(1, ',')"),
+            Err(Error::expected_string(Value::Int(1)))
+        );
+        assert_eq!(
+            eval("len(1)"),
+            Err(Error::expected_array_or_string(Value::Int(1)))
+        );
+    }
+
+    #[test]
+    fn test_array_functions() {
+        fn ints(ints: &[IntType]) -> Value {
+            Value::Array(ints.iter().map(|int| Value::Int(*int)).collect())
+        }
+
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("a".to_string(), ints(&[3, 1, 2, 3, 1]));
+        configuration.insert_variable(
+            "numbers".to_string(),
+            Value::Array(vec![Value::Float(2.5), Value::Int(-1), Value::Int(2)]),
+        );
+        configuration.insert_variable(
+            "nested".to_string(),
+            Value::Array(vec![
+                ints(&[1, 2]),
+                Value::Int(3),
+                Value::Array(vec![ints(&[4])]),
+            ]),
+        );
+        configuration.insert_variable(
+            "mixed".to_string(),
+            Value::Array(vec![Value::Int(1), Value::String("a".to_string())]),
+        );
+        configuration.insert_variable(
+            "large".to_string(),
+            ints(&[9007199254740993, 9007199254740992]),
+        );
+        let eval = |string: &str| eval_with_configuration(string, &configuration);
+
+        assert_eq!(eval("sort(a)"), Ok(ints(&[1, 1, 2, 3, 3])));
+        assert_eq!(
+            eval("sort(large)"),
+            Ok(ints(&[9007199254740992, 9007199254740993]))
+        );
+        assert_eq!(
+            eval("sort(numbers)"),
+            Ok(Value::Array(vec![
+                Value::Int(-1),
+                Value::Int(2),
+                Value::Float(2.5)
+            ]))
+        );
+        assert_eq!(
+            eval("sort(split('b a c', ' '))"),
+            Ok(Value::Array(vec![
+                Value::String("a".to_string()),
+                Value::String("b".to_string()),
+                Value::String("c".to_string()),
+            ]))
+        );
+        assert_eq!(eval("reverse(a)"), Ok(ints(&[1, 3, 2, 1, 3])));
+        assert_eq!(eval("unique(a)"), Ok(ints(&[3, 1, 2])));
+        assert_eq!(
+            eval("flatten(nested)"),
+            Ok(Value::Array(vec![
+                Value::Int(1),
+                Value::Int(2),
+                Value::Int(3),
+                ints(&[4])
+            ]))
+        );
+        assert_eq!(eval("slice(a, 1, 3)"), Ok(ints(&[1, 2])));
+        assert_eq!(eval("slice(a, -2, 5)"), Ok(ints(&[3, 1])));
+        assert_eq!(eval("slice(a, 0, -1)"), Ok(ints(&[3, 1, 2, 3])));
+        assert_eq!(eval("slice(a, -10, 10)"), Ok(ints(&[3, 1, 2, 3, 1])));
+        assert_eq!(eval("slice(a, 3, 1)"), Ok(ints(&[])));
+        assert_eq!(eval("a"), Ok(ints(&[3, 1, 2, 3, 1])));
+
+        assert_eq!(
+            eval("sort(mixed)"),
+            Err(Error::expected_number(Value::String("a".to_string())))
+        );
+        assert_eq!(
+            eval("sort(reverse(mixed))"),
+            Err(Error::expected_string(Value::Int(1)))
+        );
+        assert_eq!(
+            eval("reverse(1)"),
+            Err(Error::expected_array(Value::Int(1)))
+        );
+        assert_eq!(
+            eval("slice(a, 0, 1.5)"),
+            Err(Error::expected_int(Value::Float(1.5)))
+        );
+        assert_eq!(eval("first(a)"), Ok(Value::Int(3)));
+        assert_eq!(eval("last(reverse(a))"), Ok(Value::Int(3)));
+        assert_eq!(eval("first(slice(a, 0, 0))"), Err(Error::EmptyArray));
+    }
+
+    #[test]
+    fn test_index() {
+        let mut object = ObjectMap::default();
+        object.insert("key".to_string(), Value::Int(7));
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable(
+            "a".to_string(),
+            Value::Array(vec![Value::Int(10), Value::Int(11), Value::Int(12)]),
+        );
+        configuration.insert_variable(
+            "nested".to_string(),
+            Value::Array(vec![Value::Array(vec![Value::Int(1), Value::Int(2)])]),
+        );
+        configuration.insert_variable("o".to_string(), Value::Object(object));
+        let eval = |string: &str| eval_with_configuration(string, &configuration);
+
+        assert_eq!(eval("a[0]"), Ok(Value::Int(10)));
+        assert_eq!(eval("a[1 + 1]"), Ok(Value::Int(12)));
+        assert_eq!(eval("a[len(a) - 1] * 2"), Ok(Value::Int(24)));
+        assert_eq!(eval("-a[0]"), Ok(Value::Int(-10)));
+        assert_eq!(eval("nested[0][1]"), Ok(Value::Int(2)));
+        assert_eq!(eval("reverse(a)[0]"), Ok(Value::Int(12)));
+        assert_eq!(eval("(a)[a[0] - 9]"), Ok(Value::Int(11)));
+        assert_eq!(
+            eval("split('a b', ' ')[1]"),
+            Ok(Value::String("b".to_string()))
+        );
+        assert_eq!(eval("o['key']"), Ok(Value::Int(7)));
+        assert_eq!(eval("o['nope'] ?? 0"), Ok(Value::Int(0)));
+
+        assert_eq!(eval("a[3]"), Err(Error::index_out_of_bounds(3, 3)));
+        assert_eq!(eval("a[-1]"), Err(Error::index_out_of_bounds(-1, 3)));
+        assert_eq!(
+            eval("a[true]"),
+            Err(Error::expected_int(Value::Boolean(true)))
+        );
+        assert_eq!(eval("1[0]"), Err(Error::expected_array(Value::Int(1))));
+        assert_eq!(
+            eval("a[0"),
+            Err(Error::UnmatchedLBracket { span: Some(1..2) })
+        );
+        assert_eq!(
+            eval("a[(0]"),
+            Err(Error::UnmatchedLBrace { span: Some(2..3) })
+        );
+        assert_eq!(
+            eval("a[0)"),
+            Err(Error::UnmatchedLBracket { span: Some(1..2) })
+        );
+        assert_eq!(
+            eval("a]"),
+            Err(Error::UnmatchedRBracket { span: Some(1..2) })
+        );
+
+        let tree = build_operator_tree("nested[0][a[0] - 10] + 1").unwrap();
+        assert_eq!(tree.to_string(), "(nested[0][(a[0] - 10)] + 1)");
+        assert_eq!(
+            build_operator_tree(&tree.to_string())
+                .unwrap()
+                .eval(&configuration),
+            Ok(Value::Int(2))
+        );
+    }
+
+    #[test]
+    fn test_type_of() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable(
+            "nested".to_string(),
+            Value::Array(vec![Value::Array(vec![Value::Int(1)])]),
+        );
+        configuration.insert_variable("object".to_string(), Value::Object(ObjectMap::default()));
+        configuration.insert_variable("x".to_string(), Value::Int(3));
+        let eval = |string: &str| eval_with_configuration(string, &configuration);
+
+        assert_eq!(eval("type_of(1)"), Ok(Value::String("int".to_string())));
+        assert_eq!(eval("type_of(1.5)"), Ok(Value::String("float".to_string())));
+        assert_eq!(
+            eval("type_of(true)"),
+            Ok(Value::String("boolean".to_string()))
+        );
+        assert_eq!(
+            eval("type_of('a')"),
+            Ok(Value::String("string".to_string()))
+        );
+        assert_eq!(eval("type_of(null)"), Ok(Value::String("null".to_string())));
+        assert_eq!(
+            eval("type_of(nested)"),
+            Ok(Value::String("array".to_string()))
+        );
+        assert_eq!(
+            eval("type_of(map(nested, 'type_of'))"),
+            Ok(Value::String("array".to_string()))
+        );
+        assert_eq!(
+            eval("map(nested, 'type_of')"),
+            Ok(Value::Array(vec![Value::String("array".to_string())]))
+        );
+        assert_eq!(
+            eval("type_of(object)"),
+            Ok(Value::String("object".to_string()))
+        );
+        assert_eq!(
+            eval("type_of(x) == 'int' && x > 0"),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval("type_of(1, 2)"),
+            Err(Error::wrong_argument_amount(2, 1))
+        );
+    }
+
+    #[test]
+    fn test_conversion_functions() {
+        assert_eq!(eval("to_int(3)"), Ok(Value::Int(3)));
+        assert_eq!(eval("to_int(3.9)"), Ok(Value::Int(3)));
+        assert_eq!(eval("to_int(-3.9)"), Ok(Value::Int(-3)));
+        assert_eq!(eval("to_int(true) + to_int(false)"), Ok(Value::Int(1)));
+        assert_eq!(eval("to_int('42')"), Ok(Value::Int(42)));
+        assert_eq!(eval("to_int('-7') * 2"), Ok(Value::Int(-14)));
+        assert_eq!(eval("to_float(2)"), Ok(Value::Float(2.0)));
+        assert_eq!(eval("to_float(2.5)"), Ok(Value::Float(2.5)));
+        assert_eq!(eval("to_float('1e3')"), Ok(Value::Float(1000.0)));
+        assert_eq!(eval("to_string(12)"), Ok(Value::String("12".to_string())));
+        assert_eq!(eval("to_string(1.0)"), Ok(Value::String("1.0".to_string())));
+        assert_eq!(
+            eval("to_string(false)"),
+            Ok(Value::String("false".to_string()))
+        );
+        assert_eq!(eval("to_string('a')"), Ok(Value::String("a".to_string())));
+        assert_eq!(
+            eval("to_string(null)"),
+            Ok(Value::String("null".to_string()))
+        );
+        assert_eq!(
+            eval("to_string(split('a b', ' '))"),
+            Ok(Value::String("[\"a\", \"b\"]".to_string()))
+        );
+        assert_eq!(eval("to_bool(1)"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("to_bool(0)"), Ok(Value::Boolean(false)));
+        assert_eq!(eval("to_bool(0.0)"), Ok(Value::Boolean(false)));
+        assert_eq!(eval("to_bool(-0.5)"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("to_bool('')"), Ok(Value::Boolean(false)));
+        assert_eq!(eval("to_bool('false')"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("to_bool(split('', ','))"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("to_bool(null)"), Ok(Value::Boolean(false)));
+        assert_eq!(eval("to_bool(true)"), Ok(Value::Boolean(true)));
+
+        assert_eq!(
+            eval("to_int('abc')"),
+            Err(Error::ParseError(
+                "invalid digit found in string".to_string()
+            ))
+        );
+        assert_eq!(
+            eval("to_float('abc')"),
+            Err(Error::ParseError("invalid float literal".to_string()))
+        );
+        assert_eq!(eval("to_int(null)"), Err(Error::TypeError));
+        assert_eq!(eval("to_float(true)"), Err(Error::TypeError));
+    }
+
+    #[test]
+    fn test_higher_order_functions() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable(
+            "a".to_string(),
+            Value::Array(vec![
+                Value::Int(1),
+                Value::Int(2),
+                Value::Int(3),
+                Value::Int(4),
+            ]),
+        );
+        configuration.insert_function(
+            "double".to_string(),
+            Function::new(
+                1,
+                Box::new(|arguments| Ok(Value::Int(arguments[0].clone().into_int()? * 2))),
+            ),
+        );
+        configuration.insert_function(
+            "is_even".to_string(),
+            Function::new(
+                1,
+                Box::new(|arguments| Ok(Value::Boolean(arguments[0].clone().into_int()? % 2 == 0))),
+            ),
+        );
+        configuration.insert_function(
+            "add".to_string(),
+            Function::new(
+                2,
+                Box::new(|arguments| {
+                    Ok(Value::Int(
+                        arguments[0].clone().into_int()? + arguments[1].clone().into_int()?,
+                    ))
+                }),
+            ),
+        );
+        let eval = |string: &str| eval_with_configuration(string, &configuration);
+
+        assert_eq!(
+            eval("map(a, 'double')"),
+            Ok(Value::Array(vec![
+                Value::Int(2),
+                Value::Int(4),
+                Value::Int(6),
+                Value::Int(8)
+            ]))
+        );
+        assert_eq!(
+            eval("filter(a, 'is_even')"),
+            Ok(Value::Array(vec![Value::Int(2), Value::Int(4)]))
+        );
+        assert_eq!(eval("reduce(a, 'add', 0)"), Ok(Value::Int(10)));
+        assert_eq!(
+            eval("reduce(map(filter(a, 'is_even'), 'double'), 'add', 100)"),
+            Ok(Value::Int(112))
+        );
+        assert_eq!(
+            eval("map(split('a b', ' '), 'to_upper')"),
+            Ok(Value::Array(vec![
+                Value::String("A".to_string()),
+                Value::String("B".to_string())
+            ]))
+        );
+        assert_eq!(
+            eval("map(a, 'nope')"),
+            Err(Error::function_identifier_not_found(
+                "nope".to_string(),
+                None
+            ))
+        );
+        assert_eq!(
+            eval("filter(a, 'double')"),
+            Err(Error::expected_boolean(Value::Int(2)))
+        );
+        assert_eq!(
+            eval("map(a, 'add')"),
+            Err(Error::wrong_argument_amount(1, 2))
+        );
+        assert_eq!(
+            eval("map(1, 'double')"),
+            Err(Error::expected_array(Value::Int(1)))
+        );
+        assert_eq!(eval("map(a)"), Err(Error::wrong_argument_amount(1, 2)));
+        assert_eq!(
+            build_operator_tree("reduce(a, 'add')")
+                .unwrap()
+                .validate(&configuration),
+            Err(Error::wrong_argument_amount(2, 3))
+        );
+    }
+
+    #[test]
+    fn test_converge() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_function(
+            "half".to_string(),
+            Function::new(
+                1,
+                Box::new(|arguments| Ok(Value::Int(arguments[0].clone().into_int()? / 2))),
+            ),
+        );
+        configuration.insert_function(
+            "negate".to_string(),
+            Function::new(
+                1,
+                Box::new(|arguments| Ok(Value::Int(-arguments[0].clone().into_int()?))),
+            ),
+        );
+        configuration.insert_function(
+            "increment".to_string(),
+            Function::new(
+                1,
+                Box::new(|arguments| Ok(Value::Int(arguments[0].clone().into_int()? + 1))),
+            ),
+        );
+        configuration.insert_function(
+            "rotate".to_string(),
+            Function::new(
+                1,
+                Box::new(|arguments| Ok(Value::Int((arguments[0].clone().into_int()? + 1) % 3))),
+            ),
+        );
+        let eval = |string: &str| eval_with_configuration(string, &configuration);
+
+        assert_eq!(eval("converge(100, 'half')"), Ok(Value::Int(0)));
+        assert_eq!(eval("converge(0, 'half')"), Ok(Value::Int(0)));
+        assert_eq!(eval("converge(4.0, 'sqrt')"), Ok(Value::Float(1.0)));
+        assert_eq!(
+            eval("converge(1, 'negate')"),
+            Err(Error::CircularDependency)
+        );
+        assert_eq!(
+            eval("converge(0, 'rotate')"),
+            Err(Error::CircularDependency)
+        );
+        assert_eq!(
+            eval("converge(0, 'increment')"),
+            Err(Error::convergence_failure(1000))
+        );
+        assert_eq!(
+            eval("converge(0, 'increment', 10)"),
+            Err(Error::convergence_failure(10))
+        );
+        assert_eq!(eval("converge(100, 'half', 10)"), Ok(Value::Int(0)));
+        assert_eq!(
+            eval("converge(100, 'half', 3)"),
+            Err(Error::convergence_failure(3))
+        );
+        assert_eq!(
+            eval("converge(1, 'half', 0)"),
+            Err(Error::expected_positive_int(Value::Int(0)))
+        );
+        assert_eq!(eval("converge(1)"), Err(Error::wrong_argument_amount(1, 2)));
+        assert_eq!(
+            eval("converge(1, 'half', 1, 1)"),
+            Err(Error::wrong_argument_amount(4, 2))
+        );
+        assert_eq!(
+            build_operator_tree("converge(1, 'half', 10)")
+                .unwrap()
+                .validate(&configuration),
+            Ok(())
+        );
+        assert_eq!(
+            Error::convergence_failure(10).to_string(),
+            "No fixed point was reached within 10 iterations"
+        );
+    }
+
+    #[test]
+    fn test_function_with_context() {
+        struct Database {
+            prices: Vec<IntType>,
+        }
+
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("discount".to_string(), 5);
+        configuration.insert_function_with_context(
+            "price".to_string(),
+            FunctionWithContext::new(
+                1,
+                Box::new(|arguments, context| {
+                    let database = context.downcast_ref::<Database>().ok_or(Error::TypeError)?;
+                    let index = arguments[0].clone().into_int()?;
+                    database
+                        .prices
+                        .get(index as usize)
+                        .map(|price| Value::Int(*price))
+                        .ok_or(Error::index_out_of_bounds(index, database.prices.len()))
+                }),
+            ),
+        );
+        let database = Arc::new(Database {
+            prices: vec![10, 20],
+        });
+
+        assert_eq!(
+            eval_with_configuration_and_context(
+                "price(1) - discount",
+                &configuration,
+                database.clone()
+            ),
+            Ok(Value::Int(15))
+        );
+        assert_eq!(
+            eval_with_configuration_and_context("price(2)", &configuration, database.clone()),
+            Err(Error::function_error(
+                "price".to_string(),
+                Error::index_out_of_bounds(2, 2)
+            ))
+        );
+        assert_eq!(
+            eval_with_configuration_and_context("price(0)", &configuration, Arc::new(1)),
+            Err(Error::function_error("price".to_string(), Error::TypeError))
+        );
+        // Without a context, functions with context are not available
+        assert_eq!(
+            eval_with_configuration("price(0)", &configuration),
+            Err(Error::function_identifier_not_found(
+                "price".to_string(),
+                Some(0..5)
+            ))
+        );
+
+        // Functions without context take precedence
+        configuration.insert_function(
+            "price".to_string(),
+            Function::new(1, Box::new(|_| Ok(Value::Int(0)))),
+        );
+        let configuration_with_context = ConfigurationWithContext::new(&configuration, database);
+        assert_eq!(
+            eval_with_configuration("price(0)", &configuration_with_context),
+            Ok(Value::Int(0))
+        );
+        assert_eq!(configuration_with_context.function_names(), vec!["price"]);
+    }
+
+    #[test]
+    fn test_multiple_arguments() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_function(
+            "sum3".to_string(),
+            Function::new(
+                3,
+                Box::new(|arguments| {
+                    Ok(Value::Int(
+                        arguments
+                            .iter()
+                            .map(|argument| argument.as_int().unwrap())
+                            .sum(),
+                    ))
+                }),
+            ),
+        );
+        configuration.insert_function(
+            "first".to_string(),
+            Function::new(1, Box::new(|arguments| Ok(arguments[0].clone()))),
+        );
+        configuration.insert_variable("five".to_string(), Value::Int(5));
+
+        assert_eq!(
+            eval_with_configuration("sum3(1, 2, 3)", &configuration),
+            Ok(Value::Int(6))
+        );
+        assert_eq!(
+            eval_with_configuration(
+                "sum3(five * 2, first(1), five > 3 ? 1 : 0) + 1",
+                &configuration
+            ),
+            Ok(Value::Int(13))
+        );
+        assert_eq!(
+            eval_with_configuration("sum3(1, 2)", &configuration),
+            Err(Error::wrong_argument_amount(2, 3))
+        );
+        // Braces around comma-separated values pass a single tuple
+        assert_eq!(
+            eval_with_configuration("first((1, 2))", &configuration),
+            Ok(Value::Tuple(vec![Value::Int(1), Value::Int(2)]))
+        );
+        assert_eq!(
+            build_operator_tree("sum3(1, 2) + first(1, five)")
+                .unwrap()
+                .validate(&configuration),
+            Err(Error::MultipleErrors(vec![
+                Error::wrong_argument_amount(2, 3),
+                Error::wrong_argument_amount(2, 1),
+            ]))
+        );
+        assert_eq!(
+            build_operator_tree("sum3(x, 2, 3) + sum3(1, 2, 3)")
+                .unwrap()
+                .partial_eval(&configuration)
+                .to_string(),
+            "(sum3(x, 2, 3) + 6)"
+        );
+    }
+
+    #[test]
+    fn test_stateful_function() {
+        let counter = StatefulFunction::new(0, 0, |count: &mut IntType, _| {
+            *count += 1;
+            Ok(Value::Int(*count))
+        });
+        let state = counter.state();
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_function("next_id".to_string(), counter.into());
+
+        assert_eq!(
+            eval_with_configuration("next_id()", &configuration),
+            Ok(Value::Int(1))
+        );
+        assert_eq!(
+            eval_with_configuration("(next_id(), next_id())", &configuration),
+            Ok(Value::Tuple(vec![Value::Int(2), Value::Int(3)]))
+        );
+        assert_eq!(*state.lock().unwrap(), 3);
+
+        // Clones of the configuration share the state
+        let clone = configuration.clone();
+        assert_eq!(
+            eval_with_configuration("next_id()", &clone),
+            Ok(Value::Int(4))
+        );
+        assert_eq!(
+            eval_with_configuration("next_id()", &configuration),
+            Ok(Value::Int(5))
+        );
+
+        let total = StatefulFunction::new(1, 0, |total: &mut IntType, arguments| {
+            *total += arguments[0]
+                .as_int()
+                .ok_or_else(|| Error::expected_int(arguments[0].clone()))?;
+            Ok(Value::Int(*total))
+        });
+        configuration.insert_function("add_to_total".to_string(), total.into());
+        assert_eq!(
+            eval_with_configuration("add_to_total(2); add_to_total(3)", &configuration),
+            Ok(Value::Int(5))
+        );
+        assert_eq!(
+            eval_with_configuration("add_to_total(true)", &configuration),
+            Err(Error::function_error(
+                "add_to_total".to_string(),
+                Error::expected_int(Value::Boolean(true))
+            ))
+        );
+        assert_eq!(
+            eval_with_configuration("add_to_total(1)", &configuration),
+            Ok(Value::Int(6))
+        );
+    }
+
+    #[test]
+    fn test_functions_without_arguments() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_function(
+            "answer".to_string(),
+            Function::new(0, Box::new(|_| Ok(Value::Int(42)))),
+        );
+        let eval = |string: &str| eval_with_configuration(string, &configuration);
+
+        assert_eq!(eval("answer()"), Ok(Value::Int(42)));
+        assert_eq!(eval("answer() + answer()"), Ok(Value::Int(84)));
+        assert_eq!(eval("abs(answer())"), Ok(Value::Int(42)));
+        assert_eq!(eval("answer(1)"), Err(Error::wrong_argument_amount(1, 0)));
+        assert_eq!(eval("abs()"), Err(Error::wrong_argument_amount(0, 1)));
+
+        let tree = build_operator_tree("answer() * 2").unwrap();
+        assert_eq!(tree.to_string(), "(answer() * 2)");
+        assert_eq!(tree.validate(&configuration), Ok(()));
+        assert_eq!(
+            build_operator_tree("abs()")
+                .unwrap()
+                .validate(&configuration),
+            Err(Error::wrong_argument_amount(0, 1))
+        );
+    }
+
+    #[test]
+    fn test_tuples() {
+        let tuple = |values: &[IntType]| {
+            Value::Tuple(values.iter().map(|value| Value::Int(*value)).collect())
+        };
+        assert_eq!(eval("(1, 2, 3)"), Ok(tuple(&[1, 2, 3])));
+        assert_eq!(eval("(1, 1 + 1)"), Ok(tuple(&[1, 2])));
+        assert_eq!(eval("1, 2"), Ok(tuple(&[1, 2])));
+        assert_eq!(eval("(1,)"), Ok(tuple(&[1])));
+        assert_eq!(eval("(1)"), Ok(Value::Int(1)));
+        assert_eq!(
+            eval("((1, 2), 3)"),
+            Ok(Value::Tuple(vec![tuple(&[1, 2]), Value::Int(3)]))
+        );
+        assert_eq!(eval("(1, 2, 3)[1]"), Ok(Value::Int(2)));
+        assert_eq!(eval("len((1, 2, 3))"), Ok(Value::Int(3)));
+        assert_eq!(eval("len((1,))"), Ok(Value::Int(1)));
+        assert_eq!(eval("2 in (1, 2)"), Ok(Value::Boolean(true)));
+        assert_eq!(
+            eval("type_of((1, 2))"),
+            Ok(Value::String("tuple".to_string()))
+        );
+        assert_eq!(eval("(1, 2) == (1, 2)"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("(1, 2)[2]"), Err(Error::index_out_of_bounds(2, 2)));
+        // Trailing commas are also allowed in function calls
+        assert_eq!(eval("abs(-1,)"), Ok(Value::Int(1)));
+
+        // Functions can return tuples
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_function(
+            "divmod".to_string(),
+            Function::new(
+                2,
+                Box::new(|arguments| {
+                    let a = arguments[0].clone().into_int()?;
+                    let b = arguments[1].clone().into_int()?;
+                    Ok(Value::Tuple(vec![Value::Int(a / b), Value::Int(a % b)]))
+                }),
+            ),
+        );
+        assert_eq!(
+            eval_with_configuration("divmod(7, 2)", &configuration),
+            Ok(tuple(&[3, 1]))
+        );
+        assert_eq!(
+            eval_with_configuration("divmod(7, 2)[0] * 2 + divmod(7, 2)[1]", &configuration),
+            Ok(Value::Int(7))
+        );
+
+        assert_eq!(tuple(&[1, 2]).to_string(), "(1, 2)");
+        assert_eq!(tuple(&[1]).to_string(), "(1,)");
+        assert_eq!(build_operator_tree("(a,)").unwrap().to_string(), "(a,)");
+        let tree = build_operator_tree("(1, 2)").unwrap();
+        assert_eq!(
+            Node::from_value(tree.eval(&EmptyConfiguration).unwrap()).to_string(),
+            "(1, 2)"
+        );
+        assert_eq!(
+            build_operator_tree("abs(1, 2)")
+                .unwrap()
+                .partial_eval(&EmptyConfiguration)
+                .to_string(),
+            "abs(1, 2)"
+        );
+    }
+
+    #[test]
+    fn test_range() {
+        let array = |values: &[IntType]| {
+            Value::Array(values.iter().map(|value| Value::Int(*value)).collect())
+        };
+        assert_eq!(eval("0..3"), Ok(array(&[0, 1, 2])));
+        assert_eq!(eval("-3..0"), Ok(array(&[-3, -2, -1])));
+        assert_eq!(eval("5..3"), Ok(array(&[])));
+        assert_eq!(eval("3..3"), Ok(array(&[])));
+        assert_eq!(eval("1..2 + 2"), Ok(array(&[1, 2, 3])));
+        assert_eq!(eval("(1..4)[1]"), Ok(Value::Int(2)));
+        assert_eq!(eval("sum(1..5)"), Ok(Value::Int(10)));
+        assert_eq!(eval("2 in 1..3"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("atan2(...(0..2))"), Ok(Value::Float(0.0)));
+        assert_eq!(
+            eval("0.5..2.5"),
+            Err(Error::expected_int(Value::Float(0.5)))
+        );
+        assert_eq!(eval("0..2.5"), Err(Error::expected_int(Value::Float(2.5))));
+
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("start".to_string(), -1);
+        configuration.insert_variable("end".to_string(), 2);
+        assert_eq!(
+            eval_with_configuration("start..end", &configuration),
+            Ok(array(&[-1, 0, 1]))
+        );
+        assert_eq!(
+            eval_with_configuration("start * 2..end * 2", &configuration),
+            Ok(array(&[-2, -1, 0, 1, 2, 3]))
+        );
+        assert_eq!(
+            build_operator_tree("start..end").unwrap().to_string(),
+            "(start .. end)"
+        );
+    }
+
+    #[test]
+    fn test_spread() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable(
+            "nums".to_string(),
+            Value::Array(vec![Value::Int(1), Value::Int(2), Value::Int(3)]),
+        );
+        configuration.insert_variable(
+            "pair".to_string(),
+            Value::Array(vec![Value::Int(2), Value::Int(3)]),
+        );
+        configuration.insert_function(
+            "sum3".to_string(),
+            Function::new(
+                3,
+                Box::new(|arguments| {
+                    let mut sum = 0;
+                    for argument in arguments {
+                        sum += argument.clone().into_int()?;
+                    }
+                    Ok(Value::Int(sum))
+                }),
+            ),
+        );
+
+        assert_eq!(
+            eval_with_configuration("sum3(...nums)", &configuration),
+            Ok(Value::Int(6))
+        );
+        assert_eq!(
+            eval_with_configuration("sum3(1, ...pair)", &configuration),
+            Ok(Value::Int(6))
+        );
+        assert_eq!(
+            eval_with_configuration("sum3(...(1, 2), 3)", &configuration),
+            Ok(Value::Int(6))
+        );
+        assert_eq!(
+            eval_with_configuration("sum3(...pair)", &configuration),
+            Err(Error::wrong_argument_amount(2, 3))
+        );
+        assert_eq!(eval("atan2(...(0, 1))"), Ok(Value::Float(0.0)));
+        assert_eq!(eval("abs(...1)"), Err(Error::expected_array(Value::Int(1))));
+        assert_eq!(eval("...(1, 2)"), Err(Error::UnexpectedSpread));
+        assert_eq!(eval("'...'"), Ok(Value::String("...".to_string())));
+
+        // The amount of arguments is not known before spread arguments are evaluated
+        let tree = build_operator_tree("sum3(...nums)").unwrap();
+        assert_eq!(tree.validate(&configuration), Ok(()));
+        assert_eq!(tree.to_string(), "sum3(...nums)");
+        assert_eq!(
+            build_operator_tree("sum3(...pair, 1)").unwrap().to_string(),
+            "sum3(...pair, 1)"
+        );
+    }
+
+    #[test]
+    fn test_rewrite() {
+        // Eliminates double negations such as `-(-a)`, skipping the root nodes of braces
+        let eliminate_double_negation = |node: Node| {
+            let unwrap_braces = |node: &Node| {
+                let mut node = node.clone();
+                while node.operator().is_root_node() {
+                    node = node.children()[0].clone();
+                }
+                node
+            };
+            if node.operator().is_unary() && node.operator().to_string() == "-" {
+                let child = unwrap_braces(&node.children()[0]);
+                if child.operator().is_unary() && child.operator().to_string() == "-" {
+                    return unwrap_braces(&child.children()[0]);
+                }
+            }
+            node
+        };
+        let tree = build_operator_tree("-(-a) + -(-(-(-b)))").unwrap();
+        assert_eq!(
+            tree.rewrite(eliminate_double_negation).to_string(),
+            "(a + b)"
+        );
+        let tree = build_operator_tree("-(-(-a))").unwrap();
+        assert_eq!(tree.rewrite(eliminate_double_negation).to_string(), "(-a)");
+
+        // Nodes are rewritten bottom-up
+        let visited = RefCell::new(Vec::new());
+        let tree = build_operator_tree("a * b + c").unwrap();
+        let rewritten = tree.rewrite(|node| {
+            visited.borrow_mut().push(node.to_string());
+            match node.operator().variable_identifier() {
+                Some("b") => Node::from_value(Value::Int(2)),
+                _ => node,
+            }
+        });
+        assert_eq!(rewritten.to_string(), "((a * 2) + c)");
+        assert_eq!(
+            visited.into_inner(),
+            vec!["a", "b", "(a * 2)", "c", "((a * 2) + c)"]
+        );
+        assert_eq!(tree.to_string(), "((a * b) + c)");
+
+        // Rewriting with the identity keeps the tree
+        let tree = build_operator_tree("max(a, x ? 'a' : a)").unwrap();
+        assert_eq!(tree.rewrite(|node| node).to_string(), tree.to_string());
+    }
+
+    #[test]
+    fn test_substitute_variable() {
+        let tree = build_operator_tree("a * b + a").unwrap();
+        let tree = tree.substitute_variable("a", Value::Int(2));
+        assert_eq!(tree.to_string(), "((2 * b) + 2)");
+        assert_eq!(tree.list_variables(), vec!["b".to_string()]);
+        assert_eq!(
+            tree.eval(&EmptyConfiguration),
+            Err(Error::VariableIdentifierNotFound {
+                identifier: "b".to_string(),
+                span: Some(4..5)
+            })
+        );
+
+        let tree = tree.substitute_variable("b", Value::Int(3));
+        assert_eq!(tree.eval(&EmptyConfiguration), Ok(Value::Int(8)));
+        assert_eq!(
+            tree.substitute_variable("c", Value::Int(4)).to_string(),
+            "((2 * 3) + 2)"
+        );
+
+        let tree = build_operator_tree("max(a, x ? 'a' : a)").unwrap();
+        assert_eq!(
+            tree.substitute_variable("a", Value::String("s".to_string()))
+                .to_string(),
+            "max(\"s\", (x ? \"a\" : \"s\"))"
+        );
+
+        // Assignment targets are not variable reads
+        let tree = build_operator_tree("a = a + 1; a").unwrap();
+        assert_eq!(
+            tree.substitute_variable("a", Value::Int(1)).to_string(),
+            "((a = (1 + 1)) ; 1)"
+        );
+    }
+
+    #[test]
+    fn test_boxed_configuration() {
+        struct Holder {
+            configuration: Box<dyn Configuration>,
+        }
+
+        let holder = Holder {
+            configuration: Box::new(HashMapConfiguration::builder().var("a", 1).build()),
+        };
+        assert_eq!(
+            eval_with_configuration("a + 1", &*holder.configuration),
+            Ok(Value::Int(2))
+        );
+        assert_eq!(
+            eval_with_configuration("a + 1", &holder.configuration),
+            Ok(Value::Int(2))
+        );
+        assert_eq!(
+            compile("a").unwrap().eval(&*holder.configuration),
+            Ok(Value::Int(1))
+        );
+
+        let chained = holder
+            .configuration
+            .chain(HashMapConfiguration::builder().var("b", 2).build());
+        assert_eq!(
+            eval_with_configuration("a + b", &chained),
+            Ok(Value::Int(3))
+        );
+        let boxed: Box<dyn Configuration> = Box::new(chained);
+        assert_eq!(boxed.variable_names(), vec!["a", "b"]);
+
+        let mut mutable: Box<dyn MutableConfiguration> = Box::new(HashMapConfiguration::new());
+        assert_eq!(
+            eval_with_mutable_configuration("c = 3", &mut mutable),
+            Ok(Value::Int(3))
+        );
+        assert_eq!(mutable.get_value("c"), Some(&Value::Int(3)));
+    }
+
+    #[test]
+    fn test_configuration_names() {
+        assert!(EmptyConfiguration.variable_names().is_empty());
+        assert!(EmptyConfiguration.function_names().is_empty());
+
+        let configuration = HashMapConfiguration::builder()
+            .var("b", 2)
+            .var("a", 1)
+            .func("f", 1, |arguments| Ok(arguments[0].clone()))
+            .build();
+        assert_eq!(configuration.variable_names(), vec!["a", "b"]);
+        assert_eq!(configuration.function_names(), vec!["f"]);
+
+        let fallback = HashMapConfiguration::builder()
+            .var("c", 3)
+            .var("a", 4)
+            .func("g", 0, |_| Ok(Value::Null))
+            .build();
+        let chained = (&configuration).chain(&fallback);
+        assert_eq!(chained.variable_names(), vec!["a", "b", "c"]);
+        assert_eq!(chained.function_names(), vec!["f", "g"]);
+    }
+
+    #[test]
+    fn test_value_ordering() {
+        assert!(Value::Int(2) < Value::Int(3));
+        assert!(Value::Float(-1.5) < Value::Float(0.5));
+        assert!(Value::Boolean(false) < Value::Boolean(true));
+        assert!(Value::String("ab".to_string()) < Value::String("b".to_string()));
+        assert!(Value::from(vec![Value::Int(1), Value::Int(2)]) < Value::from(vec![Value::Int(2)]));
+        assert!(Value::from(vec![Value::Int(1)]) < Value::from(vec![Value::Int(1), Value::Int(0)]));
+
+        // Values of different types are ordered by type
+        assert!(Value::Int(5) < Value::Float(1.0));
+        assert!(Value::Float(5.0) < Value::Boolean(false));
+        assert!(Value::Boolean(true) < Value::String(String::new()));
+        assert!(Value::String("z".to_string()) < Value::Array(vec![]));
+        assert!(Value::Array(vec![Value::Int(1)]) < Value::Tuple(vec![]));
+        assert!(Value::Tuple(vec![]) < Value::Object(ObjectMap::default()));
+        assert!(Value::Object(ObjectMap::default()) < Value::Null);
+
+        // Objects are compared regardless of their key order, like their equality
+        let mut a = ObjectMap::default();
+        a.insert("x".to_string(), Value::Int(1));
+        a.insert("y".to_string(), Value::Int(2));
+        let mut b = ObjectMap::default();
+        b.insert("y".to_string(), Value::Int(2));
+        b.insert("x".to_string(), Value::Int(1));
+        assert_eq!(Value::Object(a.clone()), Value::Object(b.clone()));
+        assert_eq!(
+            Value::Object(a.clone()).total_cmp(&Value::Object(b)),
+            Ordering::Equal
+        );
+        a.insert("x".to_string(), Value::Int(3));
+        let mut c = ObjectMap::default();
+        c.insert("x".to_string(), Value::Int(2));
+        assert!(Value::Object(c) < Value::Object(a));
+
+        // NaN is greater than all other floats, and only equal to itself by the total order
+        let nan = Value::Float(FloatType::NAN);
+        assert!(Value::Float(FloatType::INFINITY) < nan);
+        assert_eq!(
+            nan.total_cmp(&Value::Float(FloatType::NAN)),
+            Ordering::Equal
+        );
+        assert_eq!(nan.partial_cmp(&Value::Float(FloatType::NAN)), None);
+        assert_ne!(nan, Value::Float(FloatType::NAN));
+        let array = Value::Array(vec![Value::Int(1), nan.clone()]);
+        assert_eq!(array.partial_cmp(&array.clone()), None);
+        assert_eq!(array.total_cmp(&array.clone()), Ordering::Equal);
+        assert!(array < Value::Array(vec![Value::Int(2), nan.clone()]));
+
+        let mut values = vec![
+            Value::Null,
+            Value::String("b".to_string()),
+            nan.clone(),
+            Value::Int(3),
+            Value::Float(1.5),
+            Value::String("a".to_string()),
+            Value::Int(-1),
+            Value::Boolean(true),
+        ];
+        values.sort_by(Value::total_cmp);
+        assert!(values.remove(3).as_float().unwrap().is_nan());
+        assert_eq!(
+            values,
+            vec![
+                Value::Int(-1),
+                Value::Int(3),
+                Value::Float(1.5),
+                Value::Boolean(true),
+                Value::String("a".to_string()),
+                Value::String("b".to_string()),
+                Value::Null,
+            ]
+        );
+
+        let mut map = BTreeMap::new();
+        map.insert(ValueKey(Value::Int(2)), "two");
+        map.insert(ValueKey(Value::String("one".to_string())), "one");
+        map.insert(ValueKey(Value::Int(1)), "one");
+        map.insert(ValueKey(nan.clone()), "nan");
+        assert_eq!(
+            map.values().cloned().collect::<Vec<_>>(),
+            vec!["one", "two", "nan", "one"]
+        );
+        assert_eq!(map.get(&ValueKey(nan.clone())), Some(&"nan"));
+    }
+
+    #[test]
+    fn test_value_hash() {
+        let mut counts = HashMap::new();
+        for value in eval("(1, 2.0, 'a', 1, 'a', true, (1, 2), (1, 2), -0.0, 0.0, null)")
+            .unwrap()
+            .as_tuple()
+            .unwrap()
+        {
+            *counts.entry(ValueKey(value.clone())).or_insert(0) += 1;
+        }
+        let count = |value: Value| counts.get(&ValueKey(value)).cloned();
+        assert_eq!(counts.len(), 7);
+        assert_eq!(count(Value::Int(1)), Some(2));
+        assert_eq!(count(Value::String("a".to_string())), Some(2));
+        assert_eq!(count(Value::Float(2.0)), Some(1));
+        assert_eq!(
+            count(Value::Tuple(vec![Value::Int(1), Value::Int(2)])),
+            Some(2)
+        );
+        assert_eq!(count(Value::Float(0.0)), Some(2));
+        assert_eq!(count(Value::Null), Some(1));
+        // Integers and floats are different values
+        assert_eq!(count(Value::Float(1.0)), None);
+        // Arrays and tuples are different values
+        assert_eq!(
+            count(Value::Array(vec![Value::Int(1), Value::Int(2)])),
+            None
+        );
+
+        // Objects are equal and hashed alike regardless of their key order
+        let mut set = HashSet::new();
+        let mut a = ObjectMap::default();
+        a.insert("x".to_string(), Value::Int(1));
+        a.insert("y".to_string(), Value::Int(2));
+        let mut b = ObjectMap::default();
+        b.insert("y".to_string(), Value::Int(2));
+        b.insert("x".to_string(), Value::Int(1));
+        set.insert(ValueKey(Value::Object(a)));
+        assert!(set.contains(&ValueKey(Value::Object(b))));
+
+        // Keys are compared by `deep_equal`, so NaN is found again
+        set.insert(ValueKey(Value::Float(FloatType::NAN)));
+        assert!(set.contains(&ValueKey(Value::Float(FloatType::NAN))));
+        set.insert(ValueKey(Value::Float(FloatType::NAN)));
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_value_deep_equal() {
+        let nan = Value::Float(FloatType::NAN);
+        assert_ne!(nan, nan);
+        assert!(nan.deep_equal(&nan));
+        assert!(Value::Float(0.0).deep_equal(&Value::Float(-0.0)));
+        assert!(!nan.deep_equal(&Value::Float(1.0)));
+        assert!(!Value::Int(1).deep_equal(&Value::Float(1.0)));
+
+        let array = Value::Array(vec![Value::Int(1), nan.clone()]);
+        assert!(array.deep_equal(&array.clone()));
+        assert!(!array.deep_equal(&Value::Array(vec![Value::Int(1)])));
+        assert!(!array.deep_equal(&Value::Tuple(vec![Value::Int(1), nan.clone()])));
+
+        let mut a = ObjectMap::default();
+        a.insert("x".to_string(), nan.clone());
+        a.insert("y".to_string(), Value::from("y"));
+        let mut b = ObjectMap::default();
+        b.insert("y".to_string(), Value::from("y"));
+        b.insert("x".to_string(), nan.clone());
+        assert!(Value::Object(a.clone()).deep_equal(&Value::Object(b.clone())));
+        b.insert("z".to_string(), Value::Null);
+        assert!(!Value::Object(a).deep_equal(&Value::Object(b)));
+
+        // The builtin functions use `deep_equal`, while the operators use `PartialEq`
+        let configuration = HashMapConfiguration::builder()
+            .var(
+                "a",
+                vec![nan.clone(), Value::Int(1), nan.clone(), Value::Int(1)],
+            )
+            .var("nan", nan.clone())
+            .build();
+        assert!(eval_with_configuration("unique(a)", &configuration)
+            .unwrap()
+            .deep_equal(&Value::Array(vec![nan.clone(), Value::Int(1)])));
+        assert_eq!(
+            eval_with_configuration("nan == nan", &configuration),
+            Ok(Value::Boolean(false))
+        );
+    }
+
+    #[test]
+    fn test_function_names() {
+        let failing = Function::new(
+            1,
+            Box::new(|arguments| Err(Error::expected_int(arguments[0].clone()))),
+        );
+        assert_eq!(failing.name(), None);
+        assert_eq!(
+            failing.call(&[Value::Null]),
+            Err(Error::expected_int(Value::Null))
+        );
+
+        let named = Function::with_name("check", failing.clone());
+        assert_eq!(named.name(), Some("check"));
+        assert_eq!(
+            named.call(&[Value::Null]),
+            Err(Error::function_error(
+                "check".to_string(),
+                Error::expected_int(Value::Null)
+            ))
+        );
+        // The argument amount is checked before the function is called
+        assert_eq!(named.call(&[]), Err(Error::wrong_argument_amount(0, 1)));
+
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_function("f".to_string(), failing);
+        configuration.insert_function("g".to_string(), named);
+        assert_eq!(configuration.get_function("f").unwrap().name(), Some("f"));
+        assert_eq!(
+            configuration.get_function("g").unwrap().name(),
+            Some("check")
+        );
+        let error = eval_with_configuration("1 + f(true)", &configuration).unwrap_err();
+        assert_eq!(
+            error,
+            Error::function_error("f".to_string(), Error::expected_int(Value::Boolean(true)))
+        );
+        assert_eq!(
+            error.to_string(),
+            "Error in function f: Expected an integer, but got true"
+        );
+
+        // Builtin functions are not named
+        assert_eq!(
+            eval("sqrt(true)"),
+            Err(Error::expected_number(Value::Boolean(true)))
+        );
+    }
+
+    #[test]
+    fn test_list_identifiers() {
+        let tree = build_operator_tree("a + f(b * a) - g(c ? f(d) : e ?? a)").unwrap();
+        assert_eq!(tree.list_variables(), vec!["a", "b", "c", "d", "e"]);
+        assert_eq!(tree.list_functions(), vec!["f", "g"]);
+
+        let tree = build_operator_tree("1 + 2").unwrap();
+        assert!(tree.list_variables().is_empty());
+        assert!(tree.list_functions().is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("five".to_string(), Value::Int(5));
+        configuration.insert_function(
+            "fail".to_string(),
+            Function::new(1, Box::new(|_| panic!("validate must not call functions"))),
+        );
+        configuration.insert_function(
+            "binary".to_string(),
+            Function::new(2, Box::new(|_| panic!("validate must not call functions"))),
+        );
+
+        let validate = |string| {
+            build_operator_tree(string)
+                .unwrap()
+                .validate(&configuration)
+        };
+        assert_eq!(validate("five + fail(five)"), Ok(()));
+        assert_eq!(validate("false && fail(five) == 1"), Ok(()));
+        assert_eq!(
+            validate("true || six"),
+            Err(Error::variable_identifier_not_found(
+                "six".to_string(),
+                Some(8..11)
+            ))
+        );
+        assert_eq!(
+            validate("five ?? nope(1)"),
+            Err(Error::function_identifier_not_found(
+                "nope".to_string(),
+                Some(8..12)
+            ))
+        );
+        assert_eq!(
+            validate("binary 1"),
+            Err(Error::wrong_argument_amount(1, 2))
+        );
+        assert_eq!(
+            validate("true ? a : b"),
+            Err(Error::MultipleErrors(vec![
+                Error::variable_identifier_not_found("a".to_string(), Some(7..8)),
+                Error::variable_identifier_not_found("b".to_string(), Some(11..12)),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_validate_all() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("five".to_string(), 5);
+        let validate_all = |string| {
+            build_operator_tree(string)
+                .unwrap()
+                .validate_all(&configuration)
+        };
+
+        let errors = validate_all("a + nope(five) * (2 + true)");
+        assert_eq!(
+            errors,
+            vec![
+                Error::variable_identifier_not_found("a".to_string(), Some(0..1)),
+                Error::function_identifier_not_found("nope".to_string(), Some(4..8)),
+                Error::expected_number(Value::Boolean(true)),
+            ]
+        );
+        assert_eq!(
+            Error::MultipleErrors(errors).to_string(),
+            "Variable not found: a at 0..1\nFunction not found: nope at 4..8\nExpected a number, but got true"
+        );
+
+        // Errors are reported in pre-order, and type errors do not cascade to the enclosing expressions
+        assert_eq!(
+            validate_all("!(1 + 'a') || (false ? 3 : abs(1, 2))"),
+            vec![
+                Error::expected_number(Value::String("a".to_string())),
+                Error::wrong_argument_amount(2, 1),
+            ]
+        );
+        assert_eq!(validate_all("(1 + 2) * 3 - five"), vec![]);
+    }
+
+    #[test]
+    fn test_configuration_builder() {
+        let configuration = HashMapConfiguration::builder()
+            .var("a", 2)
+            .var("name", "evalexpr")
+            .func("double", 1, |arguments| {
+                Ok(Value::Int(arguments[0].clone().into_int()? * 2))
+            })
+            .build();
+        assert_eq!(
+            eval_with_configuration("double(a) + len(name)", &configuration),
+            Ok(Value::Int(12))
+        );
+
+        let configuration = HashMapConfiguration::default();
+        assert_eq!(configuration.get_value("a"), None);
+    }
+
+    #[test]
+    fn test_typed_eval() {
+        assert_eq!(eval_as_bool("1 < 2"), Ok(true));
+        assert_eq!(eval_as_int("1 + 2"), Ok(3));
+        assert_eq!(eval_as_float("1.5 * 2"), Ok(3.0));
+        assert_eq!(
+            eval_as_bool("1"),
+            Err(Error::expected_boolean(Value::Int(1)))
+        );
+        assert_eq!(
+            eval_as_int("true"),
+            Err(Error::expected_int(Value::Boolean(true)))
+        );
+        assert_eq!(
+            eval_as_float("1"),
+            Err(Error::expected_float(Value::Int(1)))
+        );
+        assert_eq!(
+            eval_as_string("null"),
+            Err(Error::expected_string(Value::Null))
+        );
+        assert_eq!(
+            eval_as_int("a"),
+            Err(Error::variable_identifier_not_found(
+                "a".to_string(),
+                Some(0..1)
+            ))
+        );
+
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("five".to_string(), Value::Int(5));
+        configuration.insert_variable("half".to_string(), Value::Float(0.5));
+        configuration.insert_variable("name".to_string(), Value::String("foo".to_string()));
+        assert_eq!(
+            eval_with_configuration_as_bool("five > 3", &configuration),
+            Ok(true)
+        );
+        assert_eq!(
+            eval_with_configuration_as_int("five * 2", &configuration),
+            Ok(10)
+        );
+        assert_eq!(
+            eval_with_configuration_as_float("half * five", &configuration),
+            Ok(2.5)
+        );
+        assert_eq!(
+            eval_with_configuration_as_string("name", &configuration),
+            Ok("foo".to_string())
+        );
+        assert_eq!(
+            eval_with_configuration_as_string("five", &configuration),
+            Err(Error::expected_string(Value::Int(5)))
+        );
+
+        assert_eq!(
+            Value::String("foo".to_string()).into_string(),
+            Ok("foo".to_string())
+        );
+        assert_eq!(Value::Boolean(false).into_bool(), Ok(false));
+        assert_eq!(Value::Int(4).into_int(), Ok(4));
+        assert_eq!(Value::Float(0.5).into_float(), Ok(0.5));
+    }
+
+    #[test]
+    fn test_value_getters() {
+        let array = Value::Array(vec![Value::Int(1), Value::Boolean(true)]);
+        assert_eq!(
+            array.as_array(),
+            Some(&vec![Value::Int(1), Value::Boolean(true)])
+        );
+        assert_eq!(array.as_int(), None);
+
+        assert_eq!(Value::Int(3).as_int(), Some(3));
+        assert_eq!(Value::Int(3).as_float(), Some(3.0));
+        assert_eq!(Value::Float(0.5).as_float(), Some(0.5));
+        assert_eq!(Value::Float(0.5).as_int(), None);
+        assert_eq!(Value::Boolean(true).as_bool(), Some(true));
+        assert_eq!(Value::Null.as_bool(), None);
+        assert_eq!(Value::String("foo".to_string()).as_str(), Some("foo"));
+        assert_eq!(Value::Int(1).as_str(), None);
+        assert_eq!(Value::Null.as_array(), None);
+        assert_eq!(array.into_array().map(|array| array.len()), Ok(2));
+        assert_eq!(
+            Value::Null.into_array(),
+            Err(Error::expected_array(Value::Null))
+        );
+
+        let mut object = ObjectMap::default();
+        object.insert("b".to_string(), Value::Int(2));
+        object.insert("a".to_string(), Value::Int(1));
+        let object = Value::Object(object);
+        assert!(object.is_object());
+        assert_eq!(
+            object.as_object().unwrap().keys().collect::<Vec<_>>(),
+            vec!["b", "a"]
+        );
+        assert_eq!(Value::Int(1).as_object(), None);
+        assert_eq!(object.into_object().map(|object| object.len()), Ok(2));
+        assert_eq!(
+            Value::Null.into_object(),
+            Err(Error::expected_object(Value::Null))
+        );
+    }
+
+    #[test]
+    fn test_chained_configuration() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let mut defaults = HashMapConfiguration::new();
+        defaults.insert_variable("a".to_string(), Value::Int(1));
+        defaults.insert_variable("b".to_string(), Value::Int(2));
+        defaults.insert_function(
+            "f".to_string(),
+            Function::new(1, Box::new(|_| Ok(Value::Int(10)))),
+        );
+        defaults.insert_function(
+            "g".to_string(),
+            Function::new(1, Box::new(|_| Ok(Value::Int(20)))),
+        );
+
+        let mut overrides = HashMapConfiguration::new();
+        overrides.insert_variable("a".to_string(), Value::Int(3));
+        overrides.insert_function(
+            "f".to_string(),
+            Function::new(1, Box::new(|_| Ok(Value::Int(30)))),
+        );
+
+        let configuration = (&overrides).chain(&defaults);
+        assert_send_sync(&configuration);
+        assert_eq!(
+            eval_with_configuration("a + b", &configuration),
+            Ok(Value::Int(5))
+        );
+        assert_eq!(
+            eval_with_configuration("f(0) + g(0)", &configuration),
+            Ok(Value::Int(50))
+        );
+        assert_eq!(
+            eval_with_configuration("c", &configuration),
+            Err(Error::variable_identifier_not_found(
+                "c".to_string(),
+                Some(0..1)
+            ))
+        );
+
+        let configuration = ChainedConfiguration::new(defaults, overrides);
+        assert_eq!(
+            eval_with_configuration("a + b", &configuration),
+            Ok(Value::Int(3))
+        );
+        assert_eq!(
+            configuration.get_value_or_default("a", Value::Null),
+            Value::Int(1)
+        );
+        assert_eq!(
+            configuration.get_value_or_default("c", Value::Int(4)),
+            Value::Int(4)
+        );
+    }
+
+    #[test]
+    fn test_extend_and_merge_configuration() {
+        let mut first = HashMapConfiguration::new();
+        first.insert_variable("a".to_string(), Value::Int(1));
+        first.insert_variable("b".to_string(), Value::Int(2));
+        first.insert_function(
+            "f".to_string(),
+            Function::new(1, Box::new(|_| Ok(Value::Int(10)))),
+        );
+
+        let mut second = HashMapConfiguration::new();
+        second.insert_variable("b".to_string(), Value::Int(3));
+        second.insert_variable("c".to_string(), Value::Int(4));
+        second.insert_function(
+            "g".to_string(),
+            Function::new(1, Box::new(|_| Ok(Value::Int(20)))),
+        );
+
+        let mut extended = first.clone();
+        extended.extend(&second);
+        assert_eq!(
+            eval_with_configuration("a + b + c + f(0) + g(0)", &extended),
+            Ok(Value::Int(38))
+        );
+        assert_eq!(second.get_value("b"), Some(&Value::Int(3)));
+
+        let merged = second.merge(first);
+        assert_eq!(
+            eval_with_configuration("a + b + c + f(0) + g(0)", &merged),
+            Ok(Value::Int(37))
+        );
+    }
+
+    #[test]
+    fn test_value_display() {
+        let mut object = ObjectMap::default();
+        object.insert("b".to_string(), Value::Int(1));
+        object.insert("a".to_string(), Value::String("x".to_string()));
+
+        let examples = vec![
+            (Value::Int(3), "3"),
+            (Value::Int(-3), "-3"),
+            (Value::Float(3.5), "3.5"),
+            (Value::Float(2.0), "2.0"),
+            (Value::Boolean(true), "true"),
+            (Value::String("hello".to_string()), "\"hello\""),
+            (Value::Null, "null"),
+            (Value::Array(vec![]), "[]"),
+            (
+                Value::Array(vec![Value::Int(1), Value::Int(2), Value::Int(3)]),
+                "[1, 2, 3]",
+            ),
+            (
+                Value::Array(vec![
+                    Value::Array(vec![Value::Boolean(false)]),
+                    Value::String("a".to_string()),
+                    Value::Null,
+                ]),
+                "[[false], \"a\", null]",
+            ),
+            (Value::Object(object), "{\"b\": 1, \"a\": \"x\"}"),
+        ];
+
+        for (value, expected) in examples {
+            assert_eq!(value.to_string(), expected);
+        }
+        assert_eq!(format!("{:?}", Value::Int(3)), "Int(3)");
+    }
+
+    #[test]
+    fn test_value_conversions() {
+        assert_eq!(Value::from(3i64), Value::Int(3));
+        assert_eq!(Value::from(3i32), Value::Int(3));
+        assert_eq!(Value::from(1.5f64), Value::Float(1.5));
+        assert_eq!(Value::from(1.5f32), Value::Float(1.5));
+        assert_eq!(Value::from(true), Value::Boolean(true));
+        assert_eq!(Value::from("a"), Value::String("a".to_string()));
+        assert_eq!(Value::from("a".to_string()), Value::String("a".to_string()));
+        assert_eq!(
+            Value::from(vec![Value::from(1), Value::from("b")]),
+            Value::Array(vec![Value::Int(1), Value::String("b".to_string())])
+        );
+
+        assert_eq!(IntType::try_from(Value::Int(3)), Ok(3));
+        assert_eq!(f64::try_from(Value::Float(1.5)), Ok(1.5));
+        assert_eq!(bool::try_from(Value::Boolean(false)), Ok(false));
+        assert_eq!(String::try_from(Value::from("a")), Ok("a".to_string()));
+        let int: Result<IntType, Error> = Value::Float(1.5).try_into();
+        assert_eq!(int, Err(Error::expected_int(Value::Float(1.5))));
+        assert_eq!(
+            bool::try_from(Value::Null),
+            Err(Error::expected_boolean(Value::Null))
+        );
+
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("a".to_string(), 2);
+        configuration.insert_variable("b".to_string(), 0.5);
+        configuration.insert_variable("c".to_string(), "c");
+        assert_eq!(
+            eval_with_configuration("a * b + len(c)", &configuration),
+            Ok(Value::Float(2.0))
+        );
+    }
+
+    #[test]
+    fn test_value_operators() {
+        assert_eq!(Value::Int(2) + Value::Int(3), Value::Int(5));
+        assert_eq!(Value::Int(2) - Value::Float(0.5), Value::Float(1.5));
+        assert_eq!(Value::from("a") * Value::Int(3), Value::from("aaa"));
+        assert_eq!(Value::Int(7) / Value::Int(2), Value::Int(3));
+        assert_eq!(Value::Int(7) % Value::Int(2), Value::Int(1));
+        assert_eq!(-Value::Int(2), Value::Int(-2));
+        assert_eq!(!Value::Boolean(true), Value::Boolean(false));
+        assert_eq!(Value::from("a") + Value::from("b"), Value::from("ab"));
+
+        assert_eq!(Value::Int(2).checked_add(Value::Int(3)), eval("2 + 3"));
+        assert_eq!(
+            Value::Int(1).checked_div(Value::Int(0)),
+            Err(Error::DivisionByZero)
+        );
+        assert_eq!(
+            Value::Boolean(true).checked_sub(Value::Int(1)),
+            eval("true - 1")
+        );
+        assert_eq!(Value::Int(1).checked_not(), eval("!1"));
+        assert_eq!(Value::from("a").checked_neg(), eval("-\"a\""));
+    }
+
+    #[test]
+    #[should_panic(expected = "Division by zero")]
+    fn test_value_operator_panics() {
+        let _ = Value::Int(1) / Value::Int(0);
+    }
+
+    #[test]
+    fn test_shared_configuration() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_function(
+            "double".to_string(),
+            Function::new(
+                1,
+                Box::new(|arguments| Ok(Value::Int(arguments[0].clone().into_int()? * 2))),
+            ),
+        );
+        let triple =
+            Arc::new(|arguments: &[Value]| Ok(Value::Int(arguments[0].clone().into_int()? * 3)));
+        configuration.insert_function("triple".to_string(), Function::new_arc(1, triple.clone()));
+        configuration.insert_function("thrice".to_string(), Function::new_arc(1, triple));
+        assert_send_sync(&configuration);
+
+        let mut clone = configuration.clone();
+        clone.insert_variable("five".to_string(), Value::Int(5));
+        assert_eq!(
+            eval_with_configuration("double(five) + triple(five) + thrice(1)", &clone),
+            Ok(Value::Int(28))
+        );
+        assert!(configuration.get_value("five").is_none());
+        assert_eq!(
+            eval_with_configuration("double 4", &configuration),
+            Ok(Value::Int(8))
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let examples = [
+            ("1+2*3", "(1 + (2 * 3))"),
+            ("(1 + 2) * 3", "((1 + 2) * 3)"),
+            ("((a))", "a"),
+            ("-2 ** 2", "(-(2 ** 2))"),
+            ("(-2) ** 2", "((-2) ** 2)"),
+            ("2 ** 3 ** 2", "(2 ** (3 ** 2))"),
+            ("!a && b || c", "(((!a) && b) || c)"),
+            ("~1 & 2 | 3 ^ 4 << 5", "(((~1) & 2) | (3 ^ (4 << 5)))"),
+            ("a ?? b ? 1.0 : 2.5e20", "((a ?? b) ? 1.0 : 2.5e20)"),
+            ("c ? d ? 1 : 2 : 3", "(c ? (d ? 1 : 2) : 3)"),
+            ("f x + g(y * 2) - h(-z)", "((f(x) + g(y * 2)) - h(-z))"),
+            ("null == true", "(null == true)"),
+            ("'a' in \"abc\"", "(\"a\" in \"abc\")"),
+            ("f(a, b + 1, (c))", "f(a, (b + 1), c)"),
+            ("f((a, b), c)", "f((a, b), c)"),
+        ];
+
+        for (string, expected) in examples.iter() {
+            let display = build_operator_tree(string).unwrap().to_string();
+            assert_eq!(&display, expected);
+            assert_eq!(build_operator_tree(&display).unwrap().to_string(), display);
+        }
+    }
+
+    #[test]
+    fn test_eval_with_callback() {
+        let tree = build_operator_tree("2 * 3 + 4").unwrap();
+        let mut values = Vec::new();
+        let mut nodes = Vec::new();
+        assert_eq!(
+            tree.eval_with_callback(&EmptyConfiguration, |node, value| {
+                values.push(value.clone());
+                nodes.push(node.to_string());
+            }),
+            Ok(Value::Int(10))
+        );
+        assert_eq!(
+            values,
+            vec![
+                Value::Int(2),
+                Value::Int(3),
+                Value::Int(6),
+                Value::Int(4),
+                Value::Int(10)
+            ]
+        );
+        assert_eq!(nodes, vec!["2", "3", "(2 * 3)", "4", "((2 * 3) + 4)"]);
+
+        let tree = build_operator_tree("true ? 1 : 2 + 3").unwrap();
+        let mut values = Vec::new();
+        assert_eq!(
+            tree.eval_with_callback(&EmptyConfiguration, |_, value| values.push(value.clone())),
+            Ok(Value::Int(1))
+        );
+        // The then-branch is grouped in a root node, which is evaluated as well
+        assert_eq!(
+            values,
+            vec![
+                Value::Boolean(true),
+                Value::Int(1),
+                Value::Int(1),
+                Value::Int(1)
+            ]
+        );
+
+        let tree = build_operator_tree("1 + a").unwrap();
+        let mut count = 0;
+        assert!(tree
+            .eval_with_callback(&EmptyConfiguration, |_, _| count += 1)
+            .is_err());
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        // The tree of `1` consists of the constant only, and each pair of braces adds a root node above it
+        let mut configuration = HashMapConfiguration::new();
+        configuration.set_max_depth(10);
+        assert_eq!(
+            eval_with_configuration(&nested(9), &configuration),
+            Ok(Value::Int(1))
+        );
+        assert_eq!(
+            eval_with_configuration(&nested(10), &configuration),
+            Err(Error::MaxDepthExceeded { max_depth: 10 })
+        );
+        assert_eq!(
+            eval_with_configuration(&nested(10), &(&configuration).chain(EmptyConfiguration)),
+            Err(Error::MaxDepthExceeded { max_depth: 10 })
+        );
+        assert_eq!(eval(&nested(511)), Ok(Value::Int(1)));
+        assert_eq!(
+            eval(&nested(512)),
+            Err(Error::MaxDepthExceeded { max_depth: 512 })
+        );
+    }
+
+    #[test]
+    fn test_max_string_and_array_length() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("long".to_string(), "a".repeat(20));
+        configuration.set_max_string_length(10);
+        configuration.set_max_array_length(5);
+        let eval = |string| eval_with_configuration(string, &configuration);
+
+        assert_eq!(eval("'ab' * 5"), Ok(Value::String("ab".repeat(5))));
+        assert_eq!(
+            eval("'ab' * 6"),
+            Err(Error::StringTooLong {
+                limit: 10,
+                actual: 12
+            })
+        );
+        assert_eq!(
+            eval("'ab' * 9223372036854775807"),
+            Err(Error::StringTooLong {
+                limit: 10,
+                actual: usize::MAX - 1
+            })
+        );
+        assert_eq!(
+            super::eval("'ab' * 9223372036854775807"),
+            Err(Error::StringTooLong {
+                limit: 1 << 30,
+                actual: usize::MAX - 1
+            })
+        );
+        assert_eq!(
+            super::eval("'abcd' * 9223372036854775807"),
+            Err(Error::StringTooLong {
+                limit: 1 << 30,
+                actual: usize::MAX
+            })
+        );
+        assert_eq!(
+            build_operator_tree("'ab' * 9223372036854775807 + 1")
+                .unwrap()
+                .validate(&HashMapConfiguration::new()),
+            Err(Error::StringTooLong {
+                limit: 1 << 30,
+                actual: usize::MAX - 1
+            })
+        );
+        assert_eq!(
+            eval("'abcdef' + 'abcdef'"),
+            Err(Error::StringTooLong {
+                limit: 10,
+                actual: 12
+            })
+        );
+        assert_eq!(
+            eval("to_upper('abcdef' + 'abcde')"),
+            Err(Error::StringTooLong {
+                limit: 10,
+                actual: 11
+            })
+        );
+        // Reading a long string is allowed, but creating one is not
+        assert_eq!(eval("len(long)"), Ok(Value::Int(20)));
+        assert_eq!(
+            eval("trim(long)"),
+            Err(Error::StringTooLong {
+                limit: 10,
+                actual: 20
+            })
+        );
+
+        assert_eq!(eval("len(0..5)"), Ok(Value::Int(5)));
+        assert_eq!(
+            super::eval("0..1000000000"),
+            Err(Error::ArrayTooLong {
+                limit: 1 << 24,
+                actual: 1000000000
+            })
+        );
+        assert_eq!(
+            super::eval("0..9223372036854775807"),
+            Err(Error::ArrayTooLong {
+                limit: 1 << 24,
+                actual: IntType::MAX as usize
+            })
+        );
+        assert_eq!(super::eval("len(0..16777216)"), Ok(Value::Int(1 << 24)));
+        assert_eq!(eval("len(5..0)"), Ok(Value::Int(0)));
+        assert_eq!(
+            eval("0..6"),
+            Err(Error::ArrayTooLong {
+                limit: 5,
+                actual: 6
+            })
+        );
+        assert_eq!(
+            eval("-9223372036854775807..9223372036854775807"),
+            Err(Error::ArrayTooLong {
+                limit: 5,
+                actual: IntType::MAX as usize
+            })
+        );
+        assert_eq!(
+            eval("split('a,b,c,d,e,f', ',')"),
+            Err(Error::ArrayTooLong {
+                limit: 5,
+                actual: 6
+            })
+        );
+        assert_eq!(
+            Error::ArrayTooLong {
+                limit: 5,
+                actual: 6
+            }
+            .to_string(),
+            "An array of length 6 exceeds the maximum length of 5"
+        );
+        assert_eq!(
+            eval_with_configuration("len(0..6)", &EmptyConfiguration),
+            Ok(Value::Int(6))
+        );
+    }
+
+    #[test]
+    fn test_node_count_and_depth() {
+        let tree = build_operator_tree("1").unwrap();
+        assert_eq!(tree.node_count(), 1);
+        assert_eq!(tree.depth(), 1);
+
+        let tree = build_operator_tree("2 * (3 + 4)").unwrap();
+        assert_eq!(tree.node_count(), 6);
+        assert_eq!(tree.depth(), 4);
+
+        let tree = build_operator_tree("max(a, 2)").unwrap();
+        assert_eq!(tree.node_count(), 5);
+        assert_eq!(tree.depth(), 4);
+
+        let mut configuration = HashMapConfiguration::new();
+        configuration.set_max_node_count(6);
+        assert_eq!(
+            eval_with_configuration("2 * (3 + 4)", &configuration),
+            Ok(Value::Int(14))
+        );
+        assert_eq!(
+            eval_with_configuration("2 * (3 + 4) + 1", &configuration),
+            Err(Error::MaxNodeCountExceeded { max_node_count: 6 })
+        );
+    }
+
+    #[test]
+    fn test_is_const() {
+        let is_const = |string| build_operator_tree(string).unwrap().is_const();
+        assert!(is_const("1 + 2 * 3"));
+        assert!(is_const("sqrt(4) + len('abc')"));
+        assert!(is_const("(1, 2) == (1, 2)"));
+        assert!(is_const("1 / 0"));
+        assert!(!is_const("a + 1"));
+        assert!(!is_const("f(1)"));
+        assert!(!is_const("a = 1"));
+        assert!(!is_const("len(a)"));
+        assert!(!is_const("now()"));
+
+        let eval_if_const = |string| build_operator_tree(string).unwrap().eval_if_const();
+        assert_eq!(eval_if_const("sqrt(4) + 1"), Some(Value::Float(3.0)));
+        assert_eq!(eval_if_const("trim(' a ')"), Some(Value::from("a")));
+        assert_eq!(eval_if_const("a + 1"), None);
+        assert_eq!(eval_if_const("1 / 0"), None);
+    }
+
+    #[test]
+    fn test_fold_constants() {
+        let fold = |string| build_operator_tree(string).unwrap().fold_constants();
+        assert_eq!(fold("2 * 3 + x").to_string(), "(6 + x)");
+        assert_eq!(fold("x * (1 + 2 * 3)").to_string(), "(x * 7)");
+        assert_eq!(fold("'a' + 'b' == x").to_string(), "(\"ab\" == x)");
+        assert_eq!(fold("-(2 + 3)").to_string(), "-5");
+        assert_eq!(fold("x + 1 / 0").to_string(), "(x + (1 / 0))");
+        assert_eq!(fold("f(1 + 1, x * (2 + 2))").to_string(), "f(2, (x * 4))");
+        assert_eq!(fold("f((1 + 1))").to_string(), "f(2)");
+        assert_eq!(fold("sqrt(4) + 1").to_string(), "(sqrt(4) + 1)");
+        assert_eq!(fold("a = 2 * 3; a").to_string(), "((a = 6) ; a)");
+
+        // Configurations can override builtin functions
+        let configuration = HashMapConfiguration::builder()
+            .var("x", 2)
+            .func("sqrt", 1, |_| Ok(Value::Int(0)))
+            .func("f", 2, |arguments| Ok(Value::Tuple(arguments.to_vec())))
+            .build();
+        for expression in &[
+            "2 * 3 + x",
+            "x * (1 + 2 * 3)",
+            "-(2 + 3) * x",
+            "f(1 + 1, x * (2 + 2))",
+            "sqrt(4) + 1",
+            "(1, 2 + 3)",
+        ] {
+            let tree = build_operator_tree(expression).unwrap();
+            assert_eq!(
+                tree.fold_constants().eval(&configuration),
+                tree.eval(&configuration)
+            );
+        }
+        let mut configuration = HashMapConfiguration::new();
+        assert_eq!(
+            fold("a = 2 * 3; a + 1").eval_with_mutable_configuration(&mut configuration),
+            Ok(Value::Int(7))
+        );
+    }
+
+    #[test]
+    fn test_compile() {
+        let expression = compile("a * 2 + 1").unwrap();
+        assert_eq!(expression.to_string(), "((a * 2) + 1)");
+
+        let mut results = Vec::new();
+        for a in 0..3 {
+            let mut configuration = HashMapConfiguration::new();
+            configuration.insert_variable("a".to_string(), a);
+            results.push(expression.eval(&configuration));
+        }
+        assert_eq!(
+            results,
+            vec![Ok(Value::Int(1)), Ok(Value::Int(3)), Ok(Value::Int(5))]
+        );
+        assert_eq!(expression.node().list_variables(), vec!["a"]);
+        assert_eq!(expression.clone().to_string(), expression.to_string());
+
+        assert_eq!(
+            compile("(1").err(),
+            Some(Error::UnmatchedLBrace { span: Some(0..1) })
+        );
+    }
+
+    #[test]
+    fn test_cached_expr() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let mut configuration = HashMapConfiguration::builder()
+            .var("a", 2)
+            .var("b", 3)
+            .var("c", 0)
+            .func("expensive", 1, move |arguments| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(arguments[0].clone())
+            })
+            .build();
+
+        let mut expression = CachedExpr::new(compile("expensive(a) * 10 + c").unwrap());
+        assert_eq!(expression.eval(&configuration), Ok(Value::Int(20)));
+        assert_eq!(expression.eval(&configuration), Ok(Value::Int(20)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Variables that are not read by the expression do not matter
+        configuration.insert_variable("b".to_string(), 4);
+        assert_eq!(expression.eval(&configuration), Ok(Value::Int(20)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        configuration.insert_variable("a".to_string(), 3);
+        assert_eq!(expression.eval(&configuration), Ok(Value::Int(30)));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        configuration.insert_variable("c".to_string(), 1);
+        assert_eq!(expression.eval(&configuration), Ok(Value::Int(31)));
+        assert_eq!(expression.eval(&configuration), Ok(Value::Int(31)));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        expression.invalidate();
+        assert_eq!(expression.eval(&configuration), Ok(Value::Int(31)));
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+
+        // Errors are not cached
+        configuration.insert_variable("a".to_string(), true);
+        assert!(expression.eval(&configuration).is_err());
+        assert!(expression.eval(&configuration).is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 6);
+        assert_eq!(
+            expression.compiled().node().list_variables(),
+            vec!["a", "c"]
+        );
+    }
+
+    #[test]
+    fn test_sequence() {
+        assert_eq!(eval("1; 2; 3"), Ok(Value::Int(3)));
+        assert_eq!(eval("floor(1.5); ceil(3.5)"), Ok(Value::Float(4.0)));
+        assert_eq!(eval("1 + 2; 3 * 4"), Ok(Value::Int(12)));
+        assert_eq!(eval("true ? 1 : 2; 3"), Ok(Value::Int(3)));
+        assert_eq!(eval("(1; 2) + 3"), Ok(Value::Int(5)));
+        assert_eq!(eval("1;"), Ok(Value::Null));
+        assert_eq!(eval("(1; 2;) ?? 3"), Ok(Value::Int(3)));
+        assert_eq!(
+            eval("1; a; 3"),
+            Err(Error::VariableIdentifierNotFound {
+                identifier: "a".to_string(),
+                span: Some(3..4)
+            })
+        );
+        assert_eq!(
+            build_operator_tree("1; 2; 3").unwrap().to_string(),
+            "((1 ; 2) ; 3)"
+        );
+
+        let mut calls = 0;
+        build_operator_tree("1; 2; 3")
+            .unwrap()
+            .eval_with_callback(&EmptyConfiguration, |node, _| {
+                if node.children().is_empty() {
+                    calls += 1;
+                }
+            })
+            .unwrap();
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_assignment() {
+        let mut configuration = HashMapConfiguration::new();
+        assert_eq!(
+            eval_with_mutable_configuration("a = 3; a + 2", &mut configuration),
+            Ok(Value::Int(5))
+        );
+        assert_eq!(configuration.get_value("a"), Some(&Value::Int(3)));
+
+        assert_eq!(
+            eval_with_mutable_configuration("a = a * 2", &mut configuration),
+            Ok(Value::Int(6))
+        );
+        assert_eq!(
+            eval_with_mutable_configuration("b = c = 1 + 1; b + c", &mut configuration),
+            Ok(Value::Int(4))
+        );
+        assert_eq!(
+            eval_with_mutable_configuration("d = a > 1 ? 'big' : 'small'", &mut configuration),
+            Ok(Value::String("big".to_string()))
+        );
+        assert_eq!(
+            eval_with_mutable_configuration("1 = 2", &mut configuration),
+            Err(Error::InvalidAssignmentTarget)
+        );
+        assert_eq!(eval("a = 3"), Err(Error::ImmutableConfiguration));
+        assert_eq!(eval("1 == 1"), Ok(Value::Boolean(true)));
+
+        // Assigned variables are written to the front layer
+        let mut defaults = HashMapConfiguration::new();
+        defaults.insert_variable("a".to_string(), 1);
+        let mut chained = HashMapConfiguration::new().chain(&defaults);
+        assert_eq!(
+            eval_with_mutable_configuration("a = a + 1", &mut chained),
+            Ok(Value::Int(2))
+        );
+        assert_eq!(chained.get_value("a"), Some(&Value::Int(2)));
+        assert_eq!(defaults.get_value("a"), Some(&Value::Int(1)));
+
+        // Assigned variables are neither required by validation nor replaced by partial evaluation
+        let tree = build_operator_tree("x = 2; x + a").unwrap();
+        assert_eq!(tree.validate(&defaults), Ok(()));
+        assert_eq!(
+            tree.partial_eval(&defaults).to_string(),
+            "((x = 2) ; (x + 1))"
+        );
+    }
+
+    #[test]
+    fn test_compound_assignment() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("x".to_string(), 10);
+        let mut results = Vec::new();
+        for expression in &["x += 5", "x -= 3", "x *= 2", "x /= 4", "x %= 4"] {
+            results.push(eval_with_mutable_configuration(
+                expression,
+                &mut configuration,
+            ));
+        }
+        assert_eq!(
+            results,
+            vec![
+                Ok(Value::Int(15)),
+                Ok(Value::Int(12)),
+                Ok(Value::Int(24)),
+                Ok(Value::Int(6)),
+                Ok(Value::Int(2))
+            ]
+        );
+        assert_eq!(configuration.get_value("x"), Some(&Value::Int(2)));
+
+        assert_eq!(
+            eval_with_mutable_configuration(
+                "total = 0; total += 1.5; total += 2; total",
+                &mut configuration
+            ),
+            Ok(Value::Float(3.5))
+        );
+        assert_eq!(
+            eval_with_mutable_configuration("y += 1", &mut configuration),
+            Err(Error::VariableIdentifierNotFound {
+                identifier: "y".to_string(),
+                span: Some(0..1)
+            })
+        );
+        assert_eq!(configuration.get_value("y"), None);
+        assert_eq!(
+            eval_with_mutable_configuration("x += x -= 1", &mut configuration),
+            Ok(Value::Int(3))
+        );
+        assert_eq!(eval("1 += 2"), Err(Error::InvalidAssignmentTarget));
+
+        let tree = build_operator_tree("y += 1").unwrap();
+        assert_eq!(tree.to_string(), "(y += 1)");
+        assert!(tree.validate(&configuration).is_err());
+        assert_eq!(
+            build_operator_tree("y = 0; y += x")
+                .unwrap()
+                .validate(&configuration),
+            Ok(())
+        );
+        assert_eq!(
+            build_operator_tree("x += x")
+                .unwrap()
+                .partial_eval(&configuration)
+                .to_string(),
+            "(x += x)"
+        );
+    }
+
+    #[test]
+    fn test_variadic_function() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_function(
+            "concat".to_string(),
+            Function::variadic(
+                2,
+                Some(4),
+                Box::new(|arguments| {
+                    Ok(Value::String(
+                        arguments
+                            .iter()
+                            .map(|argument| argument.to_string())
+                            .collect(),
+                    ))
+                }),
+            )
+            .unwrap(),
+        );
+        configuration.insert_function(
+            "count".to_string(),
+            Function::variadic(
+                0,
+                None,
+                Box::new(|arguments| Ok(Value::Int(arguments.len() as IntType))),
+            )
+            .unwrap(),
+        );
+
+        assert_eq!(
+            eval_with_configuration("concat(1, 2)", &configuration),
+            Ok(Value::from("12"))
+        );
+        assert_eq!(
+            eval_with_configuration("concat(1, 2, 3, 4)", &configuration),
+            Ok(Value::from("1234"))
+        );
+        assert_eq!(
+            eval_with_configuration("concat(1)", &configuration),
+            Err(Error::wrong_argument_amount(1, 2))
+        );
+        assert_eq!(
+            eval_with_configuration("concat(1, 2, 3, 4, 5)", &configuration),
+            Err(Error::wrong_argument_amount(5, 4))
+        );
+        assert_eq!(
+            eval_with_configuration("concat(...(1..6))", &configuration),
+            Err(Error::wrong_argument_amount(5, 4))
+        );
+        assert_eq!(
+            build_operator_tree("concat(1, 2, 3, 4, 5)")
+                .unwrap()
+                .validate(&configuration),
+            Err(Error::wrong_argument_amount(5, 4))
+        );
+        assert_eq!(
+            eval_with_configuration("count()", &configuration),
+            Ok(Value::Int(0))
+        );
+        assert_eq!(
+            eval_with_configuration("count(1, 2, 3, 4, 5, 6, 7, 8)", &configuration),
+            Ok(Value::Int(8))
+        );
+
+        let function = configuration.get_function("concat").unwrap();
+        assert_eq!(function.argument_amount(), 2);
+        assert_eq!(function.max_argument_amount(), Some(4));
+        assert_eq!(
+            configuration
+                .get_function("count")
+                .unwrap()
+                .max_argument_amount(),
+            None
+        );
+        assert_eq!(
+            Function::new(1, Box::new(|_| Ok(Value::Null))).max_argument_amount(),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_variadic_function_invalid_range() {
+        assert!(matches!(
+            Function::variadic(3, Some(2), Box::new(|_| Ok(Value::Null))),
+            Err(Error::InvalidArgumentAmountRange { min: 3, max: 2 })
+        ));
+        assert!(Function::variadic(2, Some(2), Box::new(|_| Ok(Value::Null))).is_ok());
+    }
+
+    #[test]
+    fn test_recurse() {
+        use std::sync::{Arc, OnceLock};
+
+        // Each function evaluates its expression with the final configuration, which contains all functions
+        let cell: Arc<OnceLock<HashMapConfiguration>> = Arc::new(OnceLock::new());
+        let function = |expression: &str| {
+            let body = build_operator_tree(expression).unwrap();
+            let cell = cell.clone();
+            Function::new(
+                1,
+                Box::new(move |arguments| {
+                    body.substitute_variable("n", arguments[0].clone())
+                        .eval(cell.get().unwrap())
+                }),
+            )
+        };
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_function(
+            "fib".to_string(),
+            function("n < 2 ? n : recurse('fib', n - 1) + recurse('fib', n - 2)"),
+        );
+        configuration.insert_function(
+            "is_even".to_string(),
+            function("n == 0 ? true : recurse('is_odd', n - 1)"),
+        );
+        configuration.insert_function(
+            "is_odd".to_string(),
+            function("n == 0 ? false : recurse('is_even', n - 1)"),
+        );
+        configuration.insert_function("forever".to_string(), function("recurse('forever', n)"));
+        configuration.set_max_depth(64);
+        cell.set(configuration.clone()).ok().unwrap();
+
+        assert_eq!(
+            eval_with_configuration("fib(10)", &configuration),
+            Ok(Value::Int(55))
+        );
+        assert_eq!(
+            eval_with_configuration("recurse('fib', 15)", &configuration),
+            Ok(Value::Int(610))
+        );
+        assert_eq!(
+            eval_with_configuration("is_even(10) && is_odd(7)", &configuration),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval_with_configuration("recurse('double', 2)", &configuration),
+            Err(Error::FunctionIdentifierNotFound {
+                identifier: "double".to_string(),
+                span: None
+            })
+        );
+        assert_eq!(
+            eval_with_configuration("recurse()", &configuration),
+            Err(Error::wrong_argument_amount(0, 1))
+        );
+
+        // The errors of the nested calls are wrapped in function errors of each call
+        let mut error = eval_with_configuration("forever(1)", &configuration).unwrap_err();
+        while let Error::FunctionError { name, error: inner } = error {
+            assert_eq!(name, "forever");
+            error = *inner;
+        }
+        assert_eq!(error, Error::MaxDepthExceeded { max_depth: 64 });
+        assert!(eval_with_configuration("is_even(80)", &configuration).is_err());
+
+        // The depth is reset after errors
+        assert_eq!(
+            eval_with_configuration("is_even(50)", &configuration),
+            Ok(Value::Boolean(true))
+        );
+    }
+
+    #[test]
+    fn test_recurse_limits() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+        use std::sync::{Arc, OnceLock};
+
+        // Without a maximum depth in the configuration, the nesting of `recurse` is still limited
+        let cell: Arc<OnceLock<HashMapConfiguration>> = Arc::new(OnceLock::new());
+        let body = build_operator_tree("recurse('forever', n)").unwrap();
+        let inner = cell.clone();
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_function(
+            "forever".to_string(),
+            Function::new(
+                1,
+                Box::new(move |arguments| {
+                    body.substitute_variable("n", arguments[0].clone())
+                        .eval(inner.get().unwrap())
+                }),
+            ),
+        );
+        cell.set(configuration.clone()).ok().unwrap();
+        let mut error = eval_with_configuration("forever(1)", &configuration).unwrap_err();
+        while let Error::FunctionError { error: inner, .. } = error {
+            error = *inner;
+        }
+        assert_eq!(error, Error::MaxDepthExceeded { max_depth: 64 });
+
+        // The depth is restored if a recursive call panics
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_function(
+            "panic".to_string(),
+            Function::new(0, Box::new(|_| panic!("the function panicked"))),
+        );
+        configuration.insert_function(
+            "identity".to_string(),
+            Function::new(1, Box::new(|arguments| Ok(arguments[0].clone()))),
+        );
+        configuration.set_max_depth(8);
+        for _ in 0..8 {
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                eval_with_configuration("recurse('panic')", &configuration)
+            }));
+            assert!(result.is_err());
+        }
+        assert_eq!(
+            eval_with_configuration("recurse('identity', 3)", &configuration),
+            Ok(Value::Int(3))
+        );
+    }
+
+    #[test]
+    fn test_named_arguments() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_function(
+            "plot".to_string(),
+            Function::new_named(
+                vec!["x", "color", "width"],
+                Box::new(|arguments| {
+                    Ok(Value::String(format!(
+                        "{} {} {}",
+                        arguments[0], arguments[1], arguments[2]
+                    )))
+                }),
+            ),
+        );
+        configuration.insert_variable("data".to_string(), Value::Int(7));
+        let plot = |expression| eval_with_configuration(expression, &configuration);
+
+        let expected = Ok(Value::from("7 \"red\" 2"));
+        assert_eq!(plot("plot(x: data, color: \"red\", width: 2)"), expected);
+        assert_eq!(plot("plot(width: 2, x: data, color: \"red\")"), expected);
+        assert_eq!(plot("plot(data, \"red\", width: 1 + 1)"), expected);
+        assert_eq!(plot("plot(data, width: 2, \"red\")"), expected);
+        assert_eq!(plot("plot(data, width: true ? 2 : 0, \"red\")"), expected);
+        assert_eq!(plot("plot(data, \"red\", 2)"), expected);
+
+        assert_eq!(
+            plot("plot(x: data, colour: \"red\", width: 2)"),
+            Err(Error::UnknownArgumentName {
+                function: "plot".to_string(),
+                name: "colour".to_string()
+            })
+        );
+        assert_eq!(
+            plot("plot(x: data, x: 1, width: 2)"),
+            Err(Error::DuplicateArgumentName {
+                function: "plot".to_string(),
+                name: "x".to_string()
+            })
+        );
+        assert_eq!(
+            plot("plot(data, \"red\", x: 1)"),
+            Err(Error::DuplicateArgumentName {
+                function: "plot".to_string(),
+                name: "x".to_string()
+            })
+        );
+        assert_eq!(
+            plot("plot(x: data, width: 2)"),
+            Err(Error::WrongArgumentAmount {
+                expected: 3,
+                actual: 2
+            })
+        );
+        assert_eq!(
+            plot("len(x: \"abc\")"),
+            Err(Error::UnknownArgumentName {
+                function: "len".to_string(),
+                name: "x".to_string()
+            })
+        );
+        assert_eq!(
+            plot("map(array: (1, 2), function: 'plot')"),
+            Err(Error::UnknownArgumentName {
+                function: "map".to_string(),
+                name: "array".to_string()
+            })
+        );
+        assert_eq!(
+            plot("draw(x: 1)"),
+            Err(Error::FunctionIdentifierNotFound {
+                identifier: "draw".to_string(),
+                span: Some(0..4)
+            })
+        );
+        assert_eq!(
+            plot("(width: 2)"),
+            Err(Error::UnexpectedNamedArgument {
+                name: "width".to_string()
+            })
+        );
+
+        let tree = build_operator_tree("plot(data, width: 2, color: \"red\")").unwrap();
+        assert_eq!(tree.validate(&configuration), Ok(()));
+        assert_eq!(tree.to_string(), "plot(data, width: 2, color: \"red\")");
+        assert_eq!(
+            build_operator_tree(&tree.to_string())
+                .unwrap()
+                .eval(&configuration),
+            expected
+        );
+        assert_eq!(
+            build_operator_tree("plot(x: 1)").unwrap().to_string(),
+            "plot(x: 1)"
+        );
+    }
+
+    #[test]
+    fn test_typed_function() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_function(
+            "repeat".to_string(),
+            TypedFunction::new()
+                .arg(ValueType::String)
+                .arg(ValueType::Int)
+                .returning(ValueType::String)
+                .body(|arguments| {
+                    let string = arguments[0].as_str().unwrap();
+                    let count = arguments[1].as_int().unwrap();
+                    Ok(Value::String(string.repeat(count as usize)))
+                }),
+        );
+        configuration.insert_function(
+            "broken".to_string(),
+            TypedFunction::new()
+                .arg(ValueType::Any)
+                .returning(ValueType::Boolean)
+                .body(|arguments| Ok(arguments[0].clone())),
+        );
+
+        assert_eq!(
+            eval_with_configuration("repeat('ab', 2)", &configuration),
+            Ok(Value::from("abab"))
+        );
+        assert_eq!(
+            eval_with_configuration("repeat('ab', 2.0)", &configuration),
+            Err(Error::function_error(
+                "repeat".to_string(),
+                Error::type_mismatch(ValueType::Int, Value::Float(2.0), 1)
+            ))
+        );
+        assert_eq!(
+            eval_with_configuration("repeat(1, 'ab')", &configuration),
+            Err(Error::function_error(
+                "repeat".to_string(),
+                Error::type_mismatch(ValueType::String, Value::Int(1), 0)
+            ))
+        );
+        assert_eq!(
+            eval_with_configuration("repeat('ab')", &configuration),
+            Err(Error::wrong_argument_amount(1, 2))
+        );
+        assert_eq!(
+            eval_with_configuration("broken(true)", &configuration),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval_with_configuration("broken(1)", &configuration),
+            Err(Error::function_error(
+                "broken".to_string(),
+                Error::ReturnTypeMismatch {
+                    expected: ValueType::Boolean,
+                    actual: Value::Int(1)
+                }
+            ))
+        );
+
+        let function = TypedFunction::new()
+            .arg(ValueType::Array)
+            .body(|_| Ok(Value::Null));
+        assert_eq!(function.argument_amount(), 1);
+        assert_eq!(function.call(&[Value::Array(vec![])]), Ok(Value::Null));
+        assert_eq!(
+            function.call(&[Value::Tuple(vec![])]),
+            Err(Error::type_mismatch(
+                ValueType::Array,
+                Value::Tuple(vec![]),
+                0
+            ))
+        );
+        assert_eq!(
+            Error::type_mismatch(ValueType::Float, Value::Int(1), 0).to_string(),
+            "Expected the argument at index 0 to be of type float, but got 1"
+        );
+        assert!(ValueType::Any.matches(&Value::Null));
+        assert!(!ValueType::Float.matches(&Value::Int(1)));
+    }
+
+    #[test]
+    fn test_scoped_configuration() {
+        let mut configuration = ScopedConfiguration::new();
+        configuration.insert_variable("a".to_string(), 1);
+        configuration.push_scope();
+        assert_eq!(configuration.scope_count(), 2);
+        assert_eq!(configuration.get_value("a"), Some(&Value::Int(1)));
+        configuration.set_variable("a", Value::Int(2));
+        configuration.set_variable("b", Value::Int(3));
+        assert_eq!(configuration.get_value("a"), Some(&Value::Int(2)));
+        assert_eq!(configuration.variable_names(), vec!["a", "b"]);
+        configuration.pop_scope();
+        assert_eq!(configuration.get_value("a"), Some(&Value::Int(1)));
+        assert_eq!(configuration.get_value("b"), None);
+        // The outermost scope is kept
+        configuration.pop_scope();
+        assert_eq!(configuration.scope_count(), 1);
+        assert_eq!(configuration.get_value("a"), Some(&Value::Int(1)));
+
+        // Variables assigned in the body of an if-else expression do not leak
+        let mut configuration = ScopedConfiguration::new();
+        assert_eq!(
+            eval_with_mutable_configuration(
+                "x = 5; if x > 3 { y = 'big'; x = x * 2; x } else { y = 'small'; x }",
+                &mut configuration
+            ),
+            Ok(Value::Int(10))
+        );
+        assert_eq!(configuration.get_value("x"), Some(&Value::Int(5)));
+        assert_eq!(configuration.get_value("y"), None);
+        assert_eq!(configuration.scope_count(), 1);
+        assert_eq!(
+            eval_with_mutable_configuration(
+                "if false { 1 } else if true { z = 2; if true { z } else { 0 } } else { 3 }",
+                &mut configuration
+            ),
+            Ok(Value::Int(2))
+        );
+        assert_eq!(configuration.get_value("z"), None);
+
+        // Scopes are removed if the body fails, and ternaries have no scope
+        assert_eq!(
+            eval_with_mutable_configuration("if true { w = 1; 1 / 0 }", &mut configuration),
+            Err(Error::DivisionByZero)
+        );
+        assert_eq!(configuration.scope_count(), 1);
+        assert_eq!(configuration.get_value("w"), None);
+        assert_eq!(
+            eval_with_mutable_configuration("true ? (v = 1) : 0; v", &mut configuration),
+            Ok(Value::Int(1))
+        );
+
+        // Chained configurations scope their front layer
+        let mut functions = HashMapConfiguration::new();
+        functions.insert_function(
+            "double".to_string(),
+            Function::new(
+                1,
+                Box::new(|arguments| Ok(Value::Int(expect_int(&arguments[0])? * 2))),
+            ),
+        );
+        let mut configuration = ScopedConfiguration::new().chain(functions);
+        assert_eq!(
+            eval_with_mutable_configuration(
+                "if true { a = double(2) } else { 0 }",
+                &mut configuration
+            ),
+            Ok(Value::Int(4))
+        );
+        assert_eq!(configuration.get_value("a"), None);
+    }
+
+    #[test]
+    fn test_if_else() {
+        assert_eq!(eval("if true { 1 } else { 2 }"), Ok(Value::Int(1)));
+        assert_eq!(eval("if 1 > 2 { 1 } else { 2 }"), Ok(Value::Int(2)));
+        assert_eq!(eval("if false { 1 }"), Ok(Value::Null));
+        assert_eq!(eval("if true { }"), Ok(Value::Null));
+        assert_eq!(
+            eval("if false { 1 } else if true { 2 } else { 3 }"),
+            Ok(Value::Int(2))
+        );
+        assert_eq!(
+            eval("if false { 1 } else if false { 2 } else { 3 }"),
+            Ok(Value::Int(3))
+        );
+        assert_eq!(eval("if false { 1 } else if false { 2 }"), Ok(Value::Null));
+        assert_eq!(
+            eval("if (1 < 2) == true { if false { 1 } else { 2 } } else { 3 }"),
+            Ok(Value::Int(2))
+        );
+        assert_eq!(eval("1 + if true { 2 } else { 3 } * 4"), Ok(Value::Int(9)));
+        assert_eq!(eval("abs(if true { -1 } else { 1 })"), Ok(Value::Int(1)));
+        assert_eq!(eval("if true { 1; 2 } else { 3 }; 4"), Ok(Value::Int(4)));
+        assert_eq!(eval("if true { 1; 2 } else { 3 }"), Ok(Value::Int(2)));
+        // Only the selected branch is evaluated
+        assert_eq!(eval("if true { 1 } else { a }"), Ok(Value::Int(1)));
+
+        let mut configuration = HashMapConfiguration::new();
+        assert_eq!(
+            eval_with_mutable_configuration(
+                "x = 5; if x > 3 { y = 'big'; x * 2 } else { y = 'small'; x }",
+                &mut configuration
+            ),
+            Ok(Value::Int(10))
+        );
+        assert_eq!(
+            configuration.get_value("y"),
+            Some(&Value::String("big".to_string()))
+        );
+
+        assert_eq!(
+            eval("if 1 { 1 }"),
+            Err(Error::ExpectedBoolean {
+                actual: Value::Int(1)
+            })
+        );
+        assert_eq!(
+            eval("if true"),
+            Err(Error::UnmatchedIf { span: Some(0..2) })
+        );
+        assert_eq!(
+            eval("if true { 1"),
+            Err(Error::UnmatchedLCurlyBrace { span: Some(8..9) })
+        );
+        assert_eq!(
+            eval("if true { 1 } else 2"),
+            Err(Error::UnmatchedElse { span: Some(14..18) })
+        );
+        assert_eq!(
+            eval("1 else { 2 }"),
+            Err(Error::UnmatchedElse { span: Some(2..6) })
+        );
+        assert_eq!(
+            eval("{ 1 }"),
+            Err(Error::UnexpectedLCurlyBrace { span: Some(0..1) })
+        );
+        assert_eq!(
+            eval("1 }"),
+            Err(Error::UnmatchedRCurlyBrace { span: Some(2..3) })
+        );
+
+        let tree = build_operator_tree("if a { 1 } else if b { 2 }").unwrap();
+        assert_eq!(
+            tree.to_string(),
+            "(if a { 1 } else { (if b { 2 } else { null }) })"
+        );
+        assert_eq!(
+            build_operator_tree(&tree.to_string()).unwrap().to_string(),
+            tree.to_string()
+        );
+    }
+
+    #[test]
+    fn test_partial_eval() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("a".to_string(), Value::Int(2));
+        configuration.insert_variable("t".to_string(), Value::Boolean(true));
+        configuration.insert_variable("n".to_string(), Value::Null);
+        configuration.insert_function(
+            "double".to_string(),
+            Function::new(
+                1,
+                Box::new(|arguments| Ok(Value::Int(arguments[0].clone().into_int()? * 2))),
+            ),
+        );
+
+        let examples = [
+            ("x + 2 * 3", "(x + 6)"),
+            ("x + a * 3", "(x + 6)"),
+            ("(a + 1) * x", "(3 * x)"),
+            ("a + 1", "3"),
+            ("double(a) + double(x)", "(4 + double(x))"),
+            ("t ? x : y", "x"),
+            ("!t ? x : y + a", "(y + 2)"),
+            ("x ? a : y", "(x ? 2 : y)"),
+            ("n ?? x", "x"),
+            ("a ?? x", "2"),
+            ("x ?? a", "(x ?? 2)"),
+            ("x + (1 + true)", "(x + (1 + true))"),
+        ];
+
+        for (string, expected) in examples.iter() {
+            let tree = build_operator_tree(string).unwrap();
+            assert_eq!(&tree.partial_eval(&configuration).to_string(), expected);
+        }
+
+        let tree = build_operator_tree("x * (a + 1)")
+            .unwrap()
+            .partial_eval(&configuration);
+        configuration.insert_variable("x".to_string(), Value::Int(5));
+        assert_eq!(tree.eval(&configuration), Ok(Value::Int(15)));
+        assert_eq!(
+            Node::from_value(Value::Float(1.5)).eval(&configuration),
+            Ok(Value::Float(1.5))
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            eval("-true"),
+            Err(Error::expected_number(Value::Boolean(true)))
+        );
+        assert_eq!(
+            eval("1-true"),
+            Err(Error::expected_number(Value::Boolean(true)))
+        );
+        assert_eq!(eval("true-"), Err(Error::wrong_argument_amount(1, 2)));
+        assert_eq!(
+            eval("!(()true)"),
+            Err(Error::AppendedToLeafNode { span: Some(4..8) })
+        );
+        assert_eq!(eval("1.0 & 3"), Err(Error::expected_int(Value::Float(1.0))));
+        assert_eq!(
+            eval("true | false"),
+            Err(Error::expected_int(Value::Boolean(true)))
+        );
+        assert_eq!(eval("~1.5"), Err(Error::expected_int(Value::Float(1.5))));
+        assert_eq!(
+            eval("1 << 2.0"),
+            Err(Error::expected_int(Value::Float(2.0)))
+        );
+    }
+
+    #[test]
+    fn test_std_error() {
+        use std::error::Error as StdError;
+
+        fn eval_boxed(string: &str) -> Result<IntType, Box<dyn StdError + Send + Sync>> {
+            let value: EvalResult = eval(string);
+            Ok(value?.into_int()?)
+        }
+
+        assert_eq!(eval_boxed("1 + 1").unwrap(), 2);
+        let error = eval_boxed("1 + true").unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<Error>(),
+            Some(&Error::expected_number(Value::Boolean(true)))
+        );
+        assert_eq!(error.to_string(), "Expected a number, but got true");
+
+        let configuration = HashMapConfiguration::builder()
+            .func("fail", 0, |_| Err(Error::DivisionByZero))
+            .build();
+        let error = eval_with_configuration("fail()", &configuration).unwrap_err();
+        assert_eq!(
+            error
+                .source()
+                .and_then(|source| source.downcast_ref::<Error>()),
+            Some(&Error::DivisionByZero)
+        );
+        assert!(Error::DivisionByZero.source().is_none());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_parse_json() {
+        let mut configuration = HashMapConfiguration::new();
+        assert_eq!(
+            eval_with_mutable_configuration(
+                "result = parse_json('{\"a\": [1, 2, 3]}'); result['a'][0]",
+                &mut configuration
+            ),
+            Ok(Value::Int(1))
+        );
+        assert_eq!(
+            eval("parse_json('{\"a\": [1, 2, 3]}')['a'][2]"),
+            Ok(Value::Int(3))
+        );
+
+        assert_eq!(eval("parse_json('null')"), Ok(Value::Null));
+        assert_eq!(eval("parse_json('true')"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("parse_json('-7')"), Ok(Value::Int(-7)));
+        assert_eq!(eval("parse_json('1.5')"), Ok(Value::Float(1.5)));
+        assert_eq!(eval("parse_json('1e2')"), Ok(Value::Float(100.0)));
+        assert_eq!(
+            eval("parse_json('18446744073709551615')"),
+            Ok(Value::Float(18446744073709551615.0))
+        );
+        assert_eq!(eval("parse_json('\"a\\\\u00e9\"')"), Ok(Value::from("aé")));
+        assert_eq!(
+            eval("parse_json('[1, \"b\", [null]]')"),
+            Ok(Value::Array(vec![
+                Value::Int(1),
+                Value::from("b"),
+                Value::Array(vec![Value::Null])
+            ]))
+        );
+        let mut object = ObjectMap::default();
+        object.insert("z".to_string(), Value::Int(1));
+        object.insert("a".to_string(), Value::Object(ObjectMap::default()));
+        assert_eq!(
+            eval("parse_json('{\"z\": 1, \"a\": {}}')"),
+            Ok(Value::Object(object))
+        );
+        assert_eq!(eval("parse_json('{\"a\": 1}')['missing']"), Ok(Value::Null));
+
+        assert!(matches!(
+            eval("parse_json('{\"a\": }')"),
+            Err(Error::JsonError(_))
+        ));
+        assert!(matches!(eval("parse_json('')"), Err(Error::JsonError(_))));
+        assert_eq!(
+            eval("parse_json(1)"),
+            Err(Error::expected_string(Value::Int(1)))
+        );
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn test_base64() {
+        // The test vectors of RFC 4648
+        for (decoded, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(
+                eval(&format!("base64_encode({:?})", decoded)),
+                Ok(Value::from(encoded))
+            );
+            assert_eq!(
+                eval(&format!("base64_decode({:?})", encoded)),
+                Ok(Value::from(decoded))
+            );
+            assert_eq!(
+                eval(&format!("base64_url_encode({:?})", decoded)),
+                Ok(Value::from(encoded))
+            );
+        }
+
+        // The alphabets differ in the last two characters
+        assert_eq!(eval("base64_encode(\"??>\")"), Ok(Value::from("Pz8+")));
+        assert_eq!(eval("base64_url_encode(\"??>\")"), Ok(Value::from("Pz8-")));
+        assert_eq!(eval("base64_encode(\"???\")"), Ok(Value::from("Pz8/")));
+        assert_eq!(eval("base64_url_encode(\"???\")"), Ok(Value::from("Pz8_")));
+        assert_eq!(eval("base64_url_decode(\"Pz8_\")"), Ok(Value::from("???")));
+
+        for string in ["héllo wörld", "😀", "a\nb"] {
+            let value = Value::from(string);
+            let mut configuration = HashMapConfiguration::new();
+            configuration.insert_variable("s".to_string(), value.clone());
+            assert_eq!(
+                eval_with_configuration("base64_decode(base64_encode(s))", &configuration),
+                Ok(value.clone())
+            );
+            assert_eq!(
+                eval_with_configuration("base64_url_decode(base64_url_encode(s))", &configuration),
+                Ok(value)
+            );
+        }
+
+        assert!(matches!(
+            eval("base64_decode(\"Zm9v!\")"),
+            Err(Error::DecodeError(_))
+        ));
+        assert!(matches!(
+            eval("base64_decode(\"Pz8_\")"),
+            Err(Error::DecodeError(_))
+        ));
+        assert!(matches!(
+            eval("base64_url_decode(\"Pz8/\")"),
+            Err(Error::DecodeError(_))
+        ));
+        // The bytes 0xff 0xfe are not valid UTF-8
+        assert!(matches!(
+            eval("base64_decode(\"//4=\")"),
+            Err(Error::DecodeError(_))
+        ));
+        assert_eq!(
+            eval("base64_encode(1)"),
+            Err(Error::expected_string(Value::Int(1)))
+        );
+    }
+
+    #[cfg(feature = "crypto")]
+    #[test]
+    fn test_hash() {
+        assert_eq!(
+            eval("hash(\"\", \"sha256\")"),
+            Ok(Value::from(
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+            ))
+        );
+        assert_eq!(
+            eval("hash(\"hello\", \"sha256\")"),
+            Ok(Value::from(
+                "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+            ))
+        );
+        assert_eq!(
+            eval("hash(\"abc\", \"sha1\")"),
+            Ok(Value::from("a9993e364706816aba3e25717850c26c9cd0d89d"))
+        );
+        assert_eq!(
+            eval("hash(\"\", \"md5\")"),
+            Ok(Value::from("d41d8cd98f00b204e9800998ecf8427e"))
+        );
+        assert_eq!(
+            eval("hash(\"\", \"fnv\")"),
+            Ok(Value::from("cbf29ce484222325"))
+        );
+        assert_eq!(
+            eval("hash(\"a\", \"fnv\")"),
+            Ok(Value::from("af63dc4c8601ec8c"))
+        );
+        assert_eq!(
+            eval("hash(\"\", \"xxhash\")"),
+            Ok(Value::from("ef46db3751d8e999"))
+        );
+        // Strings are hashed as UTF-8
+        assert_eq!(
+            eval("hash(\"é\", \"md5\")"),
+            eval("hash(from_char_code(233), \"md5\")")
+        );
+
+        assert_eq!(
+            eval("hash(\"a\", \"sha512\")"),
+            Err(Error::UnknownHashAlgorithm("sha512".to_string()))
+        );
+        assert_eq!(
+            eval("hash(1, \"md5\")"),
+            Err(Error::expected_string(Value::Int(1)))
+        );
+        assert_eq!(
+            eval("hash(\"a\", \"SHA256\")").unwrap_err().to_string(),
+            "Unknown hash algorithm \"SHA256\""
+        );
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_uuid() {
+        let uuid = eval_as_string("uuid()").unwrap();
+        let groups: Vec<&str> = uuid.split('-').collect();
+        assert_eq!(
+            groups.iter().map(|group| group.len()).collect::<Vec<_>>(),
+            vec![8, 4, 4, 4, 12]
+        );
+        assert!(uuid
+            .chars()
+            .all(|character| matches!(character, '0'..='9' | 'a'..='f' | '-')));
+        // The version is 4 and the variant is RFC 4122
+        assert!(groups[2].starts_with('4'));
+        assert!(matches!(
+            groups[3].chars().next(),
+            Some('8' | '9' | 'a' | 'b')
+        ));
+
+        assert_ne!(eval("uuid()"), eval("uuid()"));
+        assert_eq!(eval("uuid() == uuid()"), Ok(Value::Boolean(false)));
+        assert_eq!(eval("uuid(1)"), Err(Error::wrong_argument_amount(1, 0)));
+
+        let tree = build_operator_tree("uuid() + ''").unwrap();
+        assert!(!tree.is_const());
+        assert_eq!(tree.eval_if_const(), None);
+        assert_eq!(tree.fold_constants().to_string(), tree.to_string());
+    }
+
+    #[cfg(feature = "env_access")]
+    #[test]
+    fn test_env() {
+        std::env::set_var("EVALEXPR_TEST_ENV", "value");
+        std::env::remove_var("EVALEXPR_TEST_ENV_MISSING");
+
+        assert_eq!(eval("env(\"EVALEXPR_TEST_ENV\")"), Ok(Value::from("value")));
+        assert_eq!(eval("env(\"EVALEXPR_TEST_ENV_MISSING\")"), Ok(Value::Null));
+        assert_eq!(
+            eval("env(\"EVALEXPR_TEST_ENV_MISSING\") ?? 'default'"),
+            Ok(Value::from("default"))
+        );
+        assert_eq!(
+            eval("env_or(\"EVALEXPR_TEST_ENV\", 'default')"),
+            Ok(Value::from("value"))
+        );
+        assert_eq!(
+            eval("env_or(\"EVALEXPR_TEST_ENV_MISSING\", 1)"),
+            Ok(Value::Int(1))
+        );
+        assert_eq!(eval("env(1)"), Err(Error::expected_string(Value::Int(1))));
+        assert!(!build_operator_tree("env('PATH')").unwrap().is_const());
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_regex() {
+        assert_eq!(
+            eval("regex_match('hello world', 'o w')"),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval("regex_match('hello world', '^world')"),
+            Ok(Value::Boolean(false))
+        );
+        assert_eq!(
+            eval("regex_match('hello world', '^hello.*d$')"),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval("regex_match('2024-01-15', '^(\\\\d{4})-(\\\\d{2})-(\\\\d{2})$')"),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval("regex_replace('a1b22c333', '[0-9]+', '#')"),
+            Ok(Value::String("a#b#c#".to_string()))
+        );
+        assert_eq!(
+            eval("regex_replace('John Smith', '(\\\\w+) (\\\\w+)', '$2, $1')"),
+            Ok(Value::String("Smith, John".to_string()))
+        );
+        assert_eq!(
+            eval("regex_replace('abc', 'x', 'y')"),
+            Ok(Value::String("abc".to_string()))
+        );
+        // Patterns are cached, so repeated use gives the same results
+        for _ in 0..20 {
+            assert_eq!(eval("regex_match('abc', 'b')"), Ok(Value::Boolean(true)));
+        }
+
+        match eval("regex_match('abc', '(')") {
+            Err(Error::RegexError(message)) => assert!(message.contains("unclosed group")),
+            result => panic!("Expected a regex error, got {:?}", result),
+        }
+        assert_eq!(
+            eval("regex_match(1, 'a')"),
+            Err(Error::expected_string(Value::Int(1)))
+        );
+        assert_eq!(
+            eval("regex_replace('a', 'a')"),
+            Err(Error::wrong_argument_amount(2, 3))
+        );
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn test_dates() {
+        assert_eq!(eval("timestamp(1970, 1, 1, 0, 0, 0)"), Ok(Value::Int(0)));
+        assert_eq!(
+            eval("timestamp(2024, 2, 29, 12, 30, 15)"),
+            Ok(Value::Int(1_709_209_815_000))
+        );
+        assert_eq!(
+            eval("timestamp(2024, 2, 29, 14, 30, 15, '+02:00')"),
+            Ok(Value::Int(1_709_209_815_000))
+        );
+        assert_eq!(
+            eval("timestamp(2024, 2, 29, 12, 30, 15, 'UTC')"),
+            Ok(Value::Int(1_709_209_815_000))
+        );
+        assert_eq!(
+            eval("date_add(timestamp(2024, 1, 31, 0, 0, 0), 24 * 60 * 60 * 1000)"),
+            eval("timestamp(2024, 2, 1, 0, 0, 0)")
+        );
+        assert_eq!(
+            eval("date_diff(timestamp(2024, 3, 1, 0, 0, 0), timestamp(2024, 2, 1, 0, 0, 0))"),
+            Ok(Value::Int(29 * 24 * 60 * 60 * 1000))
+        );
+        assert!(eval_as_int("now()").unwrap() > 1_700_000_000_000);
+
+        let date_error = |message: &str| Err(Error::DateError(message.to_string()));
+        assert_eq!(
+            eval("timestamp(2023, 2, 29, 0, 0, 0)"),
+            date_error("Invalid date (2023, 2, 29, 0, 0, 0)")
+        );
+        assert_eq!(
+            eval("timestamp(2024, 1, 1, 24, 0, 0)"),
+            date_error("Invalid date (2024, 1, 1, 24, 0, 0)")
+        );
+        assert_eq!(
+            eval("timestamp(2024, -1, 1, 0, 0, 0)"),
+            date_error("Invalid date (2024, -1, 1, 0, 0, 0)")
+        );
+        assert_eq!(
+            eval("timestamp(2024, 1, 1, 0, 0, 0, 'Mars')"),
+            date_error("Unknown timezone \"Mars\"")
+        );
+        assert_eq!(
+            eval("date_add(0, 9223372036854775807)"),
+            date_error("Timestamp 9223372036854775807 is out of range")
+        );
+        assert_eq!(
+            eval("timestamp(2024, 1, 1, 0, 0)"),
+            Err(Error::wrong_argument_amount(5, 6))
+        );
+        assert_eq!(
+            eval("timestamp(2024, 1, 1, 0, 0, 0, 'UTC', 1)"),
+            Err(Error::wrong_argument_amount(8, 7))
+        );
+        assert_eq!(
+            eval("timestamp(2024.5, 1, 1, 0, 0, 0)"),
+            Err(Error::expected_int(Value::Float(2024.5)))
+        );
+
+        // The current time can be fixed by a function of the configuration
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_function(
+            "now".to_string(),
+            Function::new(0, Box::new(|_| Ok(Value::Int(1_709_209_815_000)))),
+        );
+        let is_working_hours =
+            "now() >= timestamp(2024, 2, 29, 9, 0, 0) && now() < timestamp(2024, 2, 29, 17, 0, 0)";
+        assert_eq!(
+            eval_with_configuration(is_working_hours, &configuration),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval_with_configuration(
+                "date_diff(now(), timestamp(2024, 2, 29, 12, 0, 0))",
+                &configuration
+            ),
+            Ok(Value::Int(30 * 60 * 1000 + 15 * 1000))
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let values = vec![
+            Value::String("foo".to_string()),
+            Value::Float(1.5),
+            Value::Float(-2.0),
+            Value::Int(3),
+            Value::Int(-4),
+            Value::Boolean(true),
+            Value::Array(vec![Value::Int(1), Value::Array(vec![]), Value::Null]),
+            Value::Object(
+                vec![
+                    ("b".to_string(), Value::Int(1)),
+                    ("a".to_string(), Value::Object(ObjectMap::default())),
+                ]
+                .into_iter()
+                .collect(),
+            ),
+            Value::Null,
+        ];
+        for value in values {
+            let json = serde_json::to_string(&value).unwrap();
+            assert_eq!(serde_json::from_str::<Value>(&json).unwrap(), value);
+        }
+
+        assert_eq!(serde_json::to_string(&Value::Null).unwrap(), "null");
+        assert_eq!(serde_json::to_string(&Value::Float(1.0)).unwrap(), "1.0");
+        assert_eq!(
+            serde_json::from_str::<Value>(r#"{"z": 1, "a": [true]}"#)
+                .unwrap()
+                .as_object()
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>(),
+            vec!["z", "a"]
+        );
+        assert_eq!(serde_json::from_str::<Value>("1").unwrap(), Value::Int(1));
+        assert_eq!(
+            serde_json::from_str::<Value>("1.0").unwrap(),
+            Value::Float(1.0)
+        );
+        assert!(serde_json::from_str::<Value>("18446744073709551615").is_err());
+
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("five".to_string(), Value::Int(5));
+        configuration.insert_variable("name".to_string(), Value::String("foo".to_string()));
+        configuration.insert_variable("nothing".to_string(), Value::Null);
+        configuration.insert_function(
+            "id".to_string(),
+            Function::new(1, Box::new(|arguments| Ok(arguments[0].clone()))),
+        );
+        let json = serde_json::to_string(&configuration).unwrap();
+        let configuration: HashMapConfiguration = serde_json::from_str(&json).unwrap();
+        assert_eq!(configuration.get_value("five"), Some(&Value::Int(5)));
+        assert_eq!(
+            configuration.get_value("name"),
+            Some(&Value::String("foo".to_string()))
+        );
+        assert_eq!(configuration.get_value("nothing"), Some(&Value::Null));
+        assert!(configuration.get_function("id").is_none());
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_eval_parallel() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::thread::sleep;
+        use std::time::Duration;
+
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("a".to_string(), Value::Int(3));
+        {
+            let running = running.clone();
+            let max_running = max_running.clone();
+            configuration.insert_function(
+                "slow".to_string(),
+                Function::new(
+                    1,
+                    Box::new(move |arguments| {
+                        let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        max_running.fetch_max(now_running, Ordering::SeqCst);
+                        sleep(Duration::from_millis(50));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                        Ok(arguments[0].clone())
+                    }),
+                ),
+            );
+        }
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(2)
+            .build()
+            .unwrap();
+        assert_eq!(
+            pool.install(|| build_operator_tree("slow(1) + slow(2)")
+                .unwrap()
+                .eval_parallel(&configuration)),
+            Ok(Value::Int(3))
+        );
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+
+        for expression in [
+            "1 + 2 * 3 - (4 - a) * (a + 1)",
+            "slow(a) * slow(a + 1) - slow(2) ** 2",
+            "(a > 1 ? slow(1) : slow(2)) + atan2(a * 2, 7)",
+            "atan2(...(1..a)) + sum(0..a) * (a ?? 1) * (0..a)[1]",
+            "(1, a * 2, (a + 1, a - 1))",
+            "clamp(slow(a) * 2, 1, slow(4)) + len(split('a,b', ','))",
+        ]
+        .iter()
+        {
+            let tree = build_operator_tree(expression).unwrap();
+            let expected = tree.eval(&configuration);
+            assert!(expected.is_ok(), "{}: {:?}", expression, expected);
+            assert_eq!(tree.eval_parallel(&configuration), expected);
+        }
+        // Errors are the same as well
+        for expression in [
+            "(1 + true) * (false + 1)",
+            "(a + 1) + unknown(a * 2)",
+            "b = a + 1",
+        ]
+        .iter()
+        {
+            let tree = build_operator_tree(expression).unwrap();
+            let expected = tree.eval(&configuration);
+            assert!(expected.is_err(), "{}: {:?}", expression, expected);
+            assert_eq!(tree.eval_parallel(&configuration), expected);
+        }
+
+        configuration.set_max_depth(4);
+        let tree = build_operator_tree("(a + 1) * ((a + 1) * (a + 1))").unwrap();
+        assert_eq!(
+            tree.eval_parallel(&configuration),
+            Err(Error::max_depth_exceeded(4))
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing() {
+        use std::sync::Mutex;
+        use tracing::{
+            span::{Attributes, Id, Record},
+            Event, Metadata, Subscriber,
+        };
+
+        /// Records the name of each span and the span that was entered when it was created.
+        #[derive(Default)]
+        struct Recorder {
+            spans: Mutex<Vec<(&'static str, Option<u64>)>>,
+            entered: Mutex<Vec<u64>>,
+        }
+
+        impl Subscriber for Recorder {
+            fn enabled(&self, _metadata: &Metadata) -> bool {
+                true
+            }
+
+            fn new_span(&self, attributes: &Attributes) -> Id {
+                let parent = self.entered.lock().unwrap().last().cloned();
+                let mut spans = self.spans.lock().unwrap();
+                spans.push((attributes.metadata().name(), parent));
+                Id::from_u64(spans.len() as u64)
+            }
+
+            fn record(&self, _span: &Id, _values: &Record) {}
+
+            fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+            fn event(&self, _event: &Event) {}
+
+            fn enter(&self, span: &Id) {
+                self.entered.lock().unwrap().push(span.into_u64());
+            }
+
+            fn exit(&self, _span: &Id) {
+                self.entered.lock().unwrap().pop();
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        let tree = build_operator_tree("sum(1..3) * (3 + 4)").unwrap();
+        let value =
+            tracing::subscriber::with_default(recorder.clone(), || tree.eval(&EmptyConfiguration));
+        assert_eq!(value, Ok(Value::Int(21)));
+
+        // Each evaluated node has a span, and only the span of the root has no parent
+        let spans = recorder.spans.lock().unwrap();
+        assert!(spans.iter().all(|(name, _)| *name == "eval_node"));
+        assert_eq!(spans.len(), tree.node_count());
+        assert_eq!(spans[0].1, None);
+        assert!(spans[1..].iter().all(|(_, parent)| parent.is_some()));
+    }
+
+    #[test]
+    fn test_auditing_configuration() {
+        use std::sync::Mutex;
+
+        let mut inner = HashMapConfiguration::new();
+        inner.insert_variable("a".to_string(), Value::Int(2));
+        inner.insert_variable("b".to_string(), Value::Int(3));
+        inner.insert_function(
+            "double".to_string(),
+            Function::new(
+                1,
+                Box::new(|arguments| Ok(Value::Int(arguments[0].clone().into_int()? * 2))),
+            ),
+        );
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let variable_log = log.clone();
+        let function_log = log.clone();
+        let mut configuration = AuditingConfiguration::new(
+            inner,
+            Arc::new(move |identifier, value| {
+                variable_log
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} = {:?}", identifier, value));
+            }),
+            Arc::new(move |identifier, arguments| {
+                function_log
+                    .lock()
+                    .unwrap()
+                    .push(format!("{}{:?}", identifier, arguments));
+            }),
+        );
+
+        assert_eq!(
+            eval_with_configuration("double(a) + b * len(\"xy\") + c ?? 0", &configuration),
+            Err(Error::variable_identifier_not_found(
+                "c".to_string(),
+                Some(28..29)
+            ))
+        );
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "a = Some(Int(2))",
+                "double[Int(2)]",
+                "b = Some(Int(3))",
+                "len[String(\"xy\")]",
+                "c = None",
+            ]
+        );
+
+        log.lock().unwrap().clear();
+        assert_eq!(
+            eval_with_mutable_configuration("c = map(0..2, 'double'); c", &mut configuration),
+            Ok(Value::Array(vec![Value::Int(0), Value::Int(2)]))
+        );
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "map[Array([Int(0), Int(1)]), String(\"double\")]",
+                "double[Int(0)]",
+                "double[Int(1)]",
+                "c = Some(Array([Int(0), Int(2)]))",
+            ]
+        );
+
+        // Validation and other lookups are not audited
+        log.lock().unwrap().clear();
+        assert_eq!(
+            build_operator_tree("a + double(b)")
+                .unwrap()
+                .validate(&configuration),
+            Ok(())
+        );
+        assert!(log.lock().unwrap().is_empty());
+        assert_eq!(
+            configuration.into_inner().get_value("c"),
+            Some(&Value::Array(vec![Value::Int(0), Value::Int(2)]))
+        );
+    }
+
+    #[cfg(feature = "lru")]
+    #[test]
+    fn test_lru_cached_configuration() {
+        use std::cell::Cell;
+        use std::num::NonZeroUsize;
+        use LruCachedConfiguration;
+
+        /// Counts the variable lookups of the wrapped configuration.
+        struct CountingConfiguration {
+            inner: HashMapConfiguration,
+            lookups: Cell<usize>,
+        }
+
+        impl Configuration for CountingConfiguration {
+            fn get_value(&self, identifier: &str) -> Option<&Value> {
+                self.lookups.set(self.lookups.get() + 1);
+                self.inner.get_value(identifier)
+            }
+
+            fn get_function(&self, identifier: &str) -> Option<&Function> {
+                self.inner.get_function(identifier)
+            }
+        }
+
+        let inner = CountingConfiguration {
+            inner: HashMapConfiguration::builder()
+                .var("a", 2)
+                .var("b", 3)
+                .var("c", 4)
+                .build(),
+            lookups: Cell::new(0),
+        };
+        assert_eq!(
+            eval_with_configuration("a * a + a * b", &inner),
+            Ok(Value::Int(10))
+        );
+        assert_eq!(inner.lookups.get(), 4);
+
+        inner.lookups.set(0);
+        let configuration = LruCachedConfiguration::new(&inner, NonZeroUsize::new(2).unwrap());
+        assert_eq!(
+            eval_with_configuration("a * a + a * b", &configuration),
+            Ok(Value::Int(10))
+        );
+        assert_eq!(inner.lookups.get(), 2);
+        assert_eq!(
+            eval_with_configuration("a * b", &configuration),
+            Ok(Value::Int(6))
+        );
+        assert_eq!(inner.lookups.get(), 2);
+
+        // Missing variables are cached as well
+        assert_eq!(configuration.get_value("d"), None);
+        assert_eq!(configuration.get_value("d"), None);
+        assert_eq!(inner.lookups.get(), 3);
+
+        // The least recently used variable is evicted
+        assert_eq!(configuration.get_value("c"), Some(&Value::Int(4)));
+        assert_eq!(inner.lookups.get(), 4);
+        assert_eq!(configuration.get_value("b"), Some(&Value::Int(3)));
+        assert_eq!(inner.lookups.get(), 5);
+
+        configuration.invalidate("c");
+        assert_eq!(configuration.get_value("c"), Some(&Value::Int(4)));
+        assert_eq!(configuration.get_value("b"), Some(&Value::Int(3)));
+        assert_eq!(inner.lookups.get(), 6);
+        configuration.invalidate_all();
+        assert_eq!(configuration.get_value("b"), Some(&Value::Int(3)));
+        assert_eq!(inner.lookups.get(), 7);
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("a ** 2 && true // comment\n"),
+            Ok(vec![
+                (Token::Identifier("a".to_string()), 0..1),
+                (Token::DoubleStar, 2..4),
+                (Token::Int(2), 5..6),
+                (Token::And, 7..9),
+                (Token::Boolean(true), 10..14),
+            ])
+        );
+        assert_eq!(
+            tokenize("f(1.5, 'x') and not null"),
+            Ok(vec![
+                (Token::Identifier("f".to_string()), 0..1),
+                (Token::LBrace, 1..2),
+                (Token::Float(1.5), 2..5),
+                (Token::Comma, 5..6),
+                (Token::String("x".to_string()), 7..10),
+                (Token::RBrace, 10..11),
+                (Token::And, 12..15),
+                (Token::Not, 16..19),
+                (Token::Null, 20..24),
+            ])
+        );
+        assert_eq!(
+            tokenize("\"open").unwrap_err(),
+            Error::UnmatchedQuote { span: Some(0..1) }
+        );
+
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("a".to_string(), Value::Int(3));
+        for expression in [
+            "1 + 2 * 3",
+            "a ** 2 - 1",
+            "clamp(a, 4, 5) > 3 ? to_upper('x') : 'y'",
+            "b = a; b += 1",
+        ]
+        .iter()
+        {
+            let tree = tokens_to_operator_tree(tokenize(expression).unwrap()).unwrap();
+            let expected = eval_with_mutable_configuration(expression, &mut configuration.clone());
+            assert!(expected.is_ok(), "{}: {:?}", expression, expected);
+            assert_eq!(
+                tree.eval_with_mutable_configuration(&mut configuration.clone()),
+                expected
+            );
+        }
+        let tree = tokens_to_operator_tree(tokenize("1 +\n  unknown").unwrap()).unwrap();
+        assert_eq!(tree.eval(&configuration).unwrap_err().span(), Some(6..13));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_node() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("a".to_string(), Value::Int(3));
+        configuration.insert_variable("s".to_string(), Value::String("abc".to_string()));
+        configuration.insert_function(
+            "double".to_string(),
+            Function::new_named(
+                vec!["x"],
+                Box::new(|arguments| Ok(Value::Int(arguments[0].clone().into_int()? * 2))),
+            ),
+        );
+        configuration.insert_function(
+            "answer".to_string(),
+            Function::new(0, Box::new(|_| Ok(Value::Int(42)))),
+        );
+
+        let expressions = [
+            "1 + 2 * 3 - 4 / 2 % 3",
+            "-a ** 2",
+            "(1, \"x\", 2.5, true)",
+            "a > 1 && a >= 1 || a < 1 ^^ a <= 1 && a == 3 && !(a != 3)",
+            "~a & 6 | 1 ^ 2 << 3 >> 1",
+            "a == 3 ? \"yes\" : \"no\"",
+            "if a > 2 { 1 } else if a > 1 { 2 } else { 3 }",
+            "a ?? 1",
+            "\"b\" in s",
+            "(0..a)[1]",
+            "clamp(...(1..a), 7)",
+            "double(a) + double(double(1))",
+            "answer()",
+            "b = a; b += 2; b *= b; b",
+            "s + s",
+            "let x = a + 1 in let y = x in x * y",
+            "double(x: a)",
+        ];
+        for expression in expressions.iter() {
+            let node = build_operator_tree(expression).unwrap();
+            let expected = node.eval_with_mutable_configuration(&mut configuration.clone());
+            assert!(expected.is_ok(), "{}: {:?}", expression, expected);
+
+            let json = serde_json::to_string(&node).unwrap();
+            let from_json: Node = serde_json::from_str(&json).unwrap();
+            assert_eq!(from_json.to_string(), node.to_string());
+            assert_eq!(
+                from_json.eval_with_mutable_configuration(&mut configuration.clone()),
+                expected
+            );
+
+            let bytes = postcard::to_allocvec(&node).unwrap();
+            let from_bytes: Node = postcard::from_bytes(&bytes).unwrap();
+            assert_eq!(from_bytes.to_string(), node.to_string());
+            assert_eq!(
+                from_bytes.eval_with_mutable_configuration(&mut configuration.clone()),
+                expected
+            );
+        }
+
+        // Spans and tuples survive the round trip
+        let node = build_operator_tree("1 + unknown").unwrap();
+        let node: Node = postcard::from_bytes(&postcard::to_allocvec(&node).unwrap()).unwrap();
+        assert_eq!(
+            node.eval(&EmptyConfiguration).unwrap_err().span(),
+            Some(4..11)
+        );
+        let node = Node::from_value(Value::Tuple(vec![Value::Int(1), Value::Array(vec![])]));
+        let node: Node = postcard::from_bytes(&postcard::to_allocvec(&node).unwrap()).unwrap();
+        assert_eq!(
+            node.eval(&EmptyConfiguration),
+            Ok(Value::Tuple(vec![Value::Int(1), Value::Array(vec![])]))
+        );
+
+        let json = serde_json::to_string(&build_operator_tree("1 + 2").unwrap()).unwrap();
+        assert!(json.starts_with(r#"{"version":1,"#));
+        assert!(serde_json::from_str::<Node>(&json.replacen("1", "2", 1)).is_err());
+        let one = r#"{"operator":{"Const":{"Int":1}},"children":[]}"#;
+        let invalid = format!(
+            r#"{{"version":1,"node":{{"operator":"Add","children":[{}]}}}}"#,
+            one
+        );
+        assert!(serde_json::from_str::<Node>(&invalid).is_err());
+        let valid = format!(
+            r#"{{"version":1,"node":{{"operator":"Add","children":[{},{}]}}}}"#,
+            one, one
+        );
+        assert_eq!(
+            serde_json::from_str::<Node>(&valid)
+                .unwrap()
+                .eval(&EmptyConfiguration),
+            Ok(Value::Int(2))
+        );
+    }
+
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    wasm_bindgen_test::wasm_bindgen_test_configure!(run_in_browser);
+
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    #[wasm_bindgen_test::wasm_bindgen_test]
+    fn test_wasm() {
+        use js_sys::{Array, Object, Reflect, JSON};
+        use wasm::{eval_js, eval_with_configuration_js};
+        use wasm_bindgen::{JsCast, JsValue};
+
+        assert_eq!(eval_js("1 + 2").unwrap().as_f64(), Some(3.0));
+        assert_eq!(eval_js("1.5 * 2").unwrap().as_f64(), Some(3.0));
+        assert_eq!(eval_js("1 < 2").unwrap().as_bool(), Some(true));
+        assert_eq!(
+            eval_js("to_string(5)").unwrap().as_string(),
+            Some("5".to_string())
+        );
+        assert!(eval_js("null").unwrap().is_null());
+        let tuple = eval_js("(1, 'a')").unwrap();
+        assert!(Array::is_array(&tuple));
+        assert_eq!(
+            JSON::stringify(&tuple).unwrap().as_string(),
+            Some("[1,\"a\"]".to_string())
+        );
+
+        let error = eval_js("1 + true").unwrap_err();
+        assert!(error.is_instance_of::<js_sys::Error>());
+        assert_eq!(
+            Reflect::get(&error, &JsValue::from_str("message"))
+                .unwrap()
+                .as_string(),
+            Some(Error::expected_number(Value::Boolean(true)).to_string())
+        );
+
+        let variables =
+            JSON::parse(r#"{"a": 2, "b": 0.5, "s": "x", "l": [1, 2], "o": {"k": true}}"#).unwrap();
+        assert_eq!(
+            eval_with_configuration_js("a * b + len(l)", variables.clone())
+                .unwrap()
+                .as_f64(),
+            Some(3.0)
+        );
+        assert_eq!(
+            eval_with_configuration_js("o['k'] && s == 'x'", variables.clone())
+                .unwrap()
+                .as_bool(),
+            Some(true)
+        );
+        assert_eq!(
+            Value::try_from(variables).unwrap().as_object().unwrap()["a"],
+            Value::Int(2)
+        );
+        assert!(eval_with_configuration_js("a", Object::new().into()).is_err());
+        assert!(eval_with_configuration_js("a", JsValue::from_f64(1.0)).is_err());
+    }
+
+    #[cfg(feature = "global_registry")]
+    #[test]
+    fn test_global_registry() {
+        use {register_global_function, unregister_global_function};
+
+        // The registry is shared between tests, so this test uses identifiers that no other test uses
+        assert_eq!(
+            eval("registry_triple(2)"),
+            Err(Error::function_identifier_not_found(
+                "registry_triple".to_string(),
+                Some(0..15)
+            ))
+        );
+        register_global_function(
+            "registry_triple",
+            Function::new(
+                1,
+                Box::new(|arguments| Ok(Value::Int(arguments[0].as_int().unwrap() * 3))),
+            ),
+        );
+        assert_eq!(eval("registry_triple(2) + 1"), Ok(Value::Int(7)));
+        let numbers = HashMapConfiguration::builder()
+            .var("numbers", vec![Value::Int(1), Value::Int(2)])
+            .build();
+        assert_eq!(
+            eval_with_configuration("map(numbers, 'registry_triple')", &numbers),
+            Ok(Value::Array(vec![Value::Int(3), Value::Int(6)]))
+        );
+        assert_eq!(
+            build_operator_tree("registry_triple(1)")
+                .unwrap()
+                .validate(&EmptyConfiguration),
+            Ok(())
+        );
+
+        // Configuration functions shadow global functions
+        let configuration = HashMapConfiguration::builder()
+            .func("registry_triple", 1, |_| Ok(Value::Int(0)))
+            .build();
+        assert_eq!(
+            eval_with_configuration("registry_triple(2)", &configuration),
+            Ok(Value::Int(0))
+        );
+        // Global functions are named after their identifier
+        register_global_function(
+            "registry_len",
+            Function::new(1, Box::new(|_| Err(Error::EmptyArray))),
+        );
+        assert_eq!(
+            eval("registry_len(1)"),
+            Err(Error::function_error(
+                "registry_len".to_string(),
+                Error::EmptyArray
+            ))
+        );
+
+        assert!(unregister_global_function("registry_triple").is_some());
+        assert!(unregister_global_function("registry_triple").is_none());
+        assert!(unregister_global_function("registry_len").is_some());
+        assert_eq!(
+            eval("registry_triple(2)"),
+            Err(Error::function_identifier_not_found(
+                "registry_triple".to_string(),
+                Some(0..15)
+            ))
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_eval_async() {
+        use std::future::Future;
+        use std::pin::Pin;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::task::{Context, Poll};
+        use AsyncFunction;
+        use {eval_async, BoxFuture};
+
+        /// Returns its result on the second poll, such that it has to be awaited.
+        struct Delayed {
+            result: Option<Result<Value, Error>>,
+            yielded: bool,
+        }
+
+        impl Future for Delayed {
+            type Output = Result<Value, Error>;
+
+            fn poll(mut self: Pin<&mut Self>, context: &mut Context) -> Poll<Self::Output> {
+                if self.yielded {
+                    Poll::Ready(self.result.take().unwrap())
+                } else {
+                    self.yielded = true;
+                    context.waker().wake_by_ref();
+                    Poll::Pending
+                }
+            }
+        }
+
+        fn delayed(result: Result<Value, Error>) -> BoxFuture<'static, Result<Value, Error>> {
+            Box::pin(Delayed {
+                result: Some(result),
+                yielded: false,
+            })
+        }
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let fetch_calls = calls.clone();
+        let mut configuration = HashMapConfiguration::builder()
+            .var("a", 3)
+            .func("double", 1, |arguments| {
+                Ok(Value::Int(arguments[0].clone().into_int()? * 2))
+            })
+            .build();
+        configuration.insert_async_function(
+            "fetch".to_string(),
+            AsyncFunction::new(
+                1,
+                Box::new(move |arguments| {
+                    fetch_calls.fetch_add(1, Ordering::SeqCst);
+                    delayed(Ok(Value::Int(arguments[0].as_int().unwrap() + 10)))
+                }),
+            ),
+        );
+        configuration.insert_async_function(
+            "fail".to_string(),
+            AsyncFunction::new(1, Box::new(|_| delayed(Err(Error::EmptyArray)))),
+        );
+
+        assert_eq!(
+            runtime.block_on(eval_async("fetch(a) + 1", &configuration)),
+            Ok(Value::Int(14))
+        );
+        assert_eq!(
+            runtime.block_on(eval_async("double(fetch(fetch(1)))", &configuration)),
+            Ok(Value::Int(42))
+        );
+        assert_eq!(
+            runtime.block_on(eval_async("1 + 2", &configuration)),
+            Ok(Value::Int(3))
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Each call is awaited once, and unselected branches are not awaited
+        calls.store(0, Ordering::SeqCst);
+        assert_eq!(
+            runtime.block_on(eval_async(
+                "fetch(1) + fetch(1) + (a > 5 ? fetch(2) : fetch(3))",
+                &configuration
+            )),
+            Ok(Value::Int(35))
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Arguments that are not equal to themselves do not prevent the results from being found
+        calls.store(0, Ordering::SeqCst);
+        configuration.insert_async_function(
+            "identity".to_string(),
+            AsyncFunction::new(1, Box::new(|arguments| delayed(Ok(arguments[0].clone())))),
+        );
+        configuration.insert_variable("nan".to_string(), Value::Float(FloatType::NAN));
+        let result = runtime
+            .block_on(eval_async("(identity(nan), fetch(1))", &configuration))
+            .unwrap();
+        let result = result.as_tuple().unwrap();
+        assert!(result[0].as_float().unwrap().is_nan());
+        assert_eq!(result[1], Value::Int(11));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        assert_eq!(
+            runtime.block_on(eval_async("fail(1) + fetch(1)", &configuration)),
+            Err(Error::EmptyArray)
+        );
+        assert_eq!(
+            runtime.block_on(eval_async("fetch(1, 2)", &configuration)),
+            Err(Error::wrong_argument_amount(2, 1))
+        );
+        assert_eq!(
+            runtime.block_on(eval_async("fetch(b)", &configuration)),
+            Err(Error::variable_identifier_not_found(
+                "b".to_string(),
+                Some(6..7)
+            ))
+        );
+        assert_eq!(
+            runtime.block_on(eval_async("(1", &configuration)),
+            build_operator_tree("(1").map(|_| Value::Null)
+        );
+
+        let tree = build_operator_tree("fetch(a) * 2").unwrap();
+        assert_eq!(
+            runtime.block_on(tree.eval_async(&configuration)),
+            Ok(Value::Int(26))
+        );
+        // Async functions cannot be called synchronously
+        assert_eq!(
+            tree.eval(&configuration),
+            Err(Error::function_identifier_not_found(
+                "fetch".to_string(),
+                Some(0..5)
+            ))
+        );
+    }
+}
+
+/// Tests that are compiled as `#![no_std]` binary, run with `cargo test --no-default-features`.
+#[cfg(all(test, not(feature = "std")))]
+mod test_no_std {
+    use configuration::{Configuration, HashMapConfiguration};
+    use error::Error;
+    use std::{boxed::Box, string::ToString, vec::Vec};
+    use value::{ObjectMap, Value};
+    use {eval, eval_with_configuration, Function};
+
+    #[test]
+    fn test_eval() {
+        assert_eq!(eval("1 + 2 * 3"), Ok(Value::Int(7)));
+        assert_eq!(eval("'ab' * 2"), Ok(Value::String("abab".to_string())));
+        assert_eq!(eval("(1, 2)[1]"), Ok(Value::Int(2)));
+        assert_eq!(
+            eval("(1, 2)"),
+            Ok(Value::Tuple(vec![Value::Int(1), Value::Int(2)]))
+        );
+        assert_eq!(eval("1 +"), Err(Error::wrong_argument_amount(1, 2)));
+        assert_eq!(
+            eval("1 + true").unwrap_err().to_string(),
+            format!("{}", Error::expected_number(Value::Boolean(true)))
+        );
+    }
+
+    #[test]
+    fn test_float_functions() {
+        assert_eq!(eval("sqrt(16)"), Ok(Value::Float(4.0)));
+        assert_eq!(eval("2 ** 0.5 == sqrt(2)"), Ok(Value::Boolean(true)));
+        assert_eq!(eval("2 ** -1"), Ok(Value::Float(0.5)));
+        assert_eq!(
+            eval("floor(1.5) + ceil(1.5) + round(2.5)"),
+            Ok(Value::Float(6.0))
+        );
+        assert_eq!(eval("abs(-1.5)"), Ok(Value::Float(1.5)));
+        assert_eq!(eval("atan2(0, 1)"), Ok(Value::Float(0.0)));
+        assert_eq!(eval("ln(1)"), Ok(Value::Float(0.0)));
+    }
+
+    #[test]
+    fn test_configuration() {
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("b".to_string(), 2);
+        configuration.insert_variable("a".to_string(), 1);
+        configuration.insert_function(
+            "double".to_string(),
+            Function::new(
+                1,
+                Box::new(|arguments| Ok(Value::Int(arguments[0].clone().into_int()? * 2))),
+            ),
+        );
+        assert_eq!(
+            eval_with_configuration("double(a + b)", &configuration),
+            Ok(Value::Int(6))
+        );
+        assert_eq!(configuration.variable_names(), vec!["a", "b"]);
+        assert_eq!(configuration.function_names(), vec!["double"]);
+    }
+
+    #[test]
+    fn test_objects() {
+        let mut object = ObjectMap::default();
+        object.insert("y".to_string(), Value::Int(1));
+        object.insert("x".to_string(), Value::Int(2));
+        let mut configuration = HashMapConfiguration::new();
+        configuration.insert_variable("o".to_string(), Value::Object(object.clone()));
+        assert_eq!(
+            eval_with_configuration("o['x']", &configuration),
+            Ok(Value::Int(2))
+        );
+        assert_eq!(
+            object.keys().map(|key| key.as_str()).collect::<Vec<_>>(),
+            vec!["y", "x"]
         );
-        assert_eq!(eval("true-"), Err(Error::wrong_argument_amount(1, 2)));
-        assert_eq!(eval("!(()true)"), Err(Error::AppendedToLeafNode));
     }
 }
@@ -0,0 +1,56 @@
+//! Float functions that are provided by `std`, or by `libm` if the `std` feature is disabled.
+
+use value::FloatType;
+
+macro_rules! unary_float_functions {
+    ($($function:ident => $libm_function:ident),* $(,)*) => {
+        $(
+            #[cfg(feature = "std")]
+            pub fn $function(x: FloatType) -> FloatType {
+                x.$function()
+            }
+
+            #[cfg(not(feature = "std"))]
+            pub fn $function(x: FloatType) -> FloatType {
+                libm::$libm_function(x)
+            }
+        )*
+    };
+}
+
+unary_float_functions! {
+    abs => fabs,
+    floor => floor,
+    ceil => ceil,
+    round => round,
+    sqrt => sqrt,
+    ln => log,
+    log2 => log2,
+    log10 => log10,
+    sin => sin,
+    cos => cos,
+    tan => tan,
+    asin => asin,
+    acos => acos,
+    atan => atan,
+}
+
+#[cfg(feature = "std")]
+pub fn atan2(y: FloatType, x: FloatType) -> FloatType {
+    y.atan2(x)
+}
+
+#[cfg(not(feature = "std"))]
+pub fn atan2(y: FloatType, x: FloatType) -> FloatType {
+    libm::atan2(y, x)
+}
+
+#[cfg(feature = "std")]
+pub fn powf(x: FloatType, y: FloatType) -> FloatType {
+    x.powf(y)
+}
+
+#[cfg(not(feature = "std"))]
+pub fn powf(x: FloatType, y: FloatType) -> FloatType {
+    libm::pow(x, y)
+}
@@ -0,0 +1,284 @@
+use std::fmt::{Display, Error, Formatter};
+
+use operator::*;
+use value::{write_tuple, Value};
+
+impl Display for RootNode {
+    fn fmt(&self, _f: &mut Formatter) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl Display for Add {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "+")
+    }
+}
+
+impl Display for Sub {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "-")
+    }
+}
+
+impl Display for Neg {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "-")
+    }
+}
+
+impl Display for Mul {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "*")
+    }
+}
+
+impl Display for Div {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "/")
+    }
+}
+
+impl Display for Mod {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "%")
+    }
+}
+
+impl Display for Exp {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "**")
+    }
+}
+
+impl Display for Eq {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "==")
+    }
+}
+
+impl Display for Neq {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "!=")
+    }
+}
+
+impl Display for Gt {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, ">")
+    }
+}
+
+impl Display for Lt {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "<")
+    }
+}
+
+impl Display for Geq {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, ">=")
+    }
+}
+
+impl Display for Leq {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "<=")
+    }
+}
+
+impl Display for And {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "&&")
+    }
+}
+
+impl Display for Or {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "||")
+    }
+}
+
+impl Display for Xor {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "^^")
+    }
+}
+
+impl Display for Not {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "!")
+    }
+}
+
+impl Display for Spread {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "...")
+    }
+}
+
+impl Display for BitAnd {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "&")
+    }
+}
+
+impl Display for BitOr {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "|")
+    }
+}
+
+impl Display for BitXor {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "^")
+    }
+}
+
+impl Display for BitNot {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "~")
+    }
+}
+
+impl Display for Shl {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "<<")
+    }
+}
+
+impl Display for Shr {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, ">>")
+    }
+}
+
+impl Display for Ternary {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "?")
+    }
+}
+
+impl Display for IfElse {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "if")
+    }
+}
+
+impl Display for NullCoalesce {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "??")
+    }
+}
+
+impl Display for IfError {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "if_error")
+    }
+}
+
+impl Display for In {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "in")
+    }
+}
+
+impl Display for Range {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "..")
+    }
+}
+
+impl Display for Index {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "[]")
+    }
+}
+
+impl Display for Comma {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, ",")
+    }
+}
+
+impl Display for Sequence {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, ";")
+    }
+}
+
+impl Display for Assign {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "=")
+    }
+}
+
+impl Display for CompoundAssign {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}=", self.operator)
+    }
+}
+
+impl Display for NamedArg {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}:", self.name)
+    }
+}
+
+impl Display for Let {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "let {}", self.identifier)
+    }
+}
+
+impl Display for Const {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write_value(&self.value, f)
+    }
+}
+
+impl Display for VariableIdentifier {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}", self.identifier)
+    }
+}
+
+impl Display for FunctionIdentifier {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{}", self.identifier)
+    }
+}
+
+/// Writes a value such that it is parsed to the same value again.
+fn write_value(value: &Value, f: &mut Formatter) -> Result<(), Error> {
+    match value {
+        Value::String(string) => write!(f, "{:?}", string),
+        // The debug representation of floats always contains a decimal point or an exponent
+        Value::Float(float) => write!(f, "{:?}", float),
+        Value::Int(int) => write!(f, "{}", int),
+        Value::Boolean(boolean) => write!(f, "{}", boolean),
+        Value::Array(array) => {
+            write!(f, "[")?;
+            for (index, value) in array.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                write_value(value, f)?;
+            }
+            write!(f, "]")
+        }
+        Value::Tuple(tuple) => write_tuple(tuple, f, write_value),
+        Value::Object(object) => {
+            write!(f, "{{")?;
+            for (index, (key, value)) in object.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{:?}: ", key)?;
+                write_value(value, f)?;
+            }
+            write!(f, "}}")
+        }
+        Value::Null => write!(f, "null"),
+    }
+}
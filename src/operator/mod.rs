@@ -1,62 +1,294 @@
-use crate::{configuration::Configuration, error::*, value::Value};
-use std::fmt::Debug;
-
-pub trait Operator: Debug {
+use crate::{
+    configuration::Configuration,
+    error::*,
+    function::{builtin::higher_order_function, default_function},
+    math,
+    token::Span,
+    value::{FloatType, IntType, Value},
+};
+#[cfg(feature = "serde")]
+use std::any::Any;
+use std::{
+    boxed::Box,
+    convert::TryFrom,
+    fmt::{Debug, Display},
+    string::String,
+};
+
+mod display;
+#[cfg(feature = "serde")]
+mod serialization;
+
+#[cfg(feature = "serde")]
+pub(crate) use self::serialization::SerializedOperator;
+
+pub trait Operator: Debug + Display + CloneOperator + Send + Sync {
     /// Returns the precedence of the operator.
     /// A high precedence means that the operator has priority to be deeper in the tree.
     // Make this a const fn once #57563 is resolved
     fn precedence(&self) -> i32;
 
+    /// True if chains of operators with the same precedence as this one are evaluated left-to-right.
+    /// This is the case for all operators except exponentiation, which is right-associative.
+    fn is_left_to_right(&self) -> bool {
+        true
+    }
+
     /// True if this operator is a leaf, meaning it accepts no arguments.
     // Make this a const fn once #57563 is resolved
     fn is_leaf(&self) -> bool {
         self.argument_amount() == 0
     }
 
+    /// True if this operator is unary, meaning it accepts exactly one argument that follows it.
+    // Make this a const fn once #57563 is resolved
+    fn is_unary(&self) -> bool {
+        self.argument_amount() == 1
+    }
+
+    /// True if this operator is a root node, which groups a braced sub-expression.
+    fn is_root_node(&self) -> bool {
+        false
+    }
+
+    /// True if this operator is the comma that separates function arguments.
+    fn is_comma(&self) -> bool {
+        false
+    }
+
+    /// True if this operator is the spread operator `...`, which flattens an array into the arguments of a function call.
+    /// Spread nodes are evaluated by the tree, since they change the amount of arguments of the call.
+    fn is_spread(&self) -> bool {
+        false
+    }
+
+    /// True if this operator is a conditional with the condition, the then-branch and the else-branch as arguments, such as the ternary operator.
+    /// Ternary nodes are evaluated lazily, such that only the selected branch is evaluated.
+    fn is_ternary(&self) -> bool {
+        false
+    }
+
+    /// True if this operator is an `if`-`else` expression.
+    /// It is evaluated like the ternary operator, but displayed differently.
+    fn is_if_else(&self) -> bool {
+        false
+    }
+
+    /// True if this operator is the index operator `a[i]`.
+    fn is_index(&self) -> bool {
+        false
+    }
+
+    /// True if this operator is the null-coalescing operator.
+    /// Null-coalescing nodes are evaluated lazily, such that the right side is only evaluated if the left side is null.
+    fn is_null_coalescing(&self) -> bool {
+        false
+    }
+
+    /// True if this operator is the `if_error` special form.
+    /// Its nodes are evaluated lazily, such that the fallback is only evaluated if the evaluation of the first argument fails.
+    fn is_if_error(&self) -> bool {
+        false
+    }
+
+    /// True if this operator is the assignment operator.
+    /// Assignment nodes are evaluated by the tree, which writes the value of the right side to the variable on the left side.
+    fn is_assignment(&self) -> bool {
+        false
+    }
+
+    /// Returns the operator that combines the current value of the variable with the right side, if this is a compound assignment such as `+=`.
+    fn compound_assignment_operator(&self) -> Option<&dyn Operator> {
+        None
+    }
+
+    /// Returns the parameter name of this operator, if it is a named argument such as `color: "red"`.
+    /// Named arguments of function calls are mapped to the positions of their parameters by the tree.
+    fn argument_name(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the identifier bound by this operator, if it is a `let` binding.
+    /// Let nodes are evaluated by the tree, which evaluates the body with the value of the binding in place of the variable.
+    fn let_identifier(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the identifier of the variable this operator reads, if it is a variable identifier.
+    fn variable_identifier(&self) -> Option<&str> {
+        None
+    }
+
+    /// Returns the identifier of the function this operator calls, if it is a function identifier.
+    fn function_identifier(&self) -> Option<&str> {
+        None
+    }
+
     /// Returns the amount of arguments required by this operator.
     // Make this a const fn once #57563 is resolved
     fn argument_amount(&self) -> usize;
 
+    /// Checks that this operator can be evaluated with the given amount of arguments and configuration, without evaluating it.
+    /// This checks that referenced variables and functions exist and that functions are called with the correct amount of arguments.
+    fn validate(
+        &self,
+        _argument_amount: usize,
+        _configuration: &dyn Configuration,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
     /// Evaluates the operator with the given arguments and configuration.
-    fn eval(&self, arguments: &[Value], configuration: &Configuration) -> Result<Value, Error>;
+    fn eval(&self, arguments: &[Value], configuration: &dyn Configuration) -> Result<Value, Error>;
+}
+
+/// Allows cloning boxed operators.
+/// This is implemented automatically for all operators that implement `Clone`.
+pub trait CloneOperator {
+    fn clone_operator(&self) -> Box<dyn Operator>;
+
+    /// Allows downcasting boxed operators to serialize them.
+    #[cfg(feature = "serde")]
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Operator + Clone + 'static> CloneOperator for T {
+    fn clone_operator(&self) -> Box<dyn Operator> {
+        Box::new(self.clone())
+    }
+
+    #[cfg(feature = "serde")]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl Clone for Box<dyn Operator> {
+    fn clone(&self) -> Self {
+        self.clone_operator()
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct RootNode;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Add;
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Sub;
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Neg;
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Mul;
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Div;
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Mod;
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+pub struct Exp;
 
+#[derive(Clone, Debug)]
 pub struct Eq;
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Neq;
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Gt;
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Lt;
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Geq;
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Leq;
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct And;
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Or;
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+pub struct Xor;
+#[derive(Clone, Debug)]
 pub struct Not;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+pub struct BitAnd;
+#[derive(Clone, Debug)]
+pub struct BitOr;
+#[derive(Clone, Debug)]
+pub struct BitXor;
+#[derive(Clone, Debug)]
+pub struct BitNot;
+#[derive(Clone, Debug)]
+pub struct Shl;
+#[derive(Clone, Debug)]
+pub struct Shr;
+
+#[derive(Clone, Debug)]
+pub struct Ternary;
+#[derive(Clone, Debug)]
+pub struct IfElse;
+#[derive(Clone, Debug)]
+pub struct NullCoalesce;
+#[derive(Clone, Debug)]
+pub struct IfError;
+
+#[derive(Clone, Debug)]
+pub struct In;
+
+#[derive(Clone, Debug)]
+pub struct Range;
+
+#[derive(Clone, Debug)]
+pub struct Index;
+
+#[derive(Clone, Debug)]
+pub struct Comma;
+#[derive(Clone, Debug)]
+pub struct Spread;
+
+#[derive(Clone, Debug)]
+pub struct Sequence;
+
+#[derive(Clone, Debug)]
+pub struct Assign;
+
+/// An assignment that applies `operator` to the current value of the variable and the right side, such as `+=`.
+#[derive(Clone, Debug)]
+pub struct CompoundAssign {
+    operator: Box<dyn Operator>,
+}
+
+impl CompoundAssign {
+    pub fn new<T: Operator + 'static>(operator: T) -> Self {
+        Self {
+            operator: Box::new(operator),
+        }
+    }
+}
+
+/// An argument of a function call that is given for the parameter `name`.
+#[derive(Clone, Debug)]
+pub struct NamedArg {
+    name: String,
+}
+
+impl NamedArg {
+    pub fn new(name: String) -> Self {
+        Self { name }
+    }
+}
+
+/// A `let` binding of `identifier`, whose arguments are the bound value and the body.
+#[derive(Clone, Debug)]
+pub struct Let {
+    identifier: String,
+}
+
+impl Let {
+    pub fn new(identifier: String) -> Self {
+        Self { identifier }
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Const {
     value: Value,
 }
@@ -67,25 +299,27 @@ impl Const {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct VariableIdentifier {
     identifier: String,
+    span: Option<Span>,
 }
 
 impl VariableIdentifier {
-    pub fn new(identifier: String) -> Self {
-        Self { identifier }
+    pub fn new(identifier: String, span: Option<Span>) -> Self {
+        Self { identifier, span }
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct FunctionIdentifier {
     identifier: String,
+    span: Option<Span>,
 }
 
 impl FunctionIdentifier {
-    pub fn new(identifier: String) -> Self {
-        Self { identifier }
+    pub fn new(identifier: String, span: Option<Span>) -> Self {
+        Self { identifier, span }
     }
 }
 
@@ -94,16 +328,53 @@ impl Operator for RootNode {
         200
     }
 
+    fn is_root_node(&self) -> bool {
+        true
+    }
+
     fn argument_amount(&self) -> usize {
         1
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 1)?;
         Ok(arguments[0].clone())
     }
 }
 
+/// The maximum length of arrays created by ranges if the configuration does not limit the length of arrays.
+/// Without it, a short expression such as `0..1000000000000` could exhaust the memory.
+const DEFAULT_MAX_RANGE_LENGTH: usize = 1 << 24;
+
+/// The maximum length in bytes of strings created by repetition if the configuration does not limit the length of strings.
+const DEFAULT_MAX_REPETITION_LENGTH: usize = 1 << 30;
+
+/// Applies the checked integer operation, and returns `Error::IntegerOverflow` if it overflows.
+/// With the `wrapping_arithmetic` feature, the wrapping operation is applied instead, so one of the two functions is unused.
+#[allow(unused_variables)]
+fn int_operation(
+    operator: &'static str,
+    lhs: IntType,
+    rhs: IntType,
+    checked: fn(IntType, IntType) -> Option<IntType>,
+    wrapping: fn(IntType, IntType) -> IntType,
+) -> Result<Value, Error> {
+    #[cfg(feature = "wrapping_arithmetic")]
+    {
+        Ok(Value::Int(wrapping(lhs, rhs)))
+    }
+    #[cfg(not(feature = "wrapping_arithmetic"))]
+    {
+        checked(lhs, rhs)
+            .map(Value::Int)
+            .ok_or_else(|| Error::integer_overflow(operator, lhs, rhs))
+    }
+}
+
 impl Operator for Add {
     fn precedence(&self) -> i32 {
         95
@@ -113,19 +384,31 @@ impl Operator for Add {
         2
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 2)?;
+        // String concatenation, without converting other values to strings
+        if let (Value::String(a), Value::String(b)) = (&arguments[0], &arguments[1]) {
+            return Ok(Value::String(a.clone() + b));
+        }
         expect_number(&arguments[0])?;
         expect_number(&arguments[1])?;
 
         if arguments[0].is_int() && arguments[1].is_int() {
-            Ok(Value::Int(
-                arguments[0].as_int().unwrap() + arguments[1].as_int().unwrap(),
-            ))
+            int_operation(
+                "+",
+                arguments[0].as_int().unwrap(),
+                arguments[1].as_int().unwrap(),
+                IntType::checked_add,
+                IntType::wrapping_add,
+            )
         } else {
-            Ok(Value::Float(
+            Ok(Value::Float(expect_finite(
                 arguments[0].as_float().unwrap() + arguments[1].as_float().unwrap(),
-            ))
+            )?))
         }
     }
 }
@@ -139,19 +422,27 @@ impl Operator for Sub {
         2
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 2)?;
         expect_number(&arguments[0])?;
         expect_number(&arguments[1])?;
 
         if arguments[0].is_int() && arguments[1].is_int() {
-            Ok(Value::Int(
-                arguments[0].as_int().unwrap() - arguments[1].as_int().unwrap(),
-            ))
+            int_operation(
+                "-",
+                arguments[0].as_int().unwrap(),
+                arguments[1].as_int().unwrap(),
+                IntType::checked_sub,
+                IntType::wrapping_sub,
+            )
         } else {
-            Ok(Value::Float(
+            Ok(Value::Float(expect_finite(
                 arguments[0].as_float().unwrap() - arguments[1].as_float().unwrap(),
-            ))
+            )?))
         }
     }
 }
@@ -165,14 +456,26 @@ impl Operator for Neg {
         1
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 1)?;
         expect_number(&arguments[0])?;
 
         if arguments[0].is_int() {
-            Ok(Value::Int(-arguments[0].as_int().unwrap()))
+            int_operation(
+                "-",
+                0,
+                arguments[0].as_int().unwrap(),
+                IntType::checked_sub,
+                IntType::wrapping_sub,
+            )
         } else {
-            Ok(Value::Float(-arguments[0].as_float().unwrap()))
+            Ok(Value::Float(expect_finite(
+                -arguments[0].as_float().unwrap(),
+            )?))
         }
     }
 }
@@ -186,19 +489,47 @@ impl Operator for Mul {
         2
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(&self, arguments: &[Value], configuration: &dyn Configuration) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 2)?;
+        // String repetition
+        match (&arguments[0], &arguments[1]) {
+            (Value::String(string), count) | (count, Value::String(string)) => {
+                return match count {
+                    Value::Int(count) if *count >= 0 => {
+                        // Check the length before allocating the string
+                        let count = usize::try_from(*count).unwrap_or(usize::MAX);
+                        // Lengths that overflow exceed any limit
+                        let length = string.len().saturating_mul(count);
+                        expect_string_length(
+                            length,
+                            Some(
+                                configuration
+                                    .max_string_length()
+                                    .unwrap_or(DEFAULT_MAX_REPETITION_LENGTH),
+                            ),
+                        )?;
+                        Ok(Value::String(string.repeat(count)))
+                    }
+                    count => Err(Error::expected_positive_int(count.clone())),
+                };
+            }
+            _ => {}
+        }
         expect_number(&arguments[0])?;
         expect_number(&arguments[1])?;
 
         if arguments[0].is_int() && arguments[1].is_int() {
-            Ok(Value::Int(
-                arguments[0].as_int().unwrap() * arguments[1].as_int().unwrap(),
-            ))
+            int_operation(
+                "*",
+                arguments[0].as_int().unwrap(),
+                arguments[1].as_int().unwrap(),
+                IntType::checked_mul,
+                IntType::wrapping_mul,
+            )
         } else {
-            Ok(Value::Float(
+            Ok(Value::Float(expect_finite(
                 arguments[0].as_float().unwrap() * arguments[1].as_float().unwrap(),
-            ))
+            )?))
         }
     }
 }
@@ -212,19 +543,34 @@ impl Operator for Div {
         2
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 2)?;
         expect_number(&arguments[0])?;
         expect_number(&arguments[1])?;
 
         if arguments[0].is_int() && arguments[1].is_int() {
-            Ok(Value::Int(
-                arguments[0].as_int().unwrap() / arguments[1].as_int().unwrap(),
-            ))
+            if arguments[1].as_int().unwrap() == 0 {
+                return Err(Error::DivisionByZero);
+            }
+            int_operation(
+                "/",
+                arguments[0].as_int().unwrap(),
+                arguments[1].as_int().unwrap(),
+                IntType::checked_div,
+                IntType::wrapping_div,
+            )
         } else {
-            Ok(Value::Float(
-                arguments[0].as_float().unwrap() / arguments[1].as_float().unwrap(),
-            ))
+            let b = arguments[1].as_float().unwrap();
+            if b == 0.0 && !cfg!(feature = "permissive_floats") {
+                return Err(Error::DivisionByZero);
+            }
+            Ok(Value::Float(expect_finite(
+                arguments[0].as_float().unwrap() / b,
+            )?))
         }
     }
 }
@@ -238,19 +584,83 @@ impl Operator for Mod {
         2
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 2)?;
         expect_number(&arguments[0])?;
         expect_number(&arguments[1])?;
 
         if arguments[0].is_int() && arguments[1].is_int() {
-            Ok(Value::Int(
-                arguments[0].as_int().unwrap() % arguments[1].as_int().unwrap(),
-            ))
+            if arguments[1].as_int().unwrap() == 0 {
+                return Err(Error::DivisionByZero);
+            }
+            int_operation(
+                "%",
+                arguments[0].as_int().unwrap(),
+                arguments[1].as_int().unwrap(),
+                IntType::checked_rem,
+                IntType::wrapping_rem,
+            )
         } else {
-            Ok(Value::Float(
-                arguments[0].as_float().unwrap() % arguments[1].as_float().unwrap(),
-            ))
+            let b = arguments[1].as_float().unwrap();
+            if b == 0.0 && !cfg!(feature = "permissive_floats") {
+                return Err(Error::DivisionByZero);
+            }
+            Ok(Value::Float(expect_finite(
+                arguments[0].as_float().unwrap() % b,
+            )?))
+        }
+    }
+}
+
+impl Operator for Exp {
+    fn precedence(&self) -> i32 {
+        120
+    }
+
+    fn is_left_to_right(&self) -> bool {
+        false
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        expect_argument_amount(arguments.len(), 2)?;
+        expect_number(&arguments[0])?;
+        expect_number(&arguments[1])?;
+
+        if arguments[0].is_int() && arguments[1].is_int() {
+            let base = arguments[0].as_int().unwrap();
+            let exponent = arguments[1].as_int().unwrap();
+
+            if exponent < 0 {
+                // Negative exponents do not yield integer results
+                Ok(Value::Float(expect_finite(math::powf(
+                    base as FloatType,
+                    exponent as FloatType,
+                ))?))
+            } else if exponent > IntType::from(u32::MAX) {
+                Err(Error::integer_overflow("**", base, exponent))
+            } else {
+                match base.checked_pow(exponent as u32) {
+                    Some(result) => Ok(Value::Int(result)),
+                    None => Err(Error::integer_overflow("**", base, exponent)),
+                }
+            }
+        } else {
+            Ok(Value::Float(expect_finite(math::powf(
+                arguments[0].as_float().unwrap(),
+                arguments[1].as_float().unwrap(),
+            ))?))
         }
     }
 }
@@ -264,7 +674,11 @@ impl Operator for Eq {
         2
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 2)?;
 
         if arguments[0] == arguments[1] {
@@ -284,7 +698,11 @@ impl Operator for Neq {
         2
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 2)?;
 
         if arguments[0] != arguments[1] {
@@ -304,7 +722,11 @@ impl Operator for Gt {
         2
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 2)?;
         expect_number(&arguments[0])?;
         expect_number(&arguments[1])?;
@@ -334,7 +756,11 @@ impl Operator for Lt {
         2
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 2)?;
         expect_number(&arguments[0])?;
         expect_number(&arguments[1])?;
@@ -364,7 +790,11 @@ impl Operator for Geq {
         2
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 2)?;
         expect_number(&arguments[0])?;
         expect_number(&arguments[1])?;
@@ -394,7 +824,11 @@ impl Operator for Leq {
         2
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 2)?;
         expect_number(&arguments[0])?;
         expect_number(&arguments[1])?;
@@ -424,7 +858,11 @@ impl Operator for And {
         2
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 2)?;
         let a = expect_boolean(&arguments[0])?;
         let b = expect_boolean(&arguments[1])?;
@@ -446,7 +884,11 @@ impl Operator for Or {
         2
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 2)?;
         let a = expect_boolean(&arguments[0])?;
         let b = expect_boolean(&arguments[1])?;
@@ -459,6 +901,28 @@ impl Operator for Or {
     }
 }
 
+impl Operator for Xor {
+    fn precedence(&self) -> i32 {
+        72
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        expect_argument_amount(arguments.len(), 2)?;
+        let a = expect_boolean(&arguments[0])?;
+        let b = expect_boolean(&arguments[1])?;
+
+        Ok(Value::Boolean(a != b))
+    }
+}
+
 impl Operator for Not {
     fn precedence(&self) -> i32 {
         110
@@ -468,7 +932,11 @@ impl Operator for Not {
         1
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 1)?;
         let a = expect_boolean(&arguments[0])?;
 
@@ -480,6 +948,535 @@ impl Operator for Not {
     }
 }
 
+impl Operator for Spread {
+    fn precedence(&self) -> i32 {
+        110
+    }
+
+    fn is_spread(&self) -> bool {
+        true
+    }
+
+    fn argument_amount(&self) -> usize {
+        1
+    }
+
+    fn eval(
+        &self,
+        _arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        // Spread arguments of function calls are flattened by the tree
+        Err(Error::UnexpectedSpread)
+    }
+}
+
+impl Operator for NamedArg {
+    fn precedence(&self) -> i32 {
+        42
+    }
+
+    fn argument_name(&self) -> Option<&str> {
+        Some(&self.name)
+    }
+
+    fn argument_amount(&self) -> usize {
+        1
+    }
+
+    fn eval(
+        &self,
+        _arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        // Named arguments of function calls are mapped to their positions by the tree
+        Err(Error::unexpected_named_argument(self.name.clone()))
+    }
+}
+
+impl Operator for BitAnd {
+    fn precedence(&self) -> i32 {
+        78
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        expect_argument_amount(arguments.len(), 2)?;
+        let a = expect_int(&arguments[0])?;
+        let b = expect_int(&arguments[1])?;
+
+        Ok(Value::Int(a & b))
+    }
+}
+
+impl Operator for BitOr {
+    fn precedence(&self) -> i32 {
+        76
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        expect_argument_amount(arguments.len(), 2)?;
+        let a = expect_int(&arguments[0])?;
+        let b = expect_int(&arguments[1])?;
+
+        Ok(Value::Int(a | b))
+    }
+}
+
+impl Operator for BitXor {
+    fn precedence(&self) -> i32 {
+        77
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        expect_argument_amount(arguments.len(), 2)?;
+        let a = expect_int(&arguments[0])?;
+        let b = expect_int(&arguments[1])?;
+
+        Ok(Value::Int(a ^ b))
+    }
+}
+
+/// Shifts the integer by the given amount of bits, which must be non-negative and smaller than the width of the integer.
+fn shift(
+    operator: &'static str,
+    lhs: IntType,
+    rhs: IntType,
+    checked: fn(IntType, u32) -> Option<IntType>,
+) -> Result<Value, Error> {
+    u32::try_from(rhs)
+        .ok()
+        .and_then(|amount| checked(lhs, amount))
+        .map(Value::Int)
+        .ok_or_else(|| Error::integer_overflow(operator, lhs, rhs))
+}
+
+impl Operator for Shl {
+    fn precedence(&self) -> i32 {
+        97
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        expect_argument_amount(arguments.len(), 2)?;
+        let a = expect_int(&arguments[0])?;
+        let b = expect_int(&arguments[1])?;
+
+        shift("<<", a, b, IntType::checked_shl)
+    }
+}
+
+impl Operator for Shr {
+    fn precedence(&self) -> i32 {
+        97
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        expect_argument_amount(arguments.len(), 2)?;
+        let a = expect_int(&arguments[0])?;
+        let b = expect_int(&arguments[1])?;
+
+        shift(">>", a, b, IntType::checked_shr)
+    }
+}
+
+impl Operator for BitNot {
+    fn precedence(&self) -> i32 {
+        110
+    }
+
+    fn argument_amount(&self) -> usize {
+        1
+    }
+
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        expect_argument_amount(arguments.len(), 1)?;
+        let a = expect_int(&arguments[0])?;
+
+        Ok(Value::Int(!a))
+    }
+}
+
+impl Operator for Ternary {
+    fn precedence(&self) -> i32 {
+        50
+    }
+
+    fn is_left_to_right(&self) -> bool {
+        false
+    }
+
+    fn is_ternary(&self) -> bool {
+        true
+    }
+
+    fn argument_amount(&self) -> usize {
+        3
+    }
+
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        expect_argument_amount(arguments.len(), 3)?;
+
+        if expect_boolean(&arguments[0])? {
+            Ok(arguments[1].clone())
+        } else {
+            Ok(arguments[2].clone())
+        }
+    }
+}
+
+impl Operator for IfElse {
+    fn precedence(&self) -> i32 {
+        200
+    }
+
+    fn is_ternary(&self) -> bool {
+        true
+    }
+
+    fn is_if_else(&self) -> bool {
+        true
+    }
+
+    fn argument_amount(&self) -> usize {
+        3
+    }
+
+    fn eval(&self, arguments: &[Value], configuration: &dyn Configuration) -> Result<Value, Error> {
+        Ternary.eval(arguments, configuration)
+    }
+}
+
+impl Operator for NullCoalesce {
+    fn precedence(&self) -> i32 {
+        65
+    }
+
+    fn is_null_coalescing(&self) -> bool {
+        true
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        expect_argument_amount(arguments.len(), 2)?;
+
+        if arguments[0].is_null() {
+            Ok(arguments[1].clone())
+        } else {
+            Ok(arguments[0].clone())
+        }
+    }
+}
+
+impl Operator for IfError {
+    fn precedence(&self) -> i32 {
+        190
+    }
+
+    fn is_if_error(&self) -> bool {
+        true
+    }
+
+    fn argument_amount(&self) -> usize {
+        1
+    }
+
+    fn validate(
+        &self,
+        argument_amount: usize,
+        _configuration: &dyn Configuration,
+    ) -> Result<(), Error> {
+        expect_argument_amount(argument_amount, 2)
+    }
+
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        // The fallback is only needed if the evaluation of the first argument fails, which is handled by the tree
+        expect_argument_amount(arguments.len(), 2)?;
+
+        Ok(arguments[0].clone())
+    }
+}
+
+impl Operator for Let {
+    fn precedence(&self) -> i32 {
+        200
+    }
+
+    fn let_identifier(&self) -> Option<&str> {
+        Some(&self.identifier)
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        // Bodies that depend on the binding are evaluated by the tree
+        expect_argument_amount(arguments.len(), 2)?;
+
+        Ok(arguments[1].clone())
+    }
+}
+
+impl Operator for In {
+    fn precedence(&self) -> i32 {
+        80
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        expect_argument_amount(arguments.len(), 2)?;
+
+        match &arguments[1] {
+            Value::Array(array) | Value::Tuple(array) => {
+                Ok(Value::Boolean(array.contains(&arguments[0])))
+            }
+            Value::String(string) => match &arguments[0] {
+                Value::String(substring) => Ok(Value::Boolean(string.contains(substring.as_str()))),
+                value => Err(Error::expected_string(value.clone())),
+            },
+            value => Err(Error::expected_array_or_string(value.clone())),
+        }
+    }
+}
+
+impl Operator for Range {
+    fn precedence(&self) -> i32 {
+        90
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    fn eval(&self, arguments: &[Value], configuration: &dyn Configuration) -> Result<Value, Error> {
+        expect_argument_amount(arguments.len(), 2)?;
+        let start = expect_int(&arguments[0])?;
+        let end = expect_int(&arguments[1])?;
+        // Check the length before allocating the array
+        let length = usize::try_from(end.saturating_sub(start).max(0)).unwrap_or(usize::MAX);
+        expect_array_length(
+            length,
+            Some(
+                configuration
+                    .max_array_length()
+                    .unwrap_or(DEFAULT_MAX_RANGE_LENGTH),
+            ),
+        )?;
+
+        Ok(Value::Array((start..end).map(Value::Int).collect()))
+    }
+}
+
+impl Operator for Index {
+    fn precedence(&self) -> i32 {
+        180
+    }
+
+    fn is_index(&self) -> bool {
+        true
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        expect_argument_amount(arguments.len(), 2)?;
+
+        match &arguments[0] {
+            Value::Array(array) | Value::Tuple(array) => {
+                let index = expect_int(&arguments[1])?;
+                if index >= 0 && (index as usize) < array.len() {
+                    Ok(array[index as usize].clone())
+                } else {
+                    Err(Error::index_out_of_bounds(index, array.len()))
+                }
+            }
+            // Missing keys are null, such that defaults can be given with `??`
+            Value::Object(object) => Ok(object
+                .get(expect_string(&arguments[1])?)
+                .cloned()
+                .unwrap_or(Value::Null)),
+            value => Err(Error::expected_array(value.clone())),
+        }
+    }
+}
+
+impl Operator for Comma {
+    fn precedence(&self) -> i32 {
+        40
+    }
+
+    fn is_comma(&self) -> bool {
+        true
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    /// Returns the values of all comma-separated expressions as tuple.
+    /// Inside function calls, the values are passed to the function as separate arguments instead.
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        Ok(Value::Tuple(arguments.to_vec()))
+    }
+}
+
+impl Operator for Sequence {
+    fn precedence(&self) -> i32 {
+        0
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        expect_argument_amount(arguments.len(), 2)?;
+
+        // Both sides are evaluated in order, and the left side is discarded
+        Ok(arguments[1].clone())
+    }
+}
+
+impl Operator for Assign {
+    fn precedence(&self) -> i32 {
+        45
+    }
+
+    fn is_left_to_right(&self) -> bool {
+        false
+    }
+
+    fn is_assignment(&self) -> bool {
+        true
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    fn eval(
+        &self,
+        _arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        // Assignments can only be evaluated by the tree, which has access to a mutable configuration
+        Err(Error::ImmutableConfiguration)
+    }
+}
+
+impl Operator for CompoundAssign {
+    fn precedence(&self) -> i32 {
+        45
+    }
+
+    fn is_left_to_right(&self) -> bool {
+        false
+    }
+
+    fn is_assignment(&self) -> bool {
+        true
+    }
+
+    fn compound_assignment_operator(&self) -> Option<&dyn Operator> {
+        Some(self.operator.as_ref())
+    }
+
+    fn argument_amount(&self) -> usize {
+        2
+    }
+
+    fn eval(
+        &self,
+        _arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        Err(Error::ImmutableConfiguration)
+    }
+}
+
 impl Operator for Const {
     fn precedence(&self) -> i32 {
         200
@@ -489,7 +1486,11 @@ impl Operator for Const {
         0
     }
 
-    fn eval(&self, arguments: &[Value], _configuration: &Configuration) -> Result<Value, Error> {
+    fn eval(
+        &self,
+        arguments: &[Value],
+        _configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
         expect_argument_amount(arguments.len(), 0)?;
 
         Ok(self.value.clone())
@@ -501,15 +1502,43 @@ impl Operator for VariableIdentifier {
         200
     }
 
+    fn variable_identifier(&self) -> Option<&str> {
+        Some(&self.identifier)
+    }
+
     fn argument_amount(&self) -> usize {
         0
     }
 
-    fn eval(&self, _arguments: &[Value], configuration: &Configuration) -> Result<Value, Error> {
-        if let Some(value) = configuration.get_value(&self.identifier).cloned() {
+    fn validate(
+        &self,
+        _argument_amount: usize,
+        configuration: &dyn Configuration,
+    ) -> Result<(), Error> {
+        if configuration.get_value(&self.identifier).is_some() {
+            Ok(())
+        } else {
+            Err(Error::variable_identifier_not_found(
+                self.identifier.clone(),
+                self.span.clone(),
+            ))
+        }
+    }
+
+    fn eval(
+        &self,
+        _arguments: &[Value],
+        configuration: &dyn Configuration,
+    ) -> Result<Value, Error> {
+        let value = configuration.get_value(&self.identifier);
+        configuration.on_variable_access(&self.identifier, value);
+        if let Some(value) = value.cloned() {
             Ok(value)
         } else {
-            Err(Error::VariableIdentifierNotFound(self.identifier.clone()))
+            Err(Error::variable_identifier_not_found(
+                self.identifier.clone(),
+                self.span.clone(),
+            ))
         }
     }
 }
@@ -519,16 +1548,47 @@ impl Operator for FunctionIdentifier {
         190
     }
 
+    fn function_identifier(&self) -> Option<&str> {
+        Some(&self.identifier)
+    }
+
     fn argument_amount(&self) -> usize {
         1
     }
 
-    fn eval(&self, arguments: &[Value], configuration: &Configuration) -> Result<Value, Error> {
+    fn validate(
+        &self,
+        argument_amount: usize,
+        configuration: &dyn Configuration,
+    ) -> Result<(), Error> {
+        if let Some(function) = configuration.get_function(&self.identifier) {
+            function.expect_argument_amount(argument_amount)
+        } else if let Some(function) = default_function(&self.identifier) {
+            function.expect_argument_amount(argument_amount)
+        } else if let Some(function) = higher_order_function(&self.identifier) {
+            function.expect_argument_amount(argument_amount)
+        } else {
+            Err(Error::function_identifier_not_found(
+                self.identifier.clone(),
+                self.span.clone(),
+            ))
+        }
+    }
+
+    fn eval(&self, arguments: &[Value], configuration: &dyn Configuration) -> Result<Value, Error> {
+        configuration.on_function_call(&self.identifier, arguments);
         if let Some(function) = configuration.get_function(&self.identifier) {
             // Function::call checks for correct argument amount
             function.call(arguments)
+        } else if let Some(function) = default_function(&self.identifier) {
+            function.call(arguments)
+        } else if let Some(function) = higher_order_function(&self.identifier) {
+            function.call(arguments, configuration)
         } else {
-            Err(Error::FunctionIdentifierNotFound(self.identifier.clone()))
+            Err(Error::function_identifier_not_found(
+                self.identifier.clone(),
+                self.span.clone(),
+            ))
         }
     }
 }
@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+use std::{boxed::Box, string::String, vec::Vec};
+
+use super::*;
+
+/// A value as it is stored in a serialized operator tree.
+/// Unlike the serde representation of `Value` itself, this keeps tuples apart from arrays and does not require a self-describing format.
+#[derive(Serialize, Deserialize)]
+pub(crate) enum SerializedValue {
+    String(String),
+    Float(FloatType),
+    Int(IntType),
+    Boolean(bool),
+    Array(Vec<SerializedValue>),
+    Tuple(Vec<SerializedValue>),
+    Object(Vec<(String, SerializedValue)>),
+    Null,
+}
+
+impl From<&Value> for SerializedValue {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::String(string) => SerializedValue::String(string.clone()),
+            Value::Float(float) => SerializedValue::Float(*float),
+            Value::Int(int) => SerializedValue::Int(*int),
+            Value::Boolean(boolean) => SerializedValue::Boolean(*boolean),
+            Value::Array(array) => SerializedValue::Array(array.iter().map(Into::into).collect()),
+            Value::Tuple(tuple) => SerializedValue::Tuple(tuple.iter().map(Into::into).collect()),
+            Value::Object(object) => SerializedValue::Object(
+                object
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.into()))
+                    .collect(),
+            ),
+            Value::Null => SerializedValue::Null,
+        }
+    }
+}
+
+impl From<SerializedValue> for Value {
+    fn from(value: SerializedValue) -> Self {
+        match value {
+            SerializedValue::String(string) => Value::String(string),
+            SerializedValue::Float(float) => Value::Float(float),
+            SerializedValue::Int(int) => Value::Int(int),
+            SerializedValue::Boolean(boolean) => Value::Boolean(boolean),
+            SerializedValue::Array(array) => {
+                Value::Array(array.into_iter().map(Into::into).collect())
+            }
+            SerializedValue::Tuple(tuple) => {
+                Value::Tuple(tuple.into_iter().map(Into::into).collect())
+            }
+            SerializedValue::Object(object) => Value::Object(
+                object
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect(),
+            ),
+            SerializedValue::Null => Value::Null,
+        }
+    }
+}
+
+/// Defines `SerializedOperator` with a variant for each of the given operators without fields.
+/// Binary formats identify variants by their index, so new operators must be appended to the end of the list,
+/// and new operators with fields must be added after it.
+macro_rules! serialized_operator {
+    ($($operator:ident),* $(,)?) => {
+        /// An operator as it is stored in a serialized operator tree.
+        #[derive(Serialize, Deserialize)]
+        pub(crate) enum SerializedOperator {
+            Const(SerializedValue),
+            VariableIdentifier {
+                identifier: String,
+                span: Option<Span>,
+            },
+            FunctionIdentifier {
+                identifier: String,
+                span: Option<Span>,
+            },
+            CompoundAssign(Box<SerializedOperator>),
+            $($operator,)*
+            Let {
+                identifier: String,
+            },
+            NamedArg {
+                name: String,
+            },
+        }
+
+        impl SerializedOperator {
+            /// Returns the serialized representation of the given operator, or `None` if the operator is not known to this module.
+            pub(crate) fn new(operator: &dyn Operator) -> Option<Self> {
+                let operator = operator.as_any();
+                if let Some(operator) = operator.downcast_ref::<Const>() {
+                    Some(SerializedOperator::Const((&operator.value).into()))
+                } else if let Some(operator) = operator.downcast_ref::<VariableIdentifier>() {
+                    Some(SerializedOperator::VariableIdentifier {
+                        identifier: operator.identifier.clone(),
+                        span: operator.span.clone(),
+                    })
+                } else if let Some(operator) = operator.downcast_ref::<FunctionIdentifier>() {
+                    Some(SerializedOperator::FunctionIdentifier {
+                        identifier: operator.identifier.clone(),
+                        span: operator.span.clone(),
+                    })
+                } else if let Some(operator) = operator.downcast_ref::<CompoundAssign>() {
+                    SerializedOperator::new(operator.operator.as_ref())
+                        .map(|operator| SerializedOperator::CompoundAssign(Box::new(operator)))
+                } else if let Some(operator) = operator.downcast_ref::<Let>() {
+                    Some(SerializedOperator::Let {
+                        identifier: operator.identifier.clone(),
+                    })
+                } else if let Some(operator) = operator.downcast_ref::<NamedArg>() {
+                    Some(SerializedOperator::NamedArg {
+                        name: operator.name.clone(),
+                    })
+                }
+                $(else if operator.is::<$operator>() {
+                    Some(SerializedOperator::$operator)
+                })*
+                else {
+                    None
+                }
+            }
+
+            pub(crate) fn into_operator(self) -> Box<dyn Operator> {
+                match self {
+                    SerializedOperator::Const(value) => Box::new(Const::new(value.into())),
+                    SerializedOperator::VariableIdentifier { identifier, span } => {
+                        Box::new(VariableIdentifier::new(identifier, span))
+                    }
+                    SerializedOperator::FunctionIdentifier { identifier, span } => {
+                        Box::new(FunctionIdentifier::new(identifier, span))
+                    }
+                    SerializedOperator::CompoundAssign(operator) => Box::new(CompoundAssign {
+                        operator: operator.into_operator(),
+                    }),
+                    $(SerializedOperator::$operator => Box::new($operator),)*
+                    SerializedOperator::Let { identifier } => Box::new(Let::new(identifier)),
+                    SerializedOperator::NamedArg { name } => Box::new(NamedArg::new(name)),
+                }
+            }
+        }
+    };
+}
+
+serialized_operator!(
+    RootNode,
+    Add,
+    Sub,
+    Neg,
+    Mul,
+    Div,
+    Mod,
+    Exp,
+    Eq,
+    Neq,
+    Gt,
+    Lt,
+    Geq,
+    Leq,
+    And,
+    Or,
+    Xor,
+    Not,
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    Shl,
+    Shr,
+    Ternary,
+    IfElse,
+    NullCoalesce,
+    In,
+    Range,
+    Index,
+    Comma,
+    Spread,
+    Sequence,
+    Assign,
+    IfError,
+);
@@ -1,7 +1,22 @@
 use error::Error;
+use std::{
+    num::ParseIntError,
+    ops::Range,
+    str::CharIndices,
+    string::{String, ToString},
+    vec::Vec,
+};
 use value::{FloatType, IntType};
 
+/// The position of a token in the source string, as a range of byte offsets.
+pub type Span = Range<usize>;
+
+/// A token of an expression, as returned by `tokenize`.
+/// Operators that are written with several characters, such as `**` or `&&`, are single tokens.
+/// Keywords are resolved as well, such that `and` is `Token::And` like `&&`, and `true` is `Token::Boolean(true)`.
+/// More variants may be added when the syntax is extended.
 #[derive(Clone, PartialEq, Debug)]
+#[non_exhaustive]
 pub enum Token {
     // Single character tokens
     // Arithmetic
@@ -10,6 +25,7 @@ pub enum Token {
     Star,
     Slash,
     Percent,
+    DoubleStar,
 
     // Logic
     Eq,
@@ -20,17 +36,61 @@ pub enum Token {
     Leq,
     And,
     Or,
+    Xor,
     Not,
 
+    // Bitwise
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    Shl,
+    Shr,
+
     // Precedence
     LBrace,
     RBrace,
+    LBracket,
+    RBracket,
+
+    // Conditional
+    QuestionMark,
+    Colon,
+    DoubleQuestionMark,
+    If,
+    Else,
+    LCurlyBrace,
+    RCurlyBrace,
+
+    // Bindings
+    Let,
+
+    // Membership
+    In,
+
+    // Ranges
+    DoubleDot,
+
+    // Function arguments
+    Comma,
+    Spread,
+
+    // Statements
+    Semicolon,
+    Assign,
+    PlusAssign,
+    MinusAssign,
+    StarAssign,
+    SlashAssign,
+    PercentAssign,
 
     // Complex tokens
     Identifier(String),
     Float(FloatType),
     Int(IntType),
     Boolean(bool),
+    String(String),
+    Null,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -38,12 +98,15 @@ pub enum PartialToken {
     Token(Token),
     Literal(String),
     Whitespace,
+    Star,
+    QuestionMark,
     Eq,
     ExclamationMark,
     Gt,
     Lt,
     Ampersand,
     VerticalBar,
+    Caret,
 }
 
 // Make this a const fn as soon as match gets stable (issue #57563)
@@ -51,7 +114,7 @@ fn char_to_partial_token(c: char) -> PartialToken {
     match c {
         '+' => PartialToken::Token(Token::Plus),
         '-' => PartialToken::Token(Token::Minus),
-        '*' => PartialToken::Token(Token::Star),
+        '*' => PartialToken::Star,
         '/' => PartialToken::Token(Token::Slash),
         '%' => PartialToken::Token(Token::Percent),
 
@@ -61,9 +124,20 @@ fn char_to_partial_token(c: char) -> PartialToken {
         '<' => PartialToken::Lt,
         '&' => PartialToken::Ampersand,
         '|' => PartialToken::VerticalBar,
+        '^' => PartialToken::Caret,
+        '~' => PartialToken::Token(Token::BitNot),
 
         '(' => PartialToken::Token(Token::LBrace),
         ')' => PartialToken::Token(Token::RBrace),
+        '[' => PartialToken::Token(Token::LBracket),
+        ']' => PartialToken::Token(Token::RBracket),
+
+        '?' => PartialToken::QuestionMark,
+        ':' => PartialToken::Token(Token::Colon),
+        '{' => PartialToken::Token(Token::LCurlyBrace),
+        '}' => PartialToken::Token(Token::RCurlyBrace),
+        ',' => PartialToken::Token(Token::Comma),
+        ';' => PartialToken::Token(Token::Semicolon),
 
         c => {
             if c.is_whitespace() {
@@ -77,13 +151,14 @@ fn char_to_partial_token(c: char) -> PartialToken {
 
 impl Token {
     // Make this a const fn as soon as match gets stable (issue #57563)
-    pub fn is_leftsided_value(&self) -> bool {
+    pub(crate) fn is_leftsided_value(&self) -> bool {
         match self {
             Token::Plus => false,
             Token::Minus => false,
             Token::Star => false,
             Token::Slash => false,
             Token::Percent => false,
+            Token::DoubleStar => false,
 
             Token::Eq => false,
             Token::Neq => false,
@@ -93,26 +168,64 @@ impl Token {
             Token::Leq => false,
             Token::And => false,
             Token::Or => false,
+            Token::Xor => false,
             Token::Not => false,
 
+            Token::BitAnd => false,
+            Token::BitOr => false,
+            Token::BitXor => false,
+            Token::BitNot => false,
+            Token::Shl => false,
+            Token::Shr => false,
+
             Token::LBrace => true,
             Token::RBrace => false,
+            Token::LBracket => false,
+            Token::RBracket => false,
+
+            Token::QuestionMark => false,
+            Token::Colon => false,
+            Token::DoubleQuestionMark => false,
+            Token::If => false,
+            Token::Else => false,
+            Token::LCurlyBrace => false,
+            Token::RCurlyBrace => false,
+
+            Token::Let => false,
+
+            Token::In => false,
+
+            Token::DoubleDot => false,
+
+            Token::Comma => false,
+            Token::Spread => false,
+
+            Token::Semicolon => false,
+            Token::Assign => false,
+            Token::PlusAssign => false,
+            Token::MinusAssign => false,
+            Token::StarAssign => false,
+            Token::SlashAssign => false,
+            Token::PercentAssign => false,
 
             Token::Identifier(_) => true,
             Token::Float(_) => true,
             Token::Int(_) => true,
             Token::Boolean(_) => true,
+            Token::String(_) => true,
+            Token::Null => true,
         }
     }
 
     // Make this a const fn as soon as match gets stable (issue #57563)
-    pub fn is_rightsided_value(&self) -> bool {
+    pub(crate) fn is_rightsided_value(&self) -> bool {
         match self {
             Token::Plus => false,
             Token::Minus => false,
             Token::Star => false,
             Token::Slash => false,
             Token::Percent => false,
+            Token::DoubleStar => false,
 
             Token::Eq => false,
             Token::Neq => false,
@@ -122,114 +235,436 @@ impl Token {
             Token::Leq => false,
             Token::And => false,
             Token::Or => false,
+            Token::Xor => false,
             Token::Not => false,
 
+            Token::BitAnd => false,
+            Token::BitOr => false,
+            Token::BitXor => false,
+            Token::BitNot => false,
+            Token::Shl => false,
+            Token::Shr => false,
+
             Token::LBrace => false,
             Token::RBrace => true,
+            Token::LBracket => false,
+            Token::RBracket => true,
+
+            Token::QuestionMark => false,
+            Token::Colon => false,
+            Token::DoubleQuestionMark => false,
+            // The tree builder consumes the whole if-else chain at once, which ends with a block
+            Token::If => true,
+            Token::Else => false,
+            Token::LCurlyBrace => false,
+            Token::RCurlyBrace => true,
+
+            // The tree builder consumes the whole let expression at once, which ends with its body
+            Token::Let => true,
+
+            Token::In => false,
+
+            Token::DoubleDot => false,
+
+            Token::Comma => false,
+            Token::Spread => false,
+
+            Token::Semicolon => false,
+            Token::Assign => false,
+            Token::PlusAssign => false,
+            Token::MinusAssign => false,
+            Token::StarAssign => false,
+            Token::SlashAssign => false,
+            Token::PercentAssign => false,
 
             Token::Identifier(_) => true,
             Token::Float(_) => true,
             Token::Int(_) => true,
             Token::Boolean(_) => true,
+            Token::String(_) => true,
+            Token::Null => true,
         }
     }
 }
 
-/// Converts a string to a vector of partial tokens.
-fn str_to_tokens(string: &str) -> Vec<PartialToken> {
-    let mut result = Vec::new();
-    for c in string.chars() {
+/// True if the literal is a decimal number followed by an exponent marker `e` or `E`, such as `1.5e`.
+fn is_number_with_exponent_marker(literal: &str) -> bool {
+    if let Some(mantissa) = literal
+        .strip_suffix('e')
+        .or_else(|| literal.strip_suffix('E'))
+    {
+        mantissa.starts_with(|c: char| c.is_ascii_digit())
+            && mantissa.chars().all(|c| c.is_ascii_digit() || c == '.')
+    } else {
+        false
+    }
+}
+
+/// Converts a string to a vector of partial tokens together with their spans.
+fn str_to_tokens(string: &str) -> Result<Vec<(PartialToken, Span)>, Error> {
+    let mut result: Vec<(PartialToken, Span)> = Vec::new();
+    let mut chars = string.char_indices();
+    while let Some((offset, c)) = chars.next() {
+        if c == '"' || c == '\'' {
+            let (content, span) = read_string_literal(&mut chars, c, offset)?;
+            result.push((PartialToken::Token(Token::String(content)), span));
+            continue;
+        }
+
+        if string[offset..].starts_with("...") {
+            result.push((PartialToken::Token(Token::Spread), offset..offset + 3));
+            chars.nth(1);
+            continue;
+        }
+        if string[offset..].starts_with("..") {
+            result.push((PartialToken::Token(Token::DoubleDot), offset..offset + 2));
+            chars.next();
+            continue;
+        }
+
         let partial_token = char_to_partial_token(c);
+        let span = offset..offset + c.len_utf8();
 
-        let if_let_successful =
-            if let (Some(PartialToken::Literal(last)), PartialToken::Literal(literal)) =
-                (result.last_mut(), &partial_token)
-            {
-                last.push_str(literal);
-                true
-            } else {
-                false
+        if let Some((PartialToken::Literal(last), last_span)) = result.last_mut() {
+            let is_continued_literal = match &partial_token {
+                PartialToken::Literal(_) => true,
+                // The sign of the exponent of a float in scientific notation
+                PartialToken::Token(Token::Plus) | PartialToken::Token(Token::Minus) => {
+                    is_number_with_exponent_marker(last)
+                }
+                _ => false,
             };
 
-        if !if_let_successful {
-            result.push(partial_token);
+            if is_continued_literal {
+                last.push(c);
+                last_span.end = span.end;
+                continue;
+            }
+        }
+
+        result.push((partial_token, span));
+    }
+    Ok(result)
+}
+
+/// Reads the content of a string literal up to the closing quote, replacing escape sequences by the characters they stand for.
+/// The opening quote at the given offset was already consumed.
+/// Returns the content of the literal and the span of the whole literal including quotes.
+fn read_string_literal(
+    chars: &mut CharIndices,
+    quote: char,
+    offset: usize,
+) -> Result<(String, Span), Error> {
+    let mut content = String::new();
+    while let Some((end, c)) = chars.next() {
+        if c == quote {
+            return Ok((content, offset..end + c.len_utf8()));
+        }
+        if c == '\\' && !chars.as_str().is_empty() {
+            content.push(read_escape_sequence(chars, end)?);
+        } else {
+            content.push(c);
+        }
+    }
+    Err(Error::UnmatchedQuote {
+        span: Some(offset..offset + quote.len_utf8()),
+    })
+}
+
+/// Reads an escape sequence of a string literal and returns the character it stands for.
+/// The backslash at the given offset was already consumed.
+/// Supported are `\n`, `\t`, `\r`, `\\`, `\"`, `\'`, and unicode scalar values as `\uXXXX` with four hexadecimal digits or `\u{X}` with one to six.
+fn read_escape_sequence(chars: &mut CharIndices, offset: usize) -> Result<char, Error> {
+    let (character_offset, character) = chars.next().ok_or(Error::UnmatchedQuote { span: None })?;
+    let escaped = match character {
+        'n' => '\n',
+        't' => '\t',
+        'r' => '\r',
+        '\\' | '"' | '\'' => character,
+        'u' => return read_unicode_escape(chars, offset),
+        _ => {
+            return Err(Error::UnknownEscapeSequence {
+                character,
+                span: Some(offset..character_offset + character.len_utf8()),
+            })
+        }
+    };
+    Ok(escaped)
+}
+
+/// Reads the hexadecimal code of a `\u` escape sequence and returns the unicode scalar value it stands for.
+/// The backslash at the given offset and the `u` were already consumed.
+fn read_unicode_escape(chars: &mut CharIndices, offset: usize) -> Result<char, Error> {
+    let mut digits = String::new();
+    let mut end = offset + 2;
+    let is_braced = chars.as_str().starts_with('{');
+    if is_braced {
+        chars.next();
+        end += 1;
+    }
+
+    loop {
+        let is_complete = if is_braced {
+            chars.as_str().starts_with('}')
+        } else {
+            digits.len() == 4
+        };
+        if is_complete {
+            break;
+        }
+        match chars.clone().next() {
+            Some((_, digit)) if digit.is_ascii_hexdigit() && digits.len() < 6 => {
+                chars.next();
+                digits.push(digit);
+                end += 1;
+            }
+            _ => {
+                return Err(Error::InvalidUnicodeEscape {
+                    span: Some(offset..end),
+                })
+            }
         }
     }
-    result
+    if is_braced {
+        chars.next();
+        end += 1;
+    }
+
+    u32::from_str_radix(&digits, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .ok_or(Error::InvalidUnicodeEscape {
+            span: Some(offset..end),
+        })
+}
+
+/// Parses integer literals with a hexadecimal (`0x`), octal (`0o`) or binary (`0b`) prefix.
+/// Returns `None` if the literal has none of these prefixes.
+fn parse_prefixed_int(literal: &str) -> Option<Result<IntType, ParseIntError>> {
+    let radix = match literal.get(..2)? {
+        "0x" | "0X" => 16,
+        "0o" | "0O" => 8,
+        "0b" | "0B" => 2,
+        _ => return None,
+    };
+    Some(IntType::from_str_radix(&literal[2..], radix))
 }
 
 /// Resolves all partial tokens by converting them to complex tokens.
-fn resolve_literals(mut tokens: &[PartialToken]) -> Result<Vec<Token>, Error> {
+fn resolve_literals(mut tokens: &[(PartialToken, Span)]) -> Result<Vec<(Token, Span)>, Error> {
     let mut result = Vec::new();
-    while tokens.len() > 0 {
-        let first = tokens[0].clone();
-        let second = tokens.get(1).cloned();
+    while !tokens.is_empty() {
+        let (first, first_span) = tokens[0].clone();
+        let (second, second_span) = match tokens.get(1).cloned() {
+            Some((second, second_span)) => (Some(second), Some(second_span)),
+            None => (None, None),
+        };
         let mut cutoff = 2;
+        let span = first_span.start..second_span.map_or(first_span.end, |span| span.end);
 
-        result.extend(
-            match first {
-                PartialToken::Token(token) => {
+        let token = match first {
+            PartialToken::Token(token) => match (token, second) {
+                (Token::Plus, Some(PartialToken::Eq)) => Some(Token::PlusAssign),
+                (Token::Minus, Some(PartialToken::Eq)) => Some(Token::MinusAssign),
+                (Token::Slash, Some(PartialToken::Eq)) => Some(Token::SlashAssign),
+                (Token::Percent, Some(PartialToken::Eq)) => Some(Token::PercentAssign),
+                (token, _) => {
                     cutoff = 1;
                     Some(token)
                 }
-                PartialToken::Literal(literal) => {
+            },
+            PartialToken::Literal(literal) => {
+                cutoff = 1;
+                if let Some(number) = parse_prefixed_int(&literal) {
+                    Some(Token::Int(number.map_err(|error| {
+                        Error::parse_int_error(error, first_span.clone())
+                    })?))
+                } else if let Ok(number) = literal.parse::<IntType>() {
+                    Some(Token::Int(number))
+                } else if let Ok(number) = literal.parse::<FloatType>() {
+                    Some(Token::Float(number))
+                } else if let Ok(boolean) = literal.parse::<bool>() {
+                    Some(Token::Boolean(boolean))
+                } else if literal == "null" {
+                    Some(Token::Null)
+                } else if literal == "in" {
+                    Some(Token::In)
+                } else if literal == "xor" {
+                    Some(Token::Xor)
+                } else if literal == "not" {
+                    Some(Token::Not)
+                } else if literal == "and" {
+                    Some(Token::And)
+                } else if literal == "or" {
+                    Some(Token::Or)
+                } else if literal == "if" {
+                    Some(Token::If)
+                } else if literal == "else" {
+                    Some(Token::Else)
+                } else if literal == "let" {
+                    Some(Token::Let)
+                } else {
+                    Some(Token::Identifier(literal.to_string()))
+                }
+            }
+            PartialToken::Whitespace => {
+                cutoff = 1;
+                None
+            }
+            PartialToken::Star => match second {
+                Some(PartialToken::Star) => Some(Token::DoubleStar),
+                Some(PartialToken::Eq) => Some(Token::StarAssign),
+                _ => {
                     cutoff = 1;
-                    if let Ok(number) = literal.parse::<IntType>() {
-                        Some(Token::Int(number))
-                    } else if let Ok(number) = literal.parse::<FloatType>() {
-                        Some(Token::Float(number))
-                    } else if let Ok(boolean) = literal.parse::<bool>() {
-                        Some(Token::Boolean(boolean))
-                    } else {
-                        Some(Token::Identifier(literal.to_string()))
-                    }
+                    Some(Token::Star)
                 }
-                PartialToken::Whitespace => {
+            },
+            PartialToken::QuestionMark => match second {
+                Some(PartialToken::QuestionMark) => Some(Token::DoubleQuestionMark),
+                _ => {
                     cutoff = 1;
-                    None
+                    Some(Token::QuestionMark)
                 }
-                PartialToken::Eq => match second {
-                    Some(PartialToken::Eq) => Some(Token::Eq),
-                    _ => return Err(Error::unmatched_partial_token(first, second)),
-                },
-                PartialToken::ExclamationMark => match second {
-                    Some(PartialToken::Eq) => Some(Token::Eq),
-                    _ => {
-                        cutoff = 1;
-                        Some(Token::Not)
-                    }
-                },
-                PartialToken::Gt => match second {
-                    Some(PartialToken::Eq) => Some(Token::Geq),
-                    _ => {
-                        cutoff = 1;
-                        Some(Token::Gt)
-                    }
-                },
-                PartialToken::Lt => match second {
-                    Some(PartialToken::Eq) => Some(Token::Leq),
-                    _ => {
-                        cutoff = 1;
-                        Some(Token::Lt)
-                    }
-                },
-                PartialToken::Ampersand => match second {
-                    Some(PartialToken::Ampersand) => Some(Token::And),
-                    _ => return Err(Error::unmatched_partial_token(first, second)),
-                },
-                PartialToken::VerticalBar => match second {
-                    Some(PartialToken::VerticalBar) => Some(Token::Or),
-                    _ => return Err(Error::unmatched_partial_token(first, second)),
-                },
-            }
-            .into_iter(),
-        );
+            },
+            PartialToken::Eq => match second {
+                Some(PartialToken::Eq) => Some(Token::Eq),
+                _ => {
+                    cutoff = 1;
+                    Some(Token::Assign)
+                }
+            },
+            PartialToken::ExclamationMark => match second {
+                Some(PartialToken::Eq) => Some(Token::Eq),
+                _ => {
+                    cutoff = 1;
+                    Some(Token::Not)
+                }
+            },
+            PartialToken::Gt => match second {
+                Some(PartialToken::Eq) => Some(Token::Geq),
+                Some(PartialToken::Gt) => Some(Token::Shr),
+                _ => {
+                    cutoff = 1;
+                    Some(Token::Gt)
+                }
+            },
+            PartialToken::Lt => match second {
+                Some(PartialToken::Eq) => Some(Token::Leq),
+                Some(PartialToken::Lt) => Some(Token::Shl),
+                _ => {
+                    cutoff = 1;
+                    Some(Token::Lt)
+                }
+            },
+            PartialToken::Ampersand => match second {
+                Some(PartialToken::Ampersand) => Some(Token::And),
+                _ => {
+                    cutoff = 1;
+                    Some(Token::BitAnd)
+                }
+            },
+            PartialToken::VerticalBar => match second {
+                Some(PartialToken::VerticalBar) => Some(Token::Or),
+                _ => {
+                    cutoff = 1;
+                    Some(Token::BitOr)
+                }
+            },
+            PartialToken::Caret => match second {
+                Some(PartialToken::Caret) => Some(Token::Xor),
+                _ => {
+                    cutoff = 1;
+                    Some(Token::BitXor)
+                }
+            },
+        };
 
+        if let Some(token) = token {
+            let span = if cutoff == 1 { first_span } else { span };
+            result.push((token, span));
+        }
         tokens = &tokens[cutoff..];
     }
     Ok(result)
 }
 
-pub fn tokenize(string: &str) -> Result<Vec<Token>, Error> {
-    resolve_literals(&str_to_tokens(string))
+/// Converts a string to a vector of tokens together with their spans in the string.
+/// Replaces all comments in the string with whitespace.
+/// Comment markers inside of string literals are ignored.
+/// Line comments start with `//` and end at the next line break.
+/// Block comments start with `/*`, end with `*/` and may be nested.
+/// The replacement has the same length in bytes as the comment, such that spans of later tokens stay valid.
+fn remove_comments(string: &str) -> Result<String, Error> {
+    let mut result = String::with_capacity(string.len());
+    // The start offsets of the currently open block comments.
+    let mut open_block_comments = Vec::new();
+    let mut is_in_line_comment = false;
+    // The quote character of the currently open string literal.
+    let mut open_string_quote = None;
+    let mut chars = string.char_indices().peekable();
+
+    while let Some((offset, c)) = chars.next() {
+        let next = chars.peek().map(|(_, next)| *next);
+
+        if let Some(quote) = open_string_quote {
+            if c == quote {
+                open_string_quote = None;
+            } else if c == '\\' {
+                // The escaped character cannot close the string literal
+                if let Some((_, escaped)) = chars.next() {
+                    result.push(c);
+                    result.push(escaped);
+                    continue;
+                }
+            }
+            result.push(c);
+            continue;
+        } else if is_in_line_comment {
+            if c == '\n' {
+                is_in_line_comment = false;
+                result.push(c);
+                continue;
+            }
+        } else if c == '/' && next == Some('*') {
+            open_block_comments.push(offset);
+            chars.next();
+            result.push_str("  ");
+            continue;
+        } else if !open_block_comments.is_empty() {
+            if c == '*' && next == Some('/') {
+                open_block_comments.pop();
+                chars.next();
+                result.push_str("  ");
+                continue;
+            }
+        } else if c == '/' && next == Some('/') {
+            is_in_line_comment = true;
+        } else if c == '"' || c == '\'' {
+            open_string_quote = Some(c);
+            result.push(c);
+            continue;
+        } else {
+            result.push(c);
+            continue;
+        }
+
+        // Inside of a comment
+        for _ in 0..c.len_utf8() {
+            result.push(' ');
+        }
+    }
+
+    if let Some(offset) = open_block_comments.pop() {
+        Err(Error::UnmatchedComment {
+            span: Some(offset..offset + 2),
+        })
+    } else {
+        Ok(result)
+    }
+}
+
+/// Splits the given expression into tokens, each with its position in the expression.
+/// Whitespace and comments are skipped.
+pub fn tokenize(string: &str) -> Result<Vec<(Token, Span)>, Error> {
+    resolve_literals(&str_to_tokens(&remove_comments(string)?)?)
 }
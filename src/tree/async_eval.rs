@@ -0,0 +1,190 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use configuration::{AsyncConfiguration, Configuration};
+use error::Error;
+use function::{BoxFuture, Function};
+use tree::Node;
+use value::Value;
+
+/// A call of an async function, identified by the function identifier and the arguments.
+type AsyncCall = (String, Vec<Value>);
+
+/// The results of the async calls that were awaited so far, shared with the functions that stand in for the async functions.
+///
+/// The results are stored in the order of the calls, because each evaluation makes the same calls in the same order until it reaches a call whose result is not known yet.
+/// Keying them by their arguments would fail for arguments that are not equal to themselves, such as `NaN`.
+#[derive(Default)]
+struct AsyncCalls {
+    results: Vec<Value>,
+    /// The index of the next async call of the current evaluation.
+    next: usize,
+    /// The first call of the current evaluation whose result is not known yet.
+    missing: Option<AsyncCall>,
+}
+
+/// The future returned by `eval_async` and `Node::eval_async`.
+///
+/// The expression is evaluated synchronously until it calls an async function whose result is not known yet.
+/// Then the evaluation is stopped, the function is awaited, and the expression is evaluated again from the start with the result.
+/// Therefore, each async call of the expression is awaited once, but synchronous functions may be called more than once.
+/// Async functions cannot be passed by name to higher-order functions such as `map`.
+pub struct EvalAsync<'a> {
+    node: Result<Cow<'a, Node>, Option<Error>>,
+    configuration: &'a dyn AsyncConfiguration,
+    calls: Arc<Mutex<AsyncCalls>>,
+    functions: HashMap<String, Function>,
+    pending: Option<BoxFuture<'static, Result<Value, Error>>>,
+}
+
+impl<'a> EvalAsync<'a> {
+    pub(crate) fn new(
+        node: Result<Cow<'a, Node>, Error>,
+        configuration: &'a dyn AsyncConfiguration,
+    ) -> Self {
+        let calls = Arc::new(Mutex::new(AsyncCalls::default()));
+        let mut functions = HashMap::new();
+        if let Ok(node) = &node {
+            for identifier in node.list_functions() {
+                if let Some(function) = configuration.get_async_function(&identifier) {
+                    let stand_in =
+                        stand_in_function(&identifier, function.argument_amount(), &calls);
+                    functions.insert(identifier, stand_in);
+                }
+            }
+        }
+
+        Self {
+            node: node.map_err(Some),
+            configuration,
+            calls,
+            functions,
+            pending: None,
+        }
+    }
+}
+
+/// Creates a synchronous function that returns the result of the given async function if it is known,
+/// and records the call as missing otherwise.
+fn stand_in_function(
+    identifier: &str,
+    argument_amount: usize,
+    calls: &Arc<Mutex<AsyncCalls>>,
+) -> Function {
+    let identifier = identifier.to_string();
+    let calls = calls.clone();
+    Function::new(
+        argument_amount,
+        Box::new(move |arguments| {
+            let mut calls = calls.lock().unwrap();
+            let index = calls.next;
+            calls.next += 1;
+            if let Some(result) = calls.results.get(index) {
+                return Ok(result.clone());
+            }
+            if calls.missing.is_none() {
+                calls.missing = Some((identifier.clone(), arguments.to_vec()));
+            }
+            // The evaluation is repeated once the result is known, so this error is never returned
+            Err(Error::function_identifier_not_found(
+                identifier.clone(),
+                None,
+            ))
+        }),
+    )
+}
+
+impl<'a> Future for EvalAsync<'a> {
+    type Output = Result<Value, Error>;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let node = match &mut this.node {
+            Ok(node) => node,
+            Err(error) => {
+                return Poll::Ready(Err(error
+                    .take()
+                    .expect("EvalAsync polled after completion")))
+            }
+        };
+
+        loop {
+            if let Some(future) = &mut this.pending {
+                match future.as_mut().poll(context) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Err(error)) => return Poll::Ready(Err(error)),
+                    Poll::Ready(Ok(value)) => {
+                        this.calls.lock().unwrap().results.push(value);
+                        this.pending = None;
+                    }
+                }
+            }
+
+            this.calls.lock().unwrap().next = 0;
+            let result = node.eval(&StandInConfiguration {
+                configuration: this.configuration,
+                functions: &this.functions,
+            });
+            let missing = this.calls.lock().unwrap().missing.take();
+            match missing {
+                Some((identifier, arguments)) => {
+                    let function = this
+                        .configuration
+                        .get_async_function(&identifier)
+                        .expect("stand-in functions are only created for existing async functions");
+                    this.pending = Some(function.call(arguments));
+                }
+                None => return Poll::Ready(result),
+            }
+        }
+    }
+}
+
+/// Wraps an async configuration such that its async functions are replaced by their stand-in functions.
+/// Synchronous functions take precedence over async functions with the same identifier.
+struct StandInConfiguration<'a> {
+    configuration: &'a dyn AsyncConfiguration,
+    functions: &'a HashMap<String, Function>,
+}
+
+impl<'a> Configuration for StandInConfiguration<'a> {
+    fn get_value(&self, identifier: &str) -> Option<&Value> {
+        self.configuration.get_value(identifier)
+    }
+
+    fn get_function(&self, identifier: &str) -> Option<&Function> {
+        self.configuration
+            .get_function(identifier)
+            .or_else(|| self.functions.get(identifier))
+    }
+
+    fn max_depth(&self) -> Option<usize> {
+        self.configuration.max_depth()
+    }
+
+    fn max_node_count(&self) -> Option<usize> {
+        self.configuration.max_node_count()
+    }
+
+    fn max_string_length(&self) -> Option<usize> {
+        self.configuration.max_string_length()
+    }
+
+    fn max_array_length(&self) -> Option<usize> {
+        self.configuration.max_array_length()
+    }
+
+    fn on_variable_access(&self, identifier: &str, value: Option<&Value>) {
+        self.configuration.on_variable_access(identifier, value)
+    }
+
+    fn on_function_call(&self, identifier: &str, arguments: &[Value]) {
+        self.configuration.on_function_call(identifier, arguments)
+    }
+}
@@ -0,0 +1,66 @@
+use std::{string::String, vec::Vec};
+
+use configuration::Configuration;
+use error::Error;
+use tree::CompiledExpr;
+use value::Value;
+
+/// A compiled expression that remembers the result of its last evaluation, together with the values of the variables it read.
+/// If it is evaluated again with the same values of these variables, the remembered result is returned without evaluating the expression.
+///
+/// This is only correct for pure expressions, whose result depends on nothing but their variables.
+/// Functions are not called again if the variables did not change, and the configuration is not notified of variable accesses and function calls of cached results.
+/// Failed evaluations are not cached.
+#[derive(Clone, Debug)]
+pub struct CachedExpr {
+    expression: CompiledExpr,
+    /// The variables read by the expression.
+    variables: Vec<String>,
+    /// The values of the variables at the last successful evaluation, in the order of `variables`, and its result.
+    cached: Option<(Vec<Option<Value>>, Value)>,
+}
+
+impl CachedExpr {
+    pub fn new(expression: CompiledExpr) -> Self {
+        Self {
+            variables: expression.node().list_variables(),
+            expression,
+            cached: None,
+        }
+    }
+
+    /// Evaluates the expression with the given configuration, unless the variables read by the expression have the same values as at the last evaluation.
+    /// In that case, the result of the last evaluation is returned.
+    pub fn eval(&mut self, configuration: &dyn Configuration) -> Result<Value, Error> {
+        let snapshot: Vec<Option<Value>> = self
+            .variables
+            .iter()
+            .map(|identifier| configuration.get_value(identifier).cloned())
+            .collect();
+        if let Some((cached_snapshot, value)) = &self.cached {
+            if *cached_snapshot == snapshot {
+                return Ok(value.clone());
+            }
+        }
+
+        let value = self.expression.eval(configuration)?;
+        self.cached = Some((snapshot, value.clone()));
+        Ok(value)
+    }
+
+    /// Forgets the result of the last evaluation, such that the expression is evaluated again the next time.
+    pub fn invalidate(&mut self) {
+        self.cached = None;
+    }
+
+    /// Returns the wrapped compiled expression.
+    pub fn compiled(&self) -> &CompiledExpr {
+        &self.expression
+    }
+}
+
+impl From<CompiledExpr> for CachedExpr {
+    fn from(expression: CompiledExpr) -> Self {
+        Self::new(expression)
+    }
+}
@@ -0,0 +1,45 @@
+use std::fmt::{Display, Error as FmtError, Formatter};
+
+use configuration::{Configuration, MutableConfiguration};
+use error::Error;
+use tree::Node;
+use value::Value;
+
+/// A parsed expression that can be evaluated many times without being parsed again.
+///
+/// Create it with `compile`.
+#[derive(Clone, Debug)]
+pub struct CompiledExpr {
+    node: Node,
+}
+
+impl CompiledExpr {
+    pub(crate) fn new(node: Node) -> Self {
+        Self { node }
+    }
+
+    /// Evaluates the expression with the given configuration.
+    pub fn eval(&self, configuration: &dyn Configuration) -> Result<Value, Error> {
+        self.node.eval(configuration)
+    }
+
+    /// Evaluates the expression with the given configuration, and writes all variables assigned by the expression to the configuration.
+    pub fn eval_with_mutable_configuration(
+        &self,
+        configuration: &mut dyn MutableConfiguration,
+    ) -> Result<Value, Error> {
+        self.node.eval_with_mutable_configuration(configuration)
+    }
+
+    /// Returns the operator tree of the expression.
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+}
+
+/// Writes the expression in the canonical form of `Node`.
+impl Display for CompiledExpr {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), FmtError> {
+        write!(f, "{}", self.node)
+    }
+}
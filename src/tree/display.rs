@@ -0,0 +1,88 @@
+use std::{
+    fmt::{Display, Error, Formatter},
+    vec::Vec,
+};
+
+use tree::Node;
+
+/// Writes the expression fully parenthesized, such that parsing the result yields an equivalent tree.
+impl Display for Node {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        let operator = self.operator();
+        let children = self.children();
+
+        if operator.is_leaf() {
+            write!(f, "{}", operator)
+        } else if operator.is_root_node() {
+            // Children of root nodes are already parenthesized if necessary
+            match children.first() {
+                Some(child) => write!(f, "{}", child),
+                // The empty braces of a call without arguments
+                None => write!(f, "()"),
+            }
+        } else if operator.function_identifier().is_some() || operator.is_if_error() {
+            if is_parenthesized(&children[0]) {
+                write!(f, "{}{}", operator, children[0])
+            } else {
+                write!(f, "{}({})", operator, children[0])
+            }
+        } else if operator.is_comma() {
+            let mut arguments = Vec::new();
+            self.collect_comma_separated(&mut arguments);
+            write!(f, "(")?;
+            for (index, argument) in arguments.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", argument)?;
+            }
+            if arguments.len() == 1 {
+                write!(f, ",")?;
+            }
+            write!(f, ")")
+        } else if operator.is_index() {
+            write!(f, "{}[{}]", children[0], children[1])
+        } else if operator.is_if_else() {
+            write!(
+                f,
+                "(if {} {{ {} }} else {{ {} }})",
+                children[0], children[1], children[2]
+            )
+        } else if let Some(name) = operator.argument_name() {
+            // Named arguments are only valid directly inside the braces of a call
+            write!(f, "{}: {}", name, children[0])
+        } else if let Some(identifier) = operator.let_identifier() {
+            write!(
+                f,
+                "(let {} = {} in {})",
+                identifier, children[0], children[1]
+            )
+        } else if operator.is_ternary() {
+            write!(f, "({} ? {} : {})", children[0], children[1], children[2])
+        } else if operator.is_spread() {
+            // Spread arguments are only valid directly inside the braces of a call
+            write!(f, "{}{}", operator, children[0])
+        } else if operator.is_unary() {
+            write!(f, "({}{})", operator, children[0])
+        } else {
+            write!(f, "({} {} {})", children[0], operator, children[1])
+        }
+    }
+}
+
+/// True if the display of the node is enclosed in parentheses.
+fn is_parenthesized(node: &Node) -> bool {
+    let operator = node.operator();
+    if operator.is_root_node() {
+        match node.children().first() {
+            Some(child) => is_parenthesized(child),
+            None => true,
+        }
+    } else {
+        !operator.is_leaf()
+            && operator.function_identifier().is_none()
+            && !operator.is_if_error()
+            && !operator.is_spread()
+            && operator.argument_name().is_none()
+    }
+}
@@ -1,7 +1,41 @@
-use crate::{configuration::Configuration, error::Error, operator::*, value::Value};
-use token::Token;
+#[cfg(feature = "async")]
+use crate::configuration::AsyncConfiguration;
+use crate::{
+    configuration::{Configuration, EmptyConfiguration, MutableConfiguration},
+    error::{
+        expect_argument_amount, expect_array_length, expect_boolean, expect_string_length, Error,
+    },
+    function::is_pure_default_function,
+    operator::*,
+    value::Value,
+};
+#[cfg(feature = "async")]
+use std::borrow::Cow;
+use std::{
+    boxed::Box,
+    iter::Peekable,
+    slice::Iter,
+    string::{String, ToString},
+    vec::Vec,
+};
+use token::{Span, Token};
 
-#[derive(Debug)]
+#[cfg(feature = "async")]
+mod async_eval;
+mod cached;
+mod compiled;
+mod display;
+#[cfg(feature = "rayon")]
+mod parallel_eval;
+#[cfg(feature = "serde")]
+mod serialization;
+
+#[cfg(feature = "async")]
+pub use self::async_eval::EvalAsync;
+pub use self::cached::CachedExpr;
+pub use self::compiled::CompiledExpr;
+
+#[derive(Clone, Debug)]
 pub struct Node {
     children: Vec<Node>,
     operator: Box<dyn Operator>,
@@ -19,20 +53,771 @@ impl Node {
         Self::new(RootNode)
     }
 
-    pub fn eval(&self, configuration: &Configuration) -> Result<Value, Error> {
+    /// Creates a leaf node that evaluates to the given value.
+    pub fn from_value(value: Value) -> Self {
+        Self::new(Const::new(value))
+    }
+
+    /// True if this node is a constant value, i.e. a leaf that is not a variable identifier.
+    fn is_constant(&self) -> bool {
+        self.operator().is_leaf() && self.operator().variable_identifier().is_none()
+    }
+
+    /// True if this expression can be evaluated without a configuration.
+    /// This is the case if it reads no variables, assigns no variables, and calls only builtin functions that do not depend on external state.
+    /// For example, `1 + sqrt(4)` is constant, but `a + 1`, `f(1)` and `now()` are not.
+    ///
+    /// Being constant does not mean that the evaluation succeeds, as in `1 / 0`.
+    pub fn is_const(&self) -> bool {
+        let operator = self.operator();
+        let is_const_operator = if let Some(identifier) = operator.function_identifier() {
+            is_pure_default_function(identifier)
+        } else {
+            operator.variable_identifier().is_none() && !operator.is_assignment()
+        };
+        is_const_operator && self.children().iter().all(Node::is_const)
+    }
+
+    /// Evaluates this expression with an `EmptyConfiguration` if it is constant, as determined by `Node::is_const`.
+    /// Returns `None` if the expression is not constant or its evaluation fails.
+    pub fn eval_if_const(&self) -> Option<Value> {
+        if self.is_const() {
+            self.eval(&EmptyConfiguration).ok()
+        } else {
+            None
+        }
+    }
+
+    /// Returns a simplified copy of this expression, where all sub-expressions that can be evaluated with the given configuration are replaced by their values.
+    /// Sub-expressions that cannot be evaluated, for example because they contain unknown variables, are kept as they are.
+    ///
+    /// Functions are called at most once, and only if all their arguments are known.
+    /// Variables that are assigned anywhere in the expression are never replaced.
+    pub fn partial_eval(&self, configuration: &dyn Configuration) -> Node {
+        let mut assigned = Vec::new();
+        self.collect_assigned_variables(&mut assigned, true);
+        self.partial_eval_unassigned(configuration, &assigned)
+    }
+
+    /// Returns a copy of this expression where all reads of the variable `identifier` are replaced by the given value.
+    /// The expression is not evaluated, and the targets of assignments are kept as they are.
+    /// The bodies of `let` bindings of the same identifier are kept as well, since the binding shadows the variable there.
+    pub fn substitute_variable(&self, identifier: &str, value: Value) -> Node {
+        self.substitute_variable_ref(identifier, &value)
+    }
+
+    fn substitute_variable_ref(&self, identifier: &str, value: &Value) -> Node {
+        if self.operator().variable_identifier() == Some(identifier) {
+            return Node::from_value(value.clone());
+        }
+
+        let skip_target = self.assignment_target().is_some();
+        let skip_body = self.operator().let_identifier() == Some(identifier);
+        Node {
+            children: self
+                .children()
+                .iter()
+                .enumerate()
+                .map(|(index, child)| {
+                    if (skip_target && index == 0) || (skip_body && index == 1) {
+                        child.clone()
+                    } else {
+                        child.substitute_variable_ref(identifier, value)
+                    }
+                })
+                .collect(),
+            operator: self.operator.clone(),
+        }
+    }
+
+    /// Returns a copy of this expression where each node is replaced by the result of `f`.
+    /// The tree is rewritten bottom-up, so `f` is called with a node after all its children were rewritten.
+    /// Braced sub-expressions are represented by root nodes, which are passed to `f` as well.
+    pub fn rewrite<F: Fn(Node) -> Node>(&self, f: F) -> Node {
+        self.rewrite_ref(&f)
+    }
+
+    /// Returns a copy of this expression where each sub-expression that only consists of literals and operators is replaced by its value.
+    /// For example, `2 * 3 + x` is folded to `6 + x`.
+    ///
+    /// Unlike `partial_eval`, this needs no configuration, and the folded expression evaluates to the same result as this one with any configuration.
+    /// Therefore, function calls are not folded, since configurations may override builtin functions.
+    /// Sub-expressions whose evaluation fails, such as `1 / 0`, are kept as they are.
+    /// The only exception are the limits of configurations, since folded strings and arrays are not checked against `Configuration::max_string_length` and `Configuration::max_array_length`.
+    pub fn fold_constants(&self) -> Node {
+        self.fold_constants_ref(false)
+    }
+
+    fn fold_constants_ref(&self, is_argument_list: bool) -> Node {
+        let is_function_call = self.operator().function_identifier().is_some();
+        let has_argument_list = is_function_call || self.operator().is_if_error();
+        let node = Node {
+            children: self
+                .children()
+                .iter()
+                .map(|child| child.fold_constants_ref(has_argument_list))
+                .collect(),
+            operator: self.operator.clone(),
+        };
+
+        // The braces and commas of argument lists are kept, such that the arguments of function calls stay separate
+        let is_foldable = !is_argument_list
+            && !node.operator().is_comma()
+            && !node.children().is_empty()
+            && node.children().iter().all(Node::is_constant)
+            && !is_function_call;
+        if is_foldable {
+            if let Some(value) = node.eval_if_const() {
+                return Node::from_value(value);
+            }
+        }
+        node
+    }
+
+    fn rewrite_ref<F: Fn(Node) -> Node>(&self, f: &F) -> Node {
+        f(Node {
+            children: self
+                .children()
+                .iter()
+                .map(|child| child.rewrite_ref(f))
+                .collect(),
+            operator: self.operator.clone(),
+        })
+    }
+
+    fn partial_eval_unassigned(
+        &self,
+        configuration: &dyn Configuration,
+        assigned: &[String],
+    ) -> Node {
+        if let Some(identifier) = self.operator().variable_identifier() {
+            if assigned.iter().any(|assigned| assigned == identifier) {
+                return self.clone();
+            }
+        }
+        if self.operator().is_leaf() {
+            return match self.eval(configuration) {
+                Ok(value) => Node::from_value(value),
+                Err(_) => self.clone(),
+            };
+        }
+
+        let children: Vec<Node> = self
+            .children()
+            .iter()
+            .enumerate()
+            .map(|(index, child)| match self.operator().let_identifier() {
+                // The bound variable is only known when evaluating the body
+                Some(identifier) if index == 1 => {
+                    let mut assigned = assigned.to_vec();
+                    assigned.push(identifier.to_string());
+                    child.partial_eval_unassigned(configuration, &assigned)
+                }
+                _ => child.partial_eval_unassigned(configuration, assigned),
+            })
+            .collect();
+
+        // Lazy operators can be simplified if their first argument is known, even if the others are not.
+        if self.operator().is_ternary() || self.operator().is_null_coalescing() {
+            if let Some(condition) = children.first().filter(|child| child.is_constant()) {
+                if let Ok(condition) = condition.eval(configuration) {
+                    let selected = if self.operator().is_ternary() {
+                        match condition {
+                            Value::Boolean(true) => children.get(1),
+                            Value::Boolean(false) => children.get(2),
+                            _ => None,
+                        }
+                    } else if condition.is_null() {
+                        children.get(1)
+                    } else {
+                        children.first()
+                    };
+                    if let Some(selected) = selected {
+                        return selected.clone();
+                    }
+                }
+            }
+        }
+
+        let node = Node {
+            children,
+            operator: self.operator.clone(),
+        };
+        // Comma-separated lists are kept, such that the arguments of function calls stay separate
+        if !node.operator().is_comma()
+            && node
+                .arguments()
+                .iter()
+                .all(|argument| argument.is_constant())
+        {
+            if let Ok(value) = node.eval(configuration) {
+                return Node::from_value(value);
+            }
+        }
+        node
+    }
+
+    pub fn eval(&self, configuration: &dyn Configuration) -> Result<Value, Error> {
+        self.expect_node_count(configuration)?;
+        self.eval_observed(
+            &mut EvaluationConfiguration::Immutable(configuration),
+            1,
+            &mut |_, _| {},
+        )
+    }
+
+    /// Evaluates this expression like `eval`, but allows it to assign variables in the given configuration.
+    pub fn eval_with_mutable_configuration(
+        &self,
+        configuration: &mut dyn MutableConfiguration,
+    ) -> Result<Value, Error> {
+        self.expect_node_count(configuration)?;
+        self.eval_observed(
+            &mut EvaluationConfiguration::Mutable(configuration),
+            1,
+            &mut |_, _| {},
+        )
+    }
+
+    /// Evaluates this expression like `eval`, and additionally allows it to call the async functions of the given configuration.
+    /// See `EvalAsync` for how async functions are awaited.
+    #[cfg(feature = "async")]
+    pub fn eval_async<'a>(&'a self, configuration: &'a dyn AsyncConfiguration) -> EvalAsync<'a> {
+        EvalAsync::new(Ok(Cow::Borrowed(self)), configuration)
+    }
+
+    /// Evaluates this expression like `eval`, and calls `callback` with each evaluated node and its value.
+    /// The callback is called in post-order, i.e. after the arguments of a node were evaluated and before its parent is evaluated.
+    /// Nodes that are not evaluated, such as the branch of a ternary that is not selected, are not passed to the callback.
+    pub fn eval_with_callback<F: FnMut(&Node, &Value)>(
+        &self,
+        configuration: &dyn Configuration,
+        mut callback: F,
+    ) -> Result<Value, Error> {
+        self.expect_node_count(configuration)?;
+        self.eval_observed(
+            &mut EvaluationConfiguration::Immutable(configuration),
+            1,
+            &mut callback,
+        )
+    }
+
+    /// Returns `Error::MaxNodeCountExceeded` if this expression has more nodes than allowed by the configuration.
+    fn expect_node_count(&self, configuration: &dyn Configuration) -> Result<(), Error> {
+        match configuration.max_node_count() {
+            Some(max_node_count) if self.node_count() > max_node_count => {
+                Err(Error::max_node_count_exceeded(max_node_count))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Evaluates this node, which is at the given `depth` of the tree, where the node evaluation started at has depth one.
+    ///
+    /// This function and `eval_arguments` are the only ones that are called once per level of the tree.
+    /// Everything else is done in separate functions that are not inlined, such that the stack frames of the recursion stay small
+    /// and trees up to the default maximum depth can be evaluated with the default stack size of threads.
+    fn eval_observed(
+        &self,
+        configuration: &mut EvaluationConfiguration,
+        depth: usize,
+        callback: &mut dyn FnMut(&Node, &Value),
+    ) -> Result<Value, Error> {
+        // The spans of the arguments are nested in the span of this node
+        #[cfg(feature = "tracing")]
+        let _span = self.enter_eval_span();
+
+        expect_depth(configuration.get(), depth)?;
+        let value = if is_lazy(self.operator()) {
+            self.eval_lazy(configuration, depth, callback)
+        } else {
+            match self.eval_arguments(configuration, depth, callback) {
+                Ok(arguments) => self.operator().eval(&arguments, configuration.get()),
+                Err(error) => Err(error),
+            }
+        }?;
+        self.expect_value_limits(&value, configuration.get())?;
+
+        callback(self, &value);
+        Ok(value)
+    }
+
+    /// Enters the tracing span of the evaluation of this node.
+    #[cfg(feature = "tracing")]
+    #[inline(never)]
+    fn enter_eval_span(&self) -> tracing::span::EnteredSpan {
+        tracing::trace_span!("eval_node", operator = %self.operator(), node = %self).entered()
+    }
+
+    /// Evaluates a node whose operator evaluates its arguments itself, such as a ternary or an assignment.
+    #[inline(never)]
+    fn eval_lazy(
+        &self,
+        configuration: &mut EvaluationConfiguration,
+        depth: usize,
+        callback: &mut dyn FnMut(&Node, &Value),
+    ) -> Result<Value, Error> {
+        if self.operator().is_ternary() {
+            self.eval_ternary(configuration, depth, callback)
+        } else if self.operator().is_null_coalescing() {
+            self.eval_null_coalescing(configuration, depth, callback)
+        } else if self.operator().is_if_error() {
+            self.eval_if_error(configuration, depth, callback)
+        } else if self.operator().is_assignment() {
+            self.eval_assignment(configuration, depth, callback)
+        } else if let Some(identifier) = self.operator().let_identifier() {
+            self.eval_let(identifier, configuration, depth, callback)
+        } else {
+            unreachable!("the operator {} is not lazy", self.operator())
+        }
+    }
+
+    /// Returns an error if `value`, which is the result of this node, exceeds the limits of the configuration.
+    #[inline(never)]
+    fn expect_value_limits(
+        &self,
+        value: &Value,
+        configuration: &dyn Configuration,
+    ) -> Result<(), Error> {
+        // Strings and arrays created by operators and functions must not exceed the limits of the configuration.
+        // Operators that only pass on one of their operands do not create values.
+        let operator = self.operator();
+        let passes_on_operand = operator.is_leaf()
+            || operator.is_root_node()
+            || operator.is_ternary()
+            || operator.is_if_else()
+            || operator.is_null_coalescing()
+            || operator.is_if_error()
+            || operator.let_identifier().is_some()
+            || operator.is_index();
+        if passes_on_operand {
+            return Ok(());
+        }
+        match value {
+            Value::String(string) => {
+                expect_string_length(string.len(), configuration.max_string_length())
+            }
+            Value::Array(array) => {
+                expect_array_length(array.len(), configuration.max_array_length())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Evaluates the arguments of this node, which is at the given `depth` of the tree.
+    fn eval_arguments(
+        &self,
+        configuration: &mut EvaluationConfiguration,
+        depth: usize,
+        callback: &mut dyn FnMut(&Node, &Value),
+    ) -> Result<Vec<Value>, Error> {
+        #[cfg(feature = "rayon")]
+        {
+            if let Some(arguments) = self.eval_parallel_operands(configuration, depth) {
+                return arguments;
+            }
+        }
+
+        let arguments = self.arguments();
+        let is_call = self.operator().function_identifier().is_some();
+        if is_call
+            && arguments.iter().any(|argument| {
+                argument.operator().is_spread() || argument.operator().argument_name().is_some()
+            })
+        {
+            return self.eval_call_arguments(&arguments, configuration, depth, callback);
+        }
+
+        let mut values = Vec::with_capacity(arguments.len());
+        for argument in arguments {
+            values.push(argument.eval_observed(configuration, depth + 1, callback)?);
+        }
+        Ok(values)
+    }
+
+    /// Evaluates the given arguments of this function call, which contain spread or named arguments.
+    #[inline(never)]
+    fn eval_call_arguments(
+        &self,
+        arguments: &[&Node],
+        configuration: &mut EvaluationConfiguration,
+        depth: usize,
+        callback: &mut dyn FnMut(&Node, &Value),
+    ) -> Result<Vec<Value>, Error> {
+        let mut values = Vec::new();
+        let mut named_arguments = Vec::new();
+        for argument in arguments {
+            if let Some(name) = argument.operator().argument_name() {
+                let value =
+                    argument.children()[0].eval_observed(configuration, depth + 2, callback)?;
+                named_arguments.push((name.to_string(), value));
+            } else if argument.operator().is_spread() {
+                // Spread arguments are flattened into the arguments of the call
+                let value =
+                    argument.children()[0].eval_observed(configuration, depth + 2, callback)?;
+                match value {
+                    Value::Array(elements) | Value::Tuple(elements) => values.extend(elements),
+                    value => return Err(Error::expected_array(value)),
+                }
+            } else {
+                values.push(argument.eval_observed(configuration, depth + 1, callback)?);
+            }
+        }
+
+        // Named arguments are mapped to the positions of their parameters, which only functions of the configuration have
+        if let (Some(identifier), Some((name, _))) = (
+            self.operator().function_identifier(),
+            named_arguments.first(),
+        ) {
+            let configuration = configuration.get();
+            if let Some(function) = configuration.get_function(identifier) {
+                return function.order_named_arguments(identifier, values, named_arguments);
+            }
+            // Report functions that do not exist at all like calls without named arguments do
+            let argument_amount = values.len() + named_arguments.len();
+            return match self.operator().validate(argument_amount, configuration) {
+                Err(error @ Error::FunctionIdentifierNotFound { .. }) => Err(error),
+                _ => Err(Error::unknown_argument_name(
+                    identifier.to_string(),
+                    name.clone(),
+                )),
+            };
+        }
+        Ok(values)
+    }
+
+    /// Returns the nodes whose values are passed to the operator of this node.
+    /// These are the children, except for function calls and `if_error`, where the comma-separated arguments inside the braces are returned,
+    /// and for commas, where all elements of the comma-separated list are returned.
+    fn arguments(&self) -> Vec<&Node> {
+        if self.operator().is_comma() {
+            let mut arguments = Vec::new();
+            self.collect_comma_separated(&mut arguments);
+            return arguments;
+        }
+        if self.operator().function_identifier().is_none() && !self.operator().is_if_error() {
+            return self.children().iter().collect();
+        }
+
         let mut arguments = Vec::new();
+        if let Some(argument) = self.children().first() {
+            // Unwrap the braces of the call, but not any braces inside of them
+            match argument.children().first() {
+                Some(inner)
+                    if argument.operator().is_root_node() && inner.operator().is_comma() =>
+                {
+                    inner.collect_comma_separated(&mut arguments)
+                }
+                Some(inner)
+                    if argument.operator().is_root_node()
+                        && (inner.operator().is_spread()
+                            || inner.operator().argument_name().is_some()) =>
+                {
+                    arguments.push(inner)
+                }
+                // A call without arguments, such as `f()`
+                None if argument.operator().is_root_node() => {}
+                _ => arguments.push(argument),
+            }
+        }
+        arguments
+    }
+
+    fn collect_comma_separated<'a>(&'a self, arguments: &mut Vec<&'a Node>) {
+        if self.operator().is_comma() {
+            for child in self.children() {
+                child.collect_comma_separated(arguments);
+            }
+        } else {
+            arguments.push(self);
+        }
+    }
+
+    /// Checks that this expression can be evaluated with the given configuration, without evaluating it.
+    /// All variables that are not assigned by the expression and all functions must exist in the configuration, and all functions must be called with the correct amount of arguments.
+    ///
+    /// All branches are checked, including those that would not be evaluated because of short-circuiting.
+    /// If a single problem is found, the corresponding error is returned.
+    /// If several problems are found, they are returned in pre-order as `Error::MultipleErrors`.
+    /// The problems that are detected are described at `validate_all`.
+    pub fn validate(&self, configuration: &dyn Configuration) -> Result<(), Error> {
+        let mut errors = self.validate_all(configuration);
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.pop().unwrap()),
+            _ => Err(Error::MultipleErrors(errors)),
+        }
+    }
+
+    /// Checks this expression like `validate`, and returns all problems that were found in pre-order.
+    ///
+    /// Besides unknown variables and functions and wrong amounts of arguments, this reports type errors in sub-expressions that consist of constants only, such as `1 + true`.
+    /// Functions are never called.
+    pub fn validate_all(&self, configuration: &dyn Configuration) -> Vec<Error> {
+        // Compound assignments read their variable, so it must exist unless it is assigned elsewhere
+        let mut assigned = Vec::new();
+        self.collect_assigned_variables(&mut assigned, false);
+        let mut errors = Vec::new();
+        self.collect_validation_errors(configuration, &assigned, &mut errors);
+        errors
+    }
+
+    /// Collects the validation errors of this node and its children, and returns the value of this node if it consists of constants only.
+    fn collect_validation_errors(
+        &self,
+        configuration: &dyn Configuration,
+        assigned: &[String],
+        errors: &mut Vec<Error>,
+    ) -> Option<Value> {
+        let is_assigned = match self.operator().variable_identifier() {
+            Some(identifier) => assigned.iter().any(|assigned| assigned == identifier),
+            None => false,
+        };
+        if !is_assigned {
+            let arguments = self.arguments();
+            match self.operator().validate(arguments.len(), configuration) {
+                // The amount of spread arguments is only known at evaluation time
+                Err(Error::WrongArgumentAmount { .. })
+                    if arguments
+                        .iter()
+                        .any(|argument| argument.operator().is_spread()) => {}
+                Err(error) => errors.push(error),
+                Ok(()) => {}
+            }
+        }
+
+        // Errors of the first argument of `if_error` are caught when evaluating it
+        if self.operator().is_if_error() {
+            if let [argument, fallback] = self.arguments()[..] {
+                argument.collect_validation_errors(configuration, assigned, &mut Vec::new());
+                fallback.collect_validation_errors(configuration, assigned, errors);
+                return None;
+            }
+        }
+
+        // The bound variable of `let` is known in its body
+        if let Some(identifier) = self.operator().let_identifier() {
+            if let [binding, body] = self.children() {
+                let mut body_assigned = assigned.to_vec();
+                body_assigned.push(identifier.to_string());
+                binding.collect_validation_errors(configuration, assigned, errors);
+                body.collect_validation_errors(configuration, &body_assigned, errors);
+                return None;
+            }
+        }
+
+        let position = errors.len();
+        let values: Vec<Option<Value>> = self
+            .children()
+            .iter()
+            .map(|child| child.collect_validation_errors(configuration, assigned, errors))
+            .collect();
+
+        let operator = self.operator();
+        if operator.function_identifier().is_some()
+            || operator.variable_identifier().is_some()
+            || operator.is_comma()
+            || operator.is_spread()
+            || operator.argument_name().is_some()
+            || (operator.is_root_node() && self.children().is_empty())
+            || operator.is_assignment()
+        {
+            return None;
+        }
+        let arguments = values.into_iter().collect::<Option<Vec<Value>>>()?;
+        match operator.eval(&arguments, configuration) {
+            Ok(value) => Some(value),
+            Err(error) => {
+                // Keep the errors in pre-order
+                errors.insert(position, error);
+                None
+            }
+        }
+    }
+
+    /// Evaluates a ternary node, evaluating only the branch selected by the condition.
+    /// The branches of `if`-`else` expressions are evaluated in a new scope of the configuration.
+    fn eval_ternary(
+        &self,
+        configuration: &mut EvaluationConfiguration,
+        depth: usize,
+        callback: &mut dyn FnMut(&Node, &Value),
+    ) -> Result<Value, Error> {
+        expect_argument_amount(self.children().len(), 3)?;
+
+        let branch = if expect_boolean(&self.children[0].eval_observed(
+            configuration,
+            depth + 1,
+            callback,
+        )?)? {
+            &self.children[1]
+        } else {
+            &self.children[2]
+        };
+        if self.operator().is_if_else() {
+            configuration.push_scope();
+            let value = branch.eval_observed(configuration, depth + 1, callback);
+            configuration.pop_scope();
+            value
+        } else {
+            branch.eval_observed(configuration, depth + 1, callback)
+        }
+    }
+
+    /// Evaluates a null-coalescing node, evaluating the right side only if the left side is null.
+    fn eval_null_coalescing(
+        &self,
+        configuration: &mut EvaluationConfiguration,
+        depth: usize,
+        callback: &mut dyn FnMut(&Node, &Value),
+    ) -> Result<Value, Error> {
+        expect_argument_amount(self.children().len(), 2)?;
+
+        let value = self.children[0].eval_observed(configuration, depth + 1, callback)?;
+        if value.is_null() {
+            self.children[1].eval_observed(configuration, depth + 1, callback)
+        } else {
+            Ok(value)
+        }
+    }
+
+    /// Evaluates an `if_error` node, evaluating the fallback only if the evaluation of the first argument fails.
+    fn eval_if_error(
+        &self,
+        configuration: &mut EvaluationConfiguration,
+        depth: usize,
+        callback: &mut dyn FnMut(&Node, &Value),
+    ) -> Result<Value, Error> {
+        let arguments = self.arguments();
+        expect_argument_amount(arguments.len(), 2)?;
+
+        match arguments[0].eval_observed(configuration, depth + 1, callback) {
+            Ok(value) => Ok(value),
+            Err(_) => arguments[1].eval_observed(configuration, depth + 1, callback),
+        }
+    }
+
+    /// Evaluates a `let` node, evaluating its body with the variable `identifier` replaced by the value of the binding.
+    fn eval_let(
+        &self,
+        identifier: &str,
+        configuration: &mut EvaluationConfiguration,
+        depth: usize,
+        callback: &mut dyn FnMut(&Node, &Value),
+    ) -> Result<Value, Error> {
+        expect_argument_amount(self.children().len(), 2)?;
+
+        let value = self.children[0].eval_observed(configuration, depth + 1, callback)?;
+        self.children[1]
+            .substitute_variable_ref(identifier, &value)
+            .eval_observed(configuration, depth + 1, callback)
+    }
+
+    /// Evaluates an assignment node, writing the value of the right side to the variable on the left side.
+    fn eval_assignment(
+        &self,
+        configuration: &mut EvaluationConfiguration,
+        depth: usize,
+        callback: &mut dyn FnMut(&Node, &Value),
+    ) -> Result<Value, Error> {
+        expect_argument_amount(self.children().len(), 2)?;
+
+        let identifier = self
+            .assignment_target()
+            .ok_or(Error::InvalidAssignmentTarget)?;
+        let value = match self.operator().compound_assignment_operator() {
+            Some(operator) => {
+                // `x += y` is evaluated like `x = x + y`
+                let current = self.children[0].eval_observed(configuration, depth + 1, callback)?;
+                let value = self.children[1].eval_observed(configuration, depth + 1, callback)?;
+                operator.eval(&[current, value], configuration.get())?
+            }
+            None => self.children[1].eval_observed(configuration, depth + 1, callback)?,
+        };
+        configuration.set_variable(identifier, value.clone())?;
+        Ok(value)
+    }
+
+    /// Returns the identifier of the variable assigned by this node, if it is an assignment to a variable.
+    fn assignment_target(&self) -> Option<&str> {
+        if self.operator().is_assignment() {
+            self.children()
+                .first()
+                .and_then(|target| target.operator().variable_identifier())
+        } else {
+            None
+        }
+    }
+
+    /// Collects the targets of all assignments, including compound assignments such as `+=` if `compound` is true.
+    fn collect_assigned_variables(&self, identifiers: &mut Vec<String>, compound: bool) {
+        if let Some(identifier) = self.assignment_target() {
+            if compound || self.operator().compound_assignment_operator().is_none() {
+                identifiers.push(identifier.to_string());
+            }
+        }
+        for child in self.children() {
+            child.collect_assigned_variables(identifiers, compound);
+        }
+    }
+
+    /// Returns the amount of nodes in this expression, including leaves and the root nodes of braces.
+    pub fn node_count(&self) -> usize {
+        1 + self
+            .children()
+            .iter()
+            .map(|child| child.node_count())
+            .sum::<usize>()
+    }
+
+    /// Returns the amount of nodes on the longest path from this node to a leaf, including both ends.
+    /// This is the depth that is compared against `Configuration::max_depth` when evaluating.
+    pub fn depth(&self) -> usize {
+        1 + self
+            .children()
+            .iter()
+            .map(|child| child.depth())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the identifiers of all variables read by this expression, in pre-order and without duplicates.
+    /// The expression is not evaluated.
+    pub fn list_variables(&self) -> Vec<String> {
+        let mut identifiers = Vec::new();
+        self.collect_identifiers(&mut identifiers, &|operator| operator.variable_identifier());
+        identifiers
+    }
+
+    /// Returns the identifiers of all functions called by this expression, in pre-order and without duplicates.
+    /// The expression is not evaluated.
+    pub fn list_functions(&self) -> Vec<String> {
+        let mut identifiers = Vec::new();
+        self.collect_identifiers(&mut identifiers, &|operator| operator.function_identifier());
+        identifiers
+    }
+
+    fn collect_identifiers(
+        &self,
+        identifiers: &mut Vec<String>,
+        identifier: &dyn Fn(&dyn Operator) -> Option<&str>,
+    ) {
+        if let Some(identifier) = identifier(self.operator()) {
+            if !identifiers.iter().any(|existing| existing == identifier) {
+                identifiers.push(identifier.to_string());
+            }
+        }
         for child in self.children() {
-            arguments.push(child.eval(configuration)?);
+            child.collect_identifiers(identifiers, identifier);
         }
-        self.operator().eval(&arguments, configuration)
     }
 
     pub fn children(&self) -> &[Node] {
         &self.children
     }
 
-    pub fn operator(&self) -> &Box<dyn Operator> {
-        &self.operator
+    pub fn operator(&self) -> &dyn Operator {
+        self.operator.as_ref()
     }
 
     fn has_correct_amount_of_children(&self) -> bool {
@@ -40,22 +825,18 @@ impl Node {
     }
 
     fn insert_back_prioritized(&mut self, node: Node, is_root_node: bool) -> Result<(), Error> {
-        if self.operator().precedence() < node.operator().precedence() || is_root_node {
+        if is_inserted_below(self.operator(), node.operator()) || is_root_node {
             if self.operator().is_leaf() {
-                Err(Error::AppendedToLeafNode)
+                Err(Error::AppendedToLeafNode { span: None })
             } else if self.has_correct_amount_of_children() {
-                if self.children.last().unwrap().operator().precedence()
-                    < node.operator().precedence()
-                // Function call
-                //|| self.children().last().unwrap()
-                {
+                if is_inserted_below(self.children.last().unwrap().operator(), node.operator()) {
                     self.children
                         .last_mut()
                         .unwrap()
                         .insert_back_prioritized(node, false)
                 } else {
                     if node.operator().is_leaf() {
-                        return Err(Error::AppendedToLeafNode);
+                        return Err(Error::AppendedToLeafNode { span: None });
                     }
 
                     let last_child = self.children.pop().unwrap();
@@ -70,18 +851,53 @@ impl Node {
                 Ok(())
             }
         } else {
-            Err(Error::PrecedenceViolation)
+            Err(Error::PrecedenceViolation { span: None })
         }
     }
 }
 
-pub fn tokens_to_operator_tree(tokens: Vec<Token>) -> Result<Node, Error> {
+/// True if nodes with the given operator evaluate their arguments themselves, see `Node::eval_lazy`.
+fn is_lazy(operator: &dyn Operator) -> bool {
+    operator.is_ternary()
+        || operator.is_null_coalescing()
+        || operator.is_if_error()
+        || operator.is_assignment()
+        || operator.let_identifier().is_some()
+}
+
+/// Returns `Error::MaxDepthExceeded` if `depth` exceeds the maximum depth of the configuration.
+#[inline(never)]
+fn expect_depth(configuration: &dyn Configuration, depth: usize) -> Result<(), Error> {
+    match configuration.max_depth() {
+        Some(max_depth) if depth > max_depth => Err(Error::max_depth_exceeded(max_depth)),
+        _ => Ok(()),
+    }
+}
+
+/// Returns true if `node` belongs below a node with the given `operator` in the tree.
+fn is_inserted_below(operator: &dyn Operator, node: &dyn Operator) -> bool {
+    operator.precedence() < node.precedence()
+        // Unary operators always take the next value as their argument
+        || node.is_unary()
+        // Right-to-left chaining
+        || (operator.precedence() == node.precedence()
+            && !operator.is_left_to_right()
+            && !node.is_left_to_right())
+}
+
+/// Parses the given tokens, as returned by `tokenize`, into an operator tree.
+/// The spans of the tokens are used for the positions of errors.
+pub fn tokens_to_operator_tree(tokens: Vec<(Token, Span)>) -> Result<Node, Error> {
     let mut root = vec![Node::root_node()];
+    // The opening tokens of all sub-expressions in `root` except the outermost one.
+    // These are either left braces, left brackets of indices or the question marks of unfinished ternaries.
+    let mut open_tokens: Vec<(Token, Span)> = Vec::new();
     let mut last_token_is_rightsided_value = false;
+    let mut last_token_starts_argument = false;
     let mut token_iter = tokens.iter().peekable();
 
-    while let Some(token) = token_iter.next().cloned() {
-        let next = token_iter.peek().cloned();
+    while let Some((token, span)) = token_iter.next().cloned() {
+        let next = token_iter.peek().cloned().map(|(token, _)| token);
 
         let node = match token.clone() {
             Token::Plus => Some(Node::new(Add)),
@@ -93,6 +909,7 @@ pub fn tokens_to_operator_tree(tokens: Vec<Token>) -> Result<Node, Error> {
                 }
             }
             Token::Star => Some(Node::new(Mul)),
+            Token::DoubleStar => Some(Node::new(Exp)),
             Token::Slash => Some(Node::new(Div)),
             Token::Percent => Some(Node::new(Mod)),
 
@@ -104,25 +921,122 @@ pub fn tokens_to_operator_tree(tokens: Vec<Token>) -> Result<Node, Error> {
             Token::Leq => Some(Node::new(Leq)),
             Token::And => Some(Node::new(And)),
             Token::Or => Some(Node::new(Or)),
+            Token::Xor => Some(Node::new(Xor)),
             Token::Not => Some(Node::new(Not)),
+            Token::Spread => Some(Node::new(Spread)),
+
+            Token::BitAnd => Some(Node::new(BitAnd)),
+            Token::BitOr => Some(Node::new(BitOr)),
+            Token::BitXor => Some(Node::new(BitXor)),
+            Token::BitNot => Some(Node::new(BitNot)),
+            Token::Shl => Some(Node::new(Shl)),
+            Token::Shr => Some(Node::new(Shr)),
 
             Token::LBrace => {
                 root.push(Node::root_node());
+                open_tokens.push((token.clone(), span.clone()));
                 None
             }
-            Token::RBrace => {
-                if root.len() < 2 {
-                    return Err(Error::UnmatchedRBrace);
-                } else {
-                    root.pop()
+            Token::RBrace => match open_tokens.pop() {
+                Some((Token::LBrace, _)) => root.pop(),
+                Some((Token::LBracket, open_span)) => {
+                    return Err(Error::UnmatchedLBracket {
+                        span: Some(open_span),
+                    })
+                }
+                Some((_, open_span)) => {
+                    return Err(Error::UnmatchedQuestionMark {
+                        span: Some(open_span),
+                    })
                 }
+                None => return Err(Error::UnmatchedRBrace { span: Some(span) }),
+            },
+
+            Token::LBracket => {
+                // The indexed value becomes the first child of the index node,
+                // and the index is parsed separately until the matching bracket.
+                root.last_mut()
+                    .unwrap()
+                    .insert_back_prioritized(Node::new(Index), true)
+                    .map_err(|error| error.with_span(&span))?;
+                root.push(Node::root_node());
+                open_tokens.push((token.clone(), span.clone()));
+                None
+            }
+            Token::RBracket => match open_tokens.pop() {
+                Some((Token::LBracket, _)) => root.pop(),
+                Some((Token::LBrace, open_span)) => {
+                    return Err(Error::UnmatchedLBrace {
+                        span: Some(open_span),
+                    })
+                }
+                Some((_, open_span)) => {
+                    return Err(Error::UnmatchedQuestionMark {
+                        span: Some(open_span),
+                    })
+                }
+                None => return Err(Error::UnmatchedRBracket { span: Some(span) }),
+            },
+
+            Token::QuestionMark => {
+                // The condition becomes the first child of the ternary node,
+                // and the then-branch is parsed separately until the matching colon.
+                root.last_mut()
+                    .unwrap()
+                    .insert_back_prioritized(Node::new(Ternary), true)
+                    .map_err(|error| error.with_span(&span))?;
+                root.push(Node::root_node());
+                open_tokens.push((token.clone(), span.clone()));
+                None
             }
+            Token::If => Some(if_else_to_operator_tree(&span, &mut token_iter)?),
+            Token::Let => Some(let_to_operator_tree(&span, &mut token_iter)?),
+            Token::Else => return Err(Error::UnmatchedElse { span: Some(span) }),
+            Token::LCurlyBrace => return Err(Error::UnexpectedLCurlyBrace { span: Some(span) }),
+            Token::RCurlyBrace => return Err(Error::UnmatchedRCurlyBrace { span: Some(span) }),
+            Token::DoubleQuestionMark => Some(Node::new(NullCoalesce)),
+            Token::In => Some(Node::new(In)),
+            Token::DoubleDot => Some(Node::new(Range)),
+            Token::Comma => Some(Node::new(Comma)),
+            Token::Semicolon => Some(Node::new(Sequence)),
+            Token::Assign => Some(Node::new(Assign)),
+            Token::PlusAssign => Some(Node::new(CompoundAssign::new(Add))),
+            Token::MinusAssign => Some(Node::new(CompoundAssign::new(Sub))),
+            Token::StarAssign => Some(Node::new(CompoundAssign::new(Mul))),
+            Token::SlashAssign => Some(Node::new(CompoundAssign::new(Div))),
+            Token::PercentAssign => Some(Node::new(CompoundAssign::new(Mod))),
+            Token::Colon => match open_tokens.last() {
+                Some((Token::QuestionMark, _)) => {
+                    open_tokens.pop();
+                    root.pop()
+                }
+                _ => return Err(Error::UnmatchedColon { span: Some(span) }),
+            },
 
+            // An identifier followed by a colon at the start of an argument inside braces names the argument
+            Token::Identifier(identifier)
+                if next == Some(&Token::Colon)
+                    && last_token_starts_argument
+                    && matches!(open_tokens.last(), Some((Token::LBrace, _))) =>
+            {
+                token_iter.next();
+                Some(Node::new(NamedArg::new(identifier)))
+            }
             Token::Identifier(identifier) => {
-                let mut result = Some(Node::new(VariableIdentifier::new(identifier.clone())));
+                let mut result = Some(Node::new(VariableIdentifier::new(
+                    identifier.clone(),
+                    Some(span.clone()),
+                )));
                 if let Some(next) = next {
                     if next.is_leftsided_value() {
-                        result = Some(Node::new(FunctionIdentifier::new(identifier)));
+                        result = if identifier == "if_error" {
+                            Some(Node::new(IfError))
+                        } else {
+                            Some(Node::new(FunctionIdentifier::new(
+                                identifier,
+                                Some(span.clone()),
+                            )))
+                        };
                     }
                 }
                 result
@@ -130,29 +1044,233 @@ pub fn tokens_to_operator_tree(tokens: Vec<Token>) -> Result<Node, Error> {
             Token::Float(number) => Some(Node::new(Const::new(Value::Float(number)))),
             Token::Int(number) => Some(Node::new(Const::new(Value::Int(number)))),
             Token::Boolean(boolean) => Some(Node::new(Const::new(Value::Boolean(boolean)))),
+            Token::String(string) => Some(Node::new(Const::new(Value::String(string)))),
+            Token::Null => Some(Node::new(Const::new(Value::Null))),
         };
 
         if let Some(node) = node {
             if let Some(root) = root.last_mut() {
-                root.insert_back_prioritized(node, true)?;
+                root.insert_back_prioritized(node, true)
+                    .map_err(|error| error.with_span(&span))?;
             } else {
-                return Err(Error::UnmatchedRBrace);
+                return Err(Error::UnmatchedRBrace { span: Some(span) });
+            }
+        }
+
+        // A trailing semicolon ends the sequence with null
+        if token == Token::Semicolon && (next.is_none() || next == Some(&Token::RBrace)) {
+            if let Some(root) = root.last_mut() {
+                root.insert_back_prioritized(Node::from_value(Value::Null), true)
+                    .map_err(|error| error.with_span(&span))?;
             }
         }
 
         last_token_is_rightsided_value = token.is_rightsided_value();
+        last_token_starts_argument = matches!(token, Token::LBrace | Token::Comma);
     }
 
-    if root.len() > 1 {
-        Err(Error::UnmatchedLBrace)
-    } else if root.len() == 0 {
-        Err(Error::UnmatchedRBrace)
-    } else {
-        let mut root = root.pop().unwrap();
-        if root.children().len() == 1 {
-            Ok(root.children.pop().unwrap())
-        } else {
-            Err(Error::EmptyExpression)
+    match open_tokens.pop() {
+        Some((Token::LBrace, span)) => Err(Error::UnmatchedLBrace { span: Some(span) }),
+        Some((Token::LBracket, span)) => Err(Error::UnmatchedLBracket { span: Some(span) }),
+        Some((_, span)) => Err(Error::UnmatchedQuestionMark { span: Some(span) }),
+        None => {
+            let mut root = root.pop().unwrap();
+            if root.children().len() == 1 {
+                Ok(root.children.pop().unwrap())
+            } else {
+                Err(Error::EmptyExpression)
+            }
+        }
+    }
+}
+
+/// Builds the node of an `if`-`else` chain, whose `if` token at `if_span` was already consumed from `tokens`.
+/// The chain is consumed up to and including its last block.
+fn if_else_to_operator_tree(
+    if_span: &Span,
+    tokens: &mut Peekable<Iter<(Token, Span)>>,
+) -> Result<Node, Error> {
+    // The condition ends at the first curly brace that is not enclosed in braces
+    let mut condition = Vec::new();
+    let mut depth = 0usize;
+    let block_span = loop {
+        match tokens.next() {
+            Some((Token::LCurlyBrace, span)) if depth == 0 => break span,
+            Some((token, span)) => {
+                match token {
+                    Token::LBrace => depth += 1,
+                    Token::RBrace => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                condition.push((token.clone(), span.clone()));
+            }
+            None => {
+                return Err(Error::UnmatchedIf {
+                    span: Some(if_span.clone()),
+                })
+            }
+        }
+    };
+    let condition = tokens_to_operator_tree(condition)?;
+    let then_branch = block_to_operator_tree(block_span, tokens)?;
+
+    let else_branch = match tokens.peek() {
+        Some((Token::Else, else_span)) => {
+            tokens.next();
+            match tokens.next() {
+                Some((Token::LCurlyBrace, span)) => block_to_operator_tree(span, tokens)?,
+                Some((Token::If, span)) => if_else_to_operator_tree(span, tokens)?,
+                _ => {
+                    return Err(Error::UnmatchedElse {
+                        span: Some(else_span.clone()),
+                    })
+                }
+            }
+        }
+        // A missing else-branch evaluates to null
+        _ => Node::from_value(Value::Null),
+    };
+
+    Ok(Node {
+        children: vec![condition, then_branch, else_branch],
+        operator: Box::new(IfElse),
+    })
+}
+
+/// Builds the node of a `let` expression, whose `let` token at `let_span` was already consumed from `tokens`.
+/// The body extends up to the end of the enclosing braces or block, or up to the next comma or semicolon outside of braces.
+/// A colon outside of braces ends the body as well, unless it belongs to a ternary in the body.
+fn let_to_operator_tree(
+    let_span: &Span,
+    tokens: &mut Peekable<Iter<(Token, Span)>>,
+) -> Result<Node, Error> {
+    let unmatched_let = || Error::UnmatchedLet {
+        span: Some(let_span.clone()),
+    };
+    let identifier = match tokens.next() {
+        Some((Token::Identifier(identifier), _)) => identifier.clone(),
+        _ => return Err(unmatched_let()),
+    };
+    match tokens.next() {
+        Some((Token::Assign, _)) => {}
+        _ => return Err(unmatched_let()),
+    }
+
+    // The binding ends at the first `in` that is not enclosed in braces and does not belong to a nested `let`
+    let mut binding = Vec::new();
+    let mut depth = 0usize;
+    let mut nested_lets = 0usize;
+    loop {
+        let (token, span) = tokens.next().ok_or_else(unmatched_let)?;
+        match token {
+            Token::LBrace | Token::LBracket | Token::LCurlyBrace => depth += 1,
+            Token::RBrace | Token::RBracket | Token::RCurlyBrace => depth = depth.saturating_sub(1),
+            Token::Let if depth == 0 => nested_lets += 1,
+            Token::In if depth == 0 && nested_lets == 0 => break,
+            Token::In if depth == 0 => nested_lets -= 1,
+            _ => {}
+        }
+        binding.push((token.clone(), span.clone()));
+    }
+
+    let mut body = Vec::new();
+    let mut depth = 0usize;
+    let mut open_ternaries = 0usize;
+    while let Some((token, span)) = tokens.peek() {
+        match token {
+            Token::LBrace | Token::LBracket | Token::LCurlyBrace => depth += 1,
+            Token::RBrace | Token::RBracket | Token::RCurlyBrace if depth == 0 => break,
+            Token::RBrace | Token::RBracket | Token::RCurlyBrace => depth -= 1,
+            Token::Comma | Token::Semicolon if depth == 0 => break,
+            Token::QuestionMark if depth == 0 => open_ternaries += 1,
+            Token::Colon if depth == 0 && open_ternaries == 0 => break,
+            Token::Colon if depth == 0 => open_ternaries -= 1,
+            _ => {}
+        }
+        body.push((token.clone(), span.clone()));
+        tokens.next();
+    }
+
+    Ok(Node {
+        children: vec![
+            tokens_to_operator_tree(binding)?,
+            tokens_to_operator_tree(body)?,
+        ],
+        operator: Box::new(Let::new(identifier)),
+    })
+}
+
+/// Builds the node of a block, whose opening curly brace at `open_span` was already consumed from `tokens`.
+/// The block is consumed up to and including its closing curly brace.
+/// Empty blocks evaluate to null.
+fn block_to_operator_tree(
+    open_span: &Span,
+    tokens: &mut Peekable<Iter<(Token, Span)>>,
+) -> Result<Node, Error> {
+    let mut block = Vec::new();
+    let mut depth = 0usize;
+    for (token, span) in tokens {
+        match token {
+            Token::LCurlyBrace => depth += 1,
+            Token::RCurlyBrace if depth == 0 => {
+                return if block.is_empty() {
+                    Ok(Node::from_value(Value::Null))
+                } else {
+                    tokens_to_operator_tree(block)
+                };
+            }
+            Token::RCurlyBrace => depth -= 1,
+            _ => {}
+        }
+        block.push((token.clone(), span.clone()));
+    }
+
+    Err(Error::UnmatchedLCurlyBrace {
+        span: Some(open_span.clone()),
+    })
+}
+
+/// The configuration an expression is evaluated with.
+/// Only mutable configurations allow assignments.
+enum EvaluationConfiguration<'a> {
+    Immutable(&'a dyn Configuration),
+    Mutable(&'a mut dyn MutableConfiguration),
+    /// An immutable configuration that can be shared between threads, such that independent operands can be evaluated in parallel.
+    #[cfg(feature = "rayon")]
+    Parallel(&'a (dyn Configuration + Sync)),
+}
+
+impl<'a> EvaluationConfiguration<'a> {
+    fn get(&self) -> &dyn Configuration {
+        match self {
+            EvaluationConfiguration::Immutable(configuration) => *configuration,
+            EvaluationConfiguration::Mutable(configuration) => &**configuration,
+            #[cfg(feature = "rayon")]
+            EvaluationConfiguration::Parallel(configuration) => *configuration,
+        }
+    }
+
+    fn push_scope(&mut self) {
+        if let EvaluationConfiguration::Mutable(configuration) = self {
+            configuration.push_scope();
+        }
+    }
+
+    fn pop_scope(&mut self) {
+        if let EvaluationConfiguration::Mutable(configuration) = self {
+            configuration.pop_scope();
+        }
+    }
+
+    fn set_variable(&mut self, identifier: &str, value: Value) -> Result<(), Error> {
+        match self {
+            EvaluationConfiguration::Immutable(_) => Err(Error::ImmutableConfiguration),
+            #[cfg(feature = "rayon")]
+            EvaluationConfiguration::Parallel(_) => Err(Error::ImmutableConfiguration),
+            EvaluationConfiguration::Mutable(configuration) => {
+                configuration.set_variable(identifier, value);
+                Ok(())
+            }
         }
     }
 }
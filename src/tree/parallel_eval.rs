@@ -0,0 +1,85 @@
+use configuration::Configuration;
+use error::Error;
+use tree::{EvaluationConfiguration, Node};
+use value::Value;
+
+impl Node {
+    /// Evaluates this expression like `eval`, but evaluates the two operands of binary operators in parallel with [rayon](https://crates.io/crates/rayon) if neither of them is a leaf.
+    /// For example, in `compute_a() + compute_b()` both functions are called in parallel.
+    ///
+    /// Since the configuration is immutable, the operands cannot depend on each other through assignments.
+    /// However, functions may be called concurrently and in a different order than with `eval`, and both operands are evaluated even if the left one fails.
+    /// If both operands fail, the error of the left one is returned.
+    pub fn eval_parallel(
+        &self,
+        configuration: &(dyn Configuration + Sync),
+    ) -> Result<Value, Error> {
+        self.expect_node_count(configuration)?;
+        self.eval_observed(
+            &mut EvaluationConfiguration::Parallel(configuration),
+            1,
+            &mut |_, _| {},
+        )
+    }
+
+    /// Evaluates the operands of this node, which is at the given `depth` of the tree, in parallel if the configuration can be shared between threads and the operands can be evaluated in parallel.
+    /// Returns `None` if the operands have to be evaluated sequentially.
+    #[inline(never)]
+    pub(super) fn eval_parallel_operands(
+        &self,
+        configuration: &EvaluationConfiguration,
+        depth: usize,
+    ) -> Option<Result<Vec<Value>, Error>> {
+        match configuration {
+            EvaluationConfiguration::Parallel(configuration) => {
+                let (left, right) = self.parallel_operands()?;
+                Some(eval_operands(left, right, *configuration, depth + 1))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the two operands of this node if they can be evaluated in parallel.
+    /// This is the case for the operands of binary operators that are evaluated eagerly, if neither of them is a leaf.
+    /// Leaves are not worth the overhead of evaluating them on another thread.
+    fn parallel_operands(&self) -> Option<(&Node, &Node)> {
+        let operator = self.operator();
+        if operator.function_identifier().is_some() || operator.is_comma() {
+            return None;
+        }
+        match self.children() {
+            [left, right]
+                if !left.operator().is_leaf()
+                    && !right.operator().is_leaf()
+                    && !left.operator().is_spread()
+                    && !right.operator().is_spread() =>
+            {
+                Some((left, right))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Evaluates the given operands in parallel, which are at the given `depth` of the tree.
+fn eval_operands(
+    left: &Node,
+    right: &Node,
+    configuration: &(dyn Configuration + Sync),
+    depth: usize,
+) -> Result<Vec<Value>, Error> {
+    // Rayon does not propagate the current span to other threads
+    #[cfg(feature = "tracing")]
+    let span = tracing::Span::current();
+    let eval = |node: &Node| {
+        #[cfg(feature = "tracing")]
+        let _entered = span.enter();
+        node.eval_observed(
+            &mut EvaluationConfiguration::Parallel(configuration),
+            depth,
+            &mut |_, _| {},
+        )
+    };
+    let (left, right) = rayon::join(|| eval(left), || eval(right));
+    Ok(vec![left?, right?])
+}
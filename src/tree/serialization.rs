@@ -0,0 +1,87 @@
+use operator::SerializedOperator;
+use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+use std::vec::Vec;
+
+use tree::Node;
+
+/// The version of the serialization format of operator trees.
+/// It must be increased whenever the format changes in a way that makes previously serialized trees unreadable.
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct SerializedNode {
+    operator: SerializedOperator,
+    children: Vec<SerializedNode>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct VersionedNode {
+    version: u32,
+    node: SerializedNode,
+}
+
+impl SerializedNode {
+    fn new(node: &Node) -> Option<Self> {
+        Some(SerializedNode {
+            operator: SerializedOperator::new(node.operator())?,
+            children: node
+                .children
+                .iter()
+                .map(SerializedNode::new)
+                .collect::<Option<_>>()?,
+        })
+    }
+
+    /// Converts this node back to a node of an operator tree.
+    /// Fails if a node has an amount of children that its operator does not accept.
+    fn into_node(self) -> Result<Node, &'static str> {
+        let operator = self.operator.into_operator();
+        let children = self
+            .children
+            .into_iter()
+            .map(SerializedNode::into_node)
+            .collect::<Result<Vec<_>, _>>()?;
+        let is_valid = if operator.is_root_node() {
+            // The root node of a call without arguments has no children
+            children.len() <= 1
+        } else if operator.is_comma() {
+            children.len() >= 2
+        } else {
+            children.len() == operator.argument_amount()
+        };
+
+        if is_valid {
+            Ok(Node { children, operator })
+        } else {
+            Err("a node has the wrong amount of children for its operator")
+        }
+    }
+}
+
+/// Serializes the operator tree together with the version of the format.
+/// Function calls are serialized by the identifier of the function, which is looked up when the deserialized tree is evaluated.
+impl Serialize for Node {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let node = SerializedNode::new(self)
+            .ok_or_else(|| ser::Error::custom("the operator tree contains an unknown operator"))?;
+        VersionedNode {
+            version: FORMAT_VERSION,
+            node,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserializes an operator tree that was serialized with the same version of the format.
+impl<'de> Deserialize<'de> for Node {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let VersionedNode { version, node } = VersionedNode::deserialize(deserializer)?;
+        if version != FORMAT_VERSION {
+            return Err(de::Error::custom(format!(
+                "unsupported format version {}, expected {}",
+                version, FORMAT_VERSION
+            )));
+        }
+        node.into_node().map_err(de::Error::custom)
+    }
+}
@@ -1,43 +1,634 @@
 use error::Error;
+use indexmap::IndexMap;
+use math;
+#[cfg(feature = "serde")]
+use serde::{
+    de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor},
+    ser::{Serialize, SerializeMap, Serializer},
+};
+use std::{
+    cmp::Ordering,
+    convert::TryFrom,
+    fmt,
+    hash::{BuildHasherDefault, Hash, Hasher},
+    mem,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+mod ops;
 
 pub type IntType = i64;
 pub type FloatType = f64;
 
+/// The map of `Value::Object`.
+/// Without the `std` feature there is no randomly seeded hasher, so the keys are hashed with FNV-1a.
+/// The same hasher is used with the `std` feature, such that the type does not depend on the features.
+pub type ObjectMap = IndexMap<String, Value, BuildHasherDefault<FnvHasher>>;
+
+/// The 64 bit FNV-1a hash function.
+pub struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        FnvHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Value {
     String(String),
     Float(FloatType),
     Int(IntType),
     Boolean(bool),
+    /// An ordered list of values.
+    Array(Vec<Value>),
+    /// A fixed-size group of values, written as `(a, b)` in expressions.
+    Tuple(Vec<Value>),
+    /// A map from keys to values that preserves the insertion order of its keys.
+    Object(ObjectMap),
+    /// The absence of a value.
+    Null,
 }
 
 impl Value {
     pub fn is_int(&self) -> bool {
+        matches!(self, Value::Int(_))
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, Value::Float(_))
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self, Value::Array(_))
+    }
+
+    pub fn is_tuple(&self) -> bool {
+        matches!(self, Value::Tuple(_))
+    }
+
+    pub fn is_object(&self) -> bool {
+        matches!(self, Value::Object(_))
+    }
+
+    /// Returns the contained integer, or `None` if this is not an integer.
+    pub fn as_int(&self) -> Option<IntType> {
         match self {
-            Value::Int(_) => true,
-            _ => false,
+            Value::Int(i) => Some(*i),
+            _ => None,
         }
     }
 
-    pub fn is_float(&self) -> bool {
+    /// Returns the contained number as float, or `None` if this is not a number.
+    /// Integers are converted to floats.
+    pub fn as_float(&self) -> Option<FloatType> {
+        match self {
+            Value::Float(f) => Some(*f),
+            Value::Int(i) => Some(*i as FloatType),
+            _ => None,
+        }
+    }
+
+    /// Returns the contained boolean, or `None` if this is not a boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the contained string, or `None` if this is not a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the contained values, or `None` if this is not an array.
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the contained values, or `None` if this is not a tuple.
+    pub fn as_tuple(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Tuple(tuple) => Some(tuple),
+            _ => None,
+        }
+    }
+
+    /// Returns a reference to the contained map, or `None` if this is not an object.
+    pub fn as_object(&self) -> Option<&ObjectMap> {
+        match self {
+            Value::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    /// Returns the contained boolean, or `Error::ExpectedBoolean` if this is not a boolean.
+    pub fn into_bool(self) -> Result<bool, Error> {
+        match self {
+            Value::Boolean(boolean) => Ok(boolean),
+            value => Err(Error::expected_boolean(value)),
+        }
+    }
+
+    /// Returns the contained integer, or `Error::ExpectedInt` if this is not an integer.
+    pub fn into_int(self) -> Result<IntType, Error> {
+        match self {
+            Value::Int(int) => Ok(int),
+            value => Err(Error::expected_int(value)),
+        }
+    }
+
+    /// Returns the contained float, or `Error::ExpectedFloat` if this is not a float.
+    /// Integers are not converted.
+    pub fn into_float(self) -> Result<FloatType, Error> {
+        match self {
+            Value::Float(float) => Ok(float),
+            value => Err(Error::expected_float(value)),
+        }
+    }
+
+    /// Returns the contained string, or `Error::ExpectedString` if this is not a string.
+    pub fn into_string(self) -> Result<String, Error> {
+        match self {
+            Value::String(string) => Ok(string),
+            value => Err(Error::expected_string(value)),
+        }
+    }
+
+    /// Returns the contained values, or `Error::ExpectedArray` if this is not an array.
+    pub fn into_array(self) -> Result<Vec<Value>, Error> {
+        match self {
+            Value::Array(array) => Ok(array),
+            value => Err(Error::expected_array(value)),
+        }
+    }
+
+    /// Returns the contained map, or `Error::ExpectedObject` if this is not an object.
+    pub fn into_object(self) -> Result<ObjectMap, Error> {
+        match self {
+            Value::Object(object) => Ok(object),
+            value => Err(Error::expected_object(value)),
+        }
+    }
+}
+
+impl From<IntType> for Value {
+    fn from(int: IntType) -> Self {
+        Value::Int(int)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(int: i32) -> Self {
+        Value::Int(IntType::from(int))
+    }
+}
+
+impl From<FloatType> for Value {
+    fn from(float: FloatType) -> Self {
+        Value::Float(float)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(float: f32) -> Self {
+        Value::Float(FloatType::from(float))
+    }
+}
+
+impl From<bool> for Value {
+    fn from(boolean: bool) -> Self {
+        Value::Boolean(boolean)
+    }
+}
+
+impl From<String> for Value {
+    fn from(string: String) -> Self {
+        Value::String(string)
+    }
+}
+
+impl<'a> From<&'a str> for Value {
+    fn from(string: &'a str) -> Self {
+        Value::String(string.to_string())
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(array: Vec<Value>) -> Self {
+        Value::Array(array)
+    }
+}
+
+impl TryFrom<Value> for IntType {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.into_int()
+    }
+}
+
+impl TryFrom<Value> for FloatType {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.into_float()
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.into_bool()
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value.into_string()
+    }
+}
+
+/// Values are ordered like by `Value::total_cmp`, except that values that are not equal by `PartialEq` are never equal by the ordering.
+/// So comparing `NaN` with itself, also inside of arrays, tuples and objects, yields `None`.
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.total_cmp(other) {
+            Ordering::Equal if self != other => None,
+            ordering => Some(ordering),
+        }
+    }
+}
+
+impl Value {
+    /// Compares this value with the other value by a total order.
+    /// Values of different types are ordered by their type, in the order `Int < Float < Boolean < String < Array < Tuple < Object < Null`.
+    /// Values of the same type are ordered naturally, where strings, arrays and tuples are compared lexicographically.
+    /// Objects are compared lexicographically by their entries sorted by key.
+    /// `NaN` is greater than all other floats and equal to itself, such that values are equal by this order if they are the same by `Value::deep_equal`.
+    pub fn total_cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => a.cmp(b),
+            (Value::Float(a), Value::Float(b)) => compare_floats(*a, *b),
+            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Array(a), Value::Array(b)) | (Value::Tuple(a), Value::Tuple(b)) => {
+                let orderings = a.iter().zip(b).map(|(a, b)| a.total_cmp(b));
+                compare_lexicographically(orderings, a.len(), b.len())
+            }
+            (Value::Object(a), Value::Object(b)) => {
+                let (a, b) = (sorted_entries(a), sorted_entries(b));
+                let orderings = a
+                    .iter()
+                    .zip(&b)
+                    .map(|((a_key, a), (b_key, b))| a_key.cmp(b_key).then_with(|| a.total_cmp(b)));
+                compare_lexicographically(orderings, a.len(), b.len())
+            }
+            (Value::Null, Value::Null) => Ordering::Equal,
+            _ => self.type_rank().cmp(&other.type_rank()),
+        }
+    }
+
+    /// True if this value is the same as the other value.
+    /// Unlike `PartialEq`, `NaN` is the same as `NaN`, also inside of arrays, tuples and objects, such that values can be deduplicated.
+    /// Like `PartialEq`, `0.0` is the same as `-0.0`, and objects are compared regardless of their key order.
+    pub fn deep_equal(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Float(a), Value::Float(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (Value::Array(a), Value::Array(b)) | (Value::Tuple(a), Value::Tuple(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.deep_equal(b))
+            }
+            (Value::Object(a), Value::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(key, a)| b.get(key).is_some_and(|b| a.deep_equal(b)))
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// The position of the type of this value in the ordering of values of different types.
+    fn type_rank(&self) -> u8 {
+        match self {
+            Value::Int(_) => 0,
+            Value::Float(_) => 1,
+            Value::Boolean(_) => 2,
+            Value::String(_) => 3,
+            Value::Array(_) => 4,
+            Value::Tuple(_) => 5,
+            Value::Object(_) => 6,
+            Value::Null => 7,
+        }
+    }
+}
+
+/// Hashes values consistently with `PartialEq` and `Value::deep_equal`.
+/// Floats are hashed by their bit pattern, where `-0.0` is hashed like `0.0` and all `NaN`s are hashed alike.
+/// Objects are hashed regardless of their key order.
+/// Since `NaN` is not equal to itself, values are used as keys of maps and sets through `ValueKey`.
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        mem::discriminant(self).hash(state);
+        match self {
+            Value::String(string) => string.hash(state),
+            Value::Float(float) => {
+                let float = if *float == 0.0 {
+                    0.0
+                } else if float.is_nan() {
+                    FloatType::NAN
+                } else {
+                    *float
+                };
+                float.to_bits().hash(state)
+            }
+            Value::Int(int) => int.hash(state),
+            Value::Boolean(boolean) => boolean.hash(state),
+            Value::Array(array) | Value::Tuple(array) => array.hash(state),
+            Value::Object(object) => sorted_entries(object).hash(state),
+            Value::Null => {}
+        }
+    }
+}
+
+/// A value that can be used as a key of hash maps, B-tree maps and their sets.
+/// Keys are equal if their values are the same by `Value::deep_equal`, such that `NaN` keys can be found again,
+/// and they are ordered by `Value::total_cmp`.
+#[derive(Clone, Debug)]
+pub struct ValueKey(pub Value);
+
+impl From<Value> for ValueKey {
+    fn from(value: Value) -> Self {
+        ValueKey(value)
+    }
+}
+
+impl PartialEq for ValueKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.deep_equal(&other.0)
+    }
+}
+
+impl Eq for ValueKey {}
+
+impl PartialOrd for ValueKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ValueKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for ValueKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+/// Returns the entries of the object sorted by key, such that objects that are equal regardless of their key order compare as equal.
+fn sorted_entries(object: &ObjectMap) -> Vec<(&String, &Value)> {
+    let mut entries: Vec<_> = object.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+/// Compares two sequences by the first of the orderings of their elements that is not equal, or by their lengths if there is none.
+fn compare_lexicographically(
+    mut orderings: impl Iterator<Item = Ordering>,
+    a_len: usize,
+    b_len: usize,
+) -> Ordering {
+    orderings
+        .find(|ordering| *ordering != Ordering::Equal)
+        .unwrap_or_else(|| a_len.cmp(&b_len))
+}
+
+fn compare_floats(a: FloatType, b: FloatType) -> Ordering {
+    a.partial_cmp(&b)
+        .unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()))
+}
+
+/// Formats values in a form that is readable for users that are not familiar with Rust.
+/// Strings are enclosed in double quotes, and arrays and objects are formatted recursively.
+/// Integral floats end with `.0` to distinguish them from integers.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::String(string) => write!(f, "\"{}\"", string),
+            Value::Float(float) if float.is_finite() && math::floor(*float) == *float => {
+                write!(f, "{}.0", float)
+            }
+            Value::Float(float) => write!(f, "{}", float),
+            Value::Int(int) => write!(f, "{}", int),
+            Value::Boolean(boolean) => write!(f, "{}", boolean),
+            Value::Array(array) => {
+                write!(f, "[")?;
+                for (index, value) in array.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            Value::Tuple(tuple) => write_tuple(tuple, f, |value, f| write!(f, "{}", value)),
+            Value::Object(object) => {
+                write!(f, "{{")?;
+                for (index, (key, value)) in object.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "\"{}\": {}", key, value)?;
+                }
+                write!(f, "}}")
+            }
+            Value::Null => write!(f, "null"),
+        }
+    }
+}
+
+/// The type of a value, as declared for the arguments and results of a `TypedFunction`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValueType {
+    Int,
+    Float,
+    Boolean,
+    String,
+    /// Arrays, but not tuples.
+    Array,
+    /// Values of any type.
+    Any,
+}
+
+impl ValueType {
+    /// True if the value is of this type.
+    /// Types are matched exactly, such that an integer is not a float.
+    pub fn matches(self, value: &Value) -> bool {
         match self {
-            Value::Float(_) => true,
-            _ => false,
+            ValueType::Int => matches!(value, Value::Int(_)),
+            ValueType::Float => matches!(value, Value::Float(_)),
+            ValueType::Boolean => matches!(value, Value::Boolean(_)),
+            ValueType::String => matches!(value, Value::String(_)),
+            ValueType::Array => matches!(value, Value::Array(_)),
+            ValueType::Any => true,
         }
     }
+}
 
-    pub fn as_int(&self) -> Result<IntType, Error> {
+/// Formats the type like the builtin function `type_of`, such as `int`.
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Value::Int(i) => Ok(*i),
-            _ => Err(Error::TypeError),
+            ValueType::Int => write!(f, "int"),
+            ValueType::Float => write!(f, "float"),
+            ValueType::Boolean => write!(f, "boolean"),
+            ValueType::String => write!(f, "string"),
+            ValueType::Array => write!(f, "array"),
+            ValueType::Any => write!(f, "any"),
         }
     }
+}
+
+/// Writes the tuple in braces, with a trailing comma if it has only one element, such as `(1,)`.
+pub(crate) fn write_tuple(
+    tuple: &[Value],
+    f: &mut fmt::Formatter,
+    write_element: fn(&Value, &mut fmt::Formatter) -> fmt::Result,
+) -> fmt::Result {
+    write!(f, "(")?;
+    for (index, value) in tuple.iter().enumerate() {
+        if index > 0 {
+            write!(f, ", ")?;
+        }
+        write_element(value, f)?;
+    }
+    if tuple.len() == 1 {
+        write!(f, ",")?;
+    }
+    write!(f, ")")
+}
 
-    pub fn as_float(&self) -> Result<FloatType, Error> {
+#[cfg(feature = "serde")]
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         match self {
-            Value::Float(f) => Ok(*f),
-            Value::Int(i) => Ok(*i as FloatType),
-            _ => Err(Error::TypeError),
+            Value::String(string) => serializer.serialize_str(string),
+            Value::Float(float) => serializer.serialize_f64(*float),
+            Value::Int(int) => serializer.serialize_i64(*int),
+            Value::Boolean(boolean) => serializer.serialize_bool(*boolean),
+            Value::Array(array) => array.serialize(serializer),
+            // Tuples are deserialized as arrays
+            Value::Tuple(tuple) => tuple.serialize(serializer),
+            Value::Object(object) => {
+                let mut map = serializer.serialize_map(Some(object.len()))?;
+                for (key, value) in object {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            Value::Null => serializer.serialize_unit(),
         }
     }
 }
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct ValueVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string, number, boolean, sequence, map or null")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Ok(Value::Float(v))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(Value::Int(v))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        if v <= IntType::MAX as u64 {
+            Ok(Value::Int(v as IntType))
+        } else {
+            Err(E::invalid_value(de::Unexpected::Unsigned(v), &self))
+        }
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(Value::Boolean(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut array = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element()? {
+            array.push(value);
+        }
+        Ok(Value::Array(array))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut object =
+            ObjectMap::with_capacity_and_hasher(map.size_hint().unwrap_or(0), Default::default());
+        while let Some((key, value)) = map.next_entry()? {
+            object.insert(key, value);
+        }
+        Ok(Value::Object(object))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(Value::Null)
+    }
+}
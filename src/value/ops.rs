@@ -0,0 +1,80 @@
+use configuration::EmptyConfiguration;
+use error::Error;
+use operator::{Add, Div, Mod, Mul, Neg, Not, Operator, Sub};
+use std::ops;
+
+use value::Value;
+
+impl Value {
+    /// Adds the given value to this value like `+` in expressions, such that numbers are added and strings are concatenated.
+    pub fn checked_add(self, other: Value) -> Result<Value, Error> {
+        Add.eval(&[self, other], &EmptyConfiguration)
+    }
+
+    /// Subtracts the given value from this value like `-` in expressions.
+    pub fn checked_sub(self, other: Value) -> Result<Value, Error> {
+        Sub.eval(&[self, other], &EmptyConfiguration)
+    }
+
+    /// Multiplies this value with the given value like `*` in expressions, such that strings can be repeated.
+    pub fn checked_mul(self, other: Value) -> Result<Value, Error> {
+        Mul.eval(&[self, other], &EmptyConfiguration)
+    }
+
+    /// Divides this value by the given value like `/` in expressions.
+    pub fn checked_div(self, other: Value) -> Result<Value, Error> {
+        Div.eval(&[self, other], &EmptyConfiguration)
+    }
+
+    /// Returns the remainder of the division of this value by the given value like `%` in expressions.
+    pub fn checked_rem(self, other: Value) -> Result<Value, Error> {
+        Mod.eval(&[self, other], &EmptyConfiguration)
+    }
+
+    /// Negates this number like the unary `-` in expressions.
+    pub fn checked_neg(self) -> Result<Value, Error> {
+        Neg.eval(&[self], &EmptyConfiguration)
+    }
+
+    /// Negates this boolean like `!` in expressions.
+    pub fn checked_not(self) -> Result<Value, Error> {
+        Not.eval(&[self], &EmptyConfiguration)
+    }
+}
+
+/// Implements a binary operator trait for values by calling the given checked method, which panics on errors.
+macro_rules! impl_binary_operator {
+    ($trait:ident, $method:ident, $checked:ident) => {
+        impl ops::$trait for Value {
+            type Output = Value;
+
+            /// Panics if the operator is not defined for the operands or fails, such as on overflow.
+            fn $method(self, other: Value) -> Value {
+                self.$checked(other)
+                    .unwrap_or_else(|error| panic!("{}", error))
+            }
+        }
+    };
+}
+
+/// Implements a unary operator trait for values by calling the given checked method, which panics on errors.
+macro_rules! impl_unary_operator {
+    ($trait:ident, $method:ident, $checked:ident) => {
+        impl ops::$trait for Value {
+            type Output = Value;
+
+            /// Panics if the operator is not defined for the operand or fails, such as on overflow.
+            fn $method(self) -> Value {
+                self.$checked().unwrap_or_else(|error| panic!("{}", error))
+            }
+        }
+    };
+}
+
+impl_binary_operator!(Add, add, checked_add);
+impl_binary_operator!(Sub, sub, checked_sub);
+impl_binary_operator!(Mul, mul, checked_mul);
+impl_binary_operator!(Div, div, checked_div);
+impl_binary_operator!(Rem, rem, checked_rem);
+impl_unary_operator!(Neg, neg, checked_neg);
+impl_unary_operator!(Not, not, checked_not);
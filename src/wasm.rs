@@ -0,0 +1,119 @@
+//! Bindings for JavaScript, enabled by the `wasm` feature.
+
+use std::convert::TryFrom;
+
+use js_sys::{Array, Number, Object, Reflect};
+use wasm_bindgen::{prelude::*, JsCast};
+
+use configuration::HashMapConfiguration;
+use error::Error;
+use value::{ObjectMap, Value};
+
+/// Evaluates the given expression and returns its value.
+/// Errors are thrown as JavaScript `Error`s.
+#[wasm_bindgen(js_name = eval)]
+pub fn eval_js(string: &str) -> Result<JsValue, JsValue> {
+    Ok(::eval(string)?.into())
+}
+
+/// Evaluates the given expression with the properties of the given object as variables, and returns its value.
+/// Errors are thrown as JavaScript `Error`s.
+#[wasm_bindgen(js_name = evalWithConfiguration)]
+pub fn eval_with_configuration_js(string: &str, variables: JsValue) -> Result<JsValue, JsValue> {
+    let configuration = HashMapConfiguration::try_from(variables)?;
+    Ok(::eval_with_configuration(string, &configuration)?.into())
+}
+
+/// Converts numbers to JavaScript numbers, arrays and tuples to JavaScript arrays, objects to plain JavaScript objects and `Value::Null` to `null`.
+impl From<Value> for JsValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::String(string) => JsValue::from_str(&string),
+            Value::Float(float) => JsValue::from_f64(float),
+            Value::Int(int) => JsValue::from_f64(int as f64),
+            Value::Boolean(boolean) => JsValue::from_bool(boolean),
+            Value::Array(array) | Value::Tuple(array) => array
+                .into_iter()
+                .map(JsValue::from)
+                .collect::<Array>()
+                .into(),
+            Value::Object(object) => {
+                let result = Object::new();
+                for (key, value) in object {
+                    Reflect::set(&result, &JsValue::from_str(&key), &value.into())
+                        .expect("setting a property of a plain object does not fail");
+                }
+                result.into()
+            }
+            Value::Null => JsValue::NULL,
+        }
+    }
+}
+
+/// Converts JavaScript numbers that are safe integers to `Value::Int` and other numbers to `Value::Float`.
+/// `null` and `undefined` are converted to `Value::Null`, and arrays and plain objects are converted recursively.
+/// Other values, such as functions, result in `Error::TypeError`.
+impl TryFrom<JsValue> for Value {
+    type Error = Error;
+
+    fn try_from(value: JsValue) -> Result<Self, Self::Error> {
+        if value.is_null() || value.is_undefined() {
+            Ok(Value::Null)
+        } else if let Some(boolean) = value.as_bool() {
+            Ok(Value::Boolean(boolean))
+        } else if let Some(float) = value.as_f64() {
+            if Number::is_safe_integer(&value) {
+                Ok(Value::Int(float as i64))
+            } else {
+                Ok(Value::Float(float))
+            }
+        } else if let Some(string) = value.as_string() {
+            Ok(Value::String(string))
+        } else if Array::is_array(&value) {
+            Array::from(&value)
+                .iter()
+                .map(Value::try_from)
+                .collect::<Result<_, _>>()
+                .map(Value::Array)
+        } else if value.is_object() && !value.is_function() {
+            object_entries(value.unchecked_ref())
+                .map(|entries| Value::Object(entries.into_iter().collect::<ObjectMap>()))
+        } else {
+            Err(Error::TypeError)
+        }
+    }
+}
+
+/// Creates a configuration whose variables are the properties of the given plain JavaScript object.
+impl TryFrom<JsValue> for HashMapConfiguration {
+    type Error = Error;
+
+    fn try_from(value: JsValue) -> Result<Self, Self::Error> {
+        if !value.is_object() || Array::is_array(&value) {
+            return Err(Error::TypeError);
+        }
+        let mut configuration = HashMapConfiguration::new();
+        for (identifier, value) in object_entries(value.unchecked_ref())? {
+            configuration.insert_variable(identifier, value);
+        }
+        Ok(configuration)
+    }
+}
+
+/// Converts errors to JavaScript `Error`s with the message of the error.
+impl From<Error> for JsValue {
+    fn from(error: Error) -> Self {
+        js_sys::Error::new(&error.to_string()).into()
+    }
+}
+
+fn object_entries(object: &Object) -> Result<Vec<(String, Value)>, Error> {
+    Object::entries(object)
+        .iter()
+        .map(|entry| {
+            let entry = Array::from(&entry);
+            let key = entry.get(0).as_string().ok_or(Error::TypeError)?;
+            Ok((key, Value::try_from(entry.get(1))?))
+        })
+        .collect()
+}